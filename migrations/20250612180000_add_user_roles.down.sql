@@ -0,0 +1,9 @@
+DROP INDEX IF EXISTS properties_owner_id_idx;
+
+ALTER TABLE properties
+DROP COLUMN owner_id;
+
+ALTER TABLE users
+DROP COLUMN role;
+
+DROP TYPE IF EXISTS user_role;
@@ -0,0 +1,11 @@
+-- Roles used for route-level authorization
+CREATE TYPE user_role AS ENUM ('admin', 'landlord', 'tenant');
+
+ALTER TABLE users
+ADD COLUMN role user_role NOT NULL DEFAULT 'tenant';
+
+-- Track which landlord owns each property
+ALTER TABLE properties
+ADD COLUMN owner_id UUID REFERENCES users(id);
+
+CREATE INDEX properties_owner_id_idx ON properties (owner_id);
@@ -0,0 +1,15 @@
+-- Long-lived refresh tokens. Only a SHA-256 hash of each token is stored.
+-- Every rotation stays in the same family so a reused token can revoke the whole chain.
+CREATE TABLE refresh_tokens (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    family_id UUID NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ,    -- Set once the token has been rotated
+    revoked_at TIMESTAMPTZ, -- Set on logout or reuse detection
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX refresh_tokens_user_id_idx ON refresh_tokens (user_id);
+CREATE INDEX refresh_tokens_family_id_idx ON refresh_tokens (family_id);
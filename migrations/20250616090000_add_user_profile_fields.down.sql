@@ -0,0 +1,6 @@
+DROP INDEX IF EXISTS users_email_lower_key;
+
+ALTER TABLE users
+DROP COLUMN email,
+DROP COLUMN full_name,
+DROP COLUMN phone;
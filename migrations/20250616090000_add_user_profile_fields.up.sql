@@ -0,0 +1,7 @@
+ALTER TABLE users
+ADD COLUMN email TEXT,
+ADD COLUMN full_name TEXT,
+ADD COLUMN phone TEXT;
+
+-- Emails are unique regardless of case; this index also serves login-by-email lookups
+CREATE UNIQUE INDEX users_email_lower_key ON users (LOWER(email));
@@ -0,0 +1,5 @@
+DROP TABLE IF EXISTS user_tokens;
+DROP TYPE IF EXISTS user_token_purpose;
+
+ALTER TABLE users
+DROP COLUMN email_verified_at;
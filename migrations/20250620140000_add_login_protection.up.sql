@@ -0,0 +1,18 @@
+-- Per-account lockout state
+ALTER TABLE users
+ADD COLUMN failed_login_attempts INTEGER NOT NULL DEFAULT 0,
+ADD COLUMN locked_until TIMESTAMPTZ;
+
+-- Audit trail of every login attempt, also used for per-IP and per-identifier throttling
+CREATE TABLE login_attempts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    identifier TEXT NOT NULL, -- Lowercased username or email as submitted
+    user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    ip_address TEXT,
+    succeeded BOOLEAN NOT NULL,
+    failure_reason TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX login_attempts_identifier_created_at_idx ON login_attempts (identifier, created_at);
+CREATE INDEX login_attempts_ip_address_created_at_idx ON login_attempts (ip_address, created_at);
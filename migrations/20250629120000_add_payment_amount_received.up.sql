@@ -0,0 +1,4 @@
+-- How much has been received so far, recorded for partially paid payments and
+-- set to the full amount on completion
+ALTER TABLE payments
+ADD COLUMN amount_received DECIMAL(10, 2);
@@ -0,0 +1,31 @@
+DROP VIEW IF EXISTS property_details;
+
+ALTER TABLE properties
+ADD COLUMN current_rent_amount DECIMAL(10, 2),
+ADD COLUMN current_tenant_id UUID REFERENCES users(id);
+
+-- Keep whoever holds the current lease; roommates beyond the first are lost
+UPDATE properties p
+SET current_rent_amount = l.monthly_rent,
+    current_tenant_id = (
+        SELECT lt.tenant_id FROM lease_tenants lt
+        WHERE lt.lease_id = l.id
+        ORDER BY lt.tenant_id
+        LIMIT 1
+    )
+FROM leases l
+WHERE l.property_id = p.id
+  AND l.status = 'active'
+  AND l.start_date <= CURRENT_DATE
+  AND (l.end_date IS NULL OR l.end_date >= CURRENT_DATE);
+
+UPDATE properties SET current_rent_amount = 0 WHERE current_rent_amount IS NULL;
+
+ALTER TABLE properties
+ALTER COLUMN current_rent_amount SET NOT NULL;
+
+CREATE INDEX properties_current_tenant_id_idx ON properties (current_tenant_id);
+
+DROP TABLE IF EXISTS lease_tenants;
+DROP TABLE IF EXISTS leases;
+DROP TYPE IF EXISTS lease_status;
@@ -0,0 +1,82 @@
+-- Leases replace the single tenant and rent stored on each property, keeping the
+-- history of who rented a property and allowing several tenants per lease.
+CREATE EXTENSION IF NOT EXISTS btree_gist;
+
+CREATE TYPE lease_status AS ENUM ('draft', 'active', 'ended', 'terminated');
+
+CREATE TABLE leases (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    property_id UUID NOT NULL REFERENCES properties(id),
+    start_date DATE NOT NULL,
+    end_date DATE, -- NULL for an open-ended (month-to-month) lease; inclusive otherwise
+    monthly_rent DECIMAL(10, 2) NOT NULL CHECK (monthly_rent > 0),
+    deposit DECIMAL(10, 2) NOT NULL DEFAULT 0 CHECK (deposit >= 0),
+    currency TEXT NOT NULL DEFAULT 'USD',
+    billing_day SMALLINT NOT NULL DEFAULT 1 CHECK (billing_day BETWEEN 1 AND 28), -- Day of the month rent is due
+    status lease_status NOT NULL DEFAULT 'draft',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (end_date IS NULL OR end_date >= start_date),
+    -- A property can only be let once at a time
+    CONSTRAINT leases_no_overlapping_active EXCLUDE USING gist (
+        property_id WITH =,
+        daterange(start_date, end_date, '[]') WITH &&
+    ) WHERE (status = 'active')
+);
+
+CREATE INDEX leases_property_id_idx ON leases (property_id);
+
+CREATE TABLE lease_tenants (
+    lease_id UUID NOT NULL REFERENCES leases(id) ON DELETE CASCADE,
+    tenant_id UUID NOT NULL REFERENCES users(id),
+    PRIMARY KEY (lease_id, tenant_id)
+);
+
+CREATE INDEX lease_tenants_tenant_id_idx ON lease_tenants (tenant_id);
+
+-- Carry existing tenancies over as active leases. When they started is unknown,
+-- so they are taken to have started when the property was added. The rent asked
+-- for a vacant property is not kept; it is set on the property's next lease.
+INSERT INTO leases (property_id, start_date, monthly_rent, status)
+SELECT id, created_at::date, current_rent_amount, 'active'
+FROM properties
+WHERE current_tenant_id IS NOT NULL AND current_rent_amount > 0;
+
+INSERT INTO lease_tenants (lease_id, tenant_id)
+SELECT l.id, p.current_tenant_id
+FROM leases l
+JOIN properties p ON p.id = l.property_id;
+
+ALTER TABLE properties
+DROP COLUMN current_tenant_id,
+DROP COLUMN current_rent_amount;
+
+-- Properties together with the lease in effect today, if any
+CREATE VIEW property_details AS
+SELECT
+    p.id,
+    p.address,
+    p.unit_number,
+    p.owner_id,
+    l.id AS current_lease_id,
+    l.monthly_rent AS current_rent_amount,
+    COALESCE(l.tenant_ids, '{}') AS current_tenant_ids,
+    p.created_at,
+    p.updated_at
+FROM properties p
+LEFT JOIN LATERAL (
+    SELECT
+        leases.id,
+        leases.monthly_rent,
+        ARRAY(
+            SELECT lt.tenant_id FROM lease_tenants lt
+            WHERE lt.lease_id = leases.id
+            ORDER BY lt.tenant_id
+        ) AS tenant_ids
+    FROM leases
+    WHERE leases.property_id = p.id
+      AND leases.status = 'active'
+      AND leases.start_date <= CURRENT_DATE
+      AND (leases.end_date IS NULL OR leases.end_date >= CURRENT_DATE)
+    LIMIT 1
+) l ON TRUE;
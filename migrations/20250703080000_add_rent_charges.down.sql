@@ -0,0 +1,7 @@
+DROP INDEX payments_rent_lease_period_key;
+
+ALTER TABLE payments
+DROP COLUMN lease_id,
+DROP COLUMN kind;
+
+DROP TYPE payment_kind;
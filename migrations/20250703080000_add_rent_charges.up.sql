@@ -0,0 +1,10 @@
+-- Rent charges are generated from active leases by the scheduler; everything else
+-- is recorded by hand through the API.
+CREATE TYPE payment_kind AS ENUM ('manual', 'rent');
+
+ALTER TABLE payments
+ADD COLUMN kind payment_kind NOT NULL DEFAULT 'manual',
+ADD COLUMN lease_id UUID REFERENCES leases(id);
+
+-- One rent charge per lease and period, however often or wherever the scheduler runs
+CREATE UNIQUE INDEX payments_rent_lease_period_key ON payments (lease_id, period_start) WHERE kind = 'rent';
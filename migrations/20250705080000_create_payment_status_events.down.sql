@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS payments_status_due_date_idx;
+DROP TABLE IF EXISTS payment_status_events;
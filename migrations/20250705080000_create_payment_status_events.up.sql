@@ -0,0 +1,15 @@
+-- History of payment status changes: when each happened, who or what made it, and why
+CREATE TABLE payment_status_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    payment_id UUID NOT NULL REFERENCES payments(id) ON DELETE CASCADE,
+    from_status payment_status NOT NULL,
+    to_status payment_status NOT NULL,
+    reason TEXT NOT NULL,
+    changed_by UUID REFERENCES users(id), -- NULL when a background job made the change
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX payment_status_events_payment_id_idx ON payment_status_events (payment_id, created_at);
+
+-- For finding pending payments that have fallen due
+CREATE INDEX payments_status_due_date_idx ON payments (status, due_date);
@@ -0,0 +1,11 @@
+-- Enum values cannot be dropped, so remove the late fees (the only waived payments)
+-- and leave 'late_fee' and 'waived' unused
+DELETE FROM payments WHERE kind = 'late_fee';
+
+DROP INDEX IF EXISTS payments_parent_payment_id_key;
+
+ALTER TABLE payments
+DROP COLUMN parent_payment_id;
+
+DROP TABLE IF EXISTS late_fee_policies;
+DROP TYPE IF EXISTS late_fee_kind;
@@ -0,0 +1,32 @@
+-- How each property charges for rent that is paid late
+CREATE TYPE late_fee_kind AS ENUM ('flat', 'percentage', 'daily');
+
+CREATE TABLE late_fee_policies (
+    property_id UUID PRIMARY KEY REFERENCES properties(id) ON DELETE CASCADE,
+    kind late_fee_kind NOT NULL,
+    amount DECIMAL(10, 2) CHECK (amount > 0),                -- The flat fee, or the fee per day
+    percentage DECIMAL(5, 2) CHECK (percentage > 0 AND percentage <= 100), -- Of the rent
+    grace_days SMALLINT NOT NULL DEFAULT 0 CHECK (grace_days >= 0), -- Days past the due date before a fee applies
+    max_amount DECIMAL(10, 2) CHECK (max_amount > 0),        -- Cap on the fee for one late payment
+    max_percentage DECIMAL(5, 2) CHECK (max_percentage > 0 AND max_percentage <= 100), -- Legal maximum, as a percentage of the rent
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (
+        CASE kind
+            WHEN 'flat' THEN amount IS NOT NULL
+            WHEN 'percentage' THEN percentage IS NOT NULL
+            WHEN 'daily' THEN amount IS NOT NULL AND max_amount IS NOT NULL
+        END
+    )
+);
+
+-- Late fees are payments of their own, linked to the charge that was paid late
+ALTER TYPE payment_kind ADD VALUE IF NOT EXISTS 'late_fee';
+-- Waived late fees are no longer owed; the waiver is kept in the status history
+ALTER TYPE payment_status ADD VALUE IF NOT EXISTS 'waived';
+
+ALTER TABLE payments
+ADD COLUMN parent_payment_id UUID REFERENCES payments(id);
+
+-- One late fee per late charge; a daily fee grows in place
+CREATE UNIQUE INDEX payments_parent_payment_id_key ON payments (parent_payment_id) WHERE parent_payment_id IS NOT NULL;
@@ -0,0 +1,13 @@
+ALTER TABLE payments
+ADD COLUMN amount_received DECIMAL(10, 2);
+
+UPDATE payments
+SET amount_received = amount_paid
+WHERE amount_paid > 0;
+
+ALTER TABLE payments
+DROP COLUMN balance_due,
+DROP COLUMN amount_paid;
+
+DROP TABLE IF EXISTS payment_allocations;
+DROP TABLE IF EXISTS receipts;
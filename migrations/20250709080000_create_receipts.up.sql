@@ -0,0 +1,52 @@
+-- Money received from tenants is recorded as receipts and allocated against the
+-- charges it pays, replacing the single amount received kept on each payment.
+CREATE TABLE receipts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id), -- Who paid
+    property_id UUID REFERENCES properties(id), -- NULL only for receipts carried over below
+    amount DECIMAL(10, 2) NOT NULL CHECK (amount > 0),
+    currency TEXT NOT NULL,
+    received_on DATE NOT NULL DEFAULT CURRENT_DATE,
+    transaction_id TEXT UNIQUE, -- From the payment provider or bank
+    notes TEXT,
+    recorded_by UUID REFERENCES users(id), -- NULL for receipts carried over below
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX receipts_user_id_idx ON receipts (user_id);
+CREATE INDEX receipts_property_id_idx ON receipts (property_id);
+
+CREATE TABLE payment_allocations (
+    receipt_id UUID NOT NULL REFERENCES receipts(id),
+    payment_id UUID NOT NULL REFERENCES payments(id),
+    amount DECIMAL(10, 2) NOT NULL CHECK (amount > 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (receipt_id, payment_id)
+);
+
+CREATE INDEX payment_allocations_payment_id_idx ON payment_allocations (payment_id);
+
+-- Always the sum of the payment's allocations
+ALTER TABLE payments
+ADD COLUMN amount_paid DECIMAL(10, 2) NOT NULL DEFAULT 0 CHECK (amount_paid >= 0);
+
+-- Carry what was received so far over as one receipt per payment, sharing its id
+INSERT INTO receipts (id, user_id, property_id, amount, currency, received_on, transaction_id, notes)
+SELECT id, user_id, property_id, amount_received, currency, updated_at::date, transaction_id,
+       'Recorded before receipts were tracked'
+FROM payments
+WHERE amount_received > 0;
+
+INSERT INTO payment_allocations (receipt_id, payment_id, amount)
+SELECT id, id, LEAST(amount_received, amount)
+FROM payments
+WHERE amount_received > 0;
+
+UPDATE payments
+SET amount_paid = LEAST(amount_received, amount)
+WHERE amount_received > 0;
+
+ALTER TABLE payments
+DROP COLUMN amount_received,
+ADD CHECK (amount_paid <= amount),
+ADD COLUMN balance_due DECIMAL(10, 2) GENERATED ALWAYS AS (amount - amount_paid) STORED;
@@ -0,0 +1,5 @@
+DROP TABLE IF EXISTS journal_lines;
+DROP FUNCTION IF EXISTS check_journal_entry_balanced();
+DROP TABLE IF EXISTS journal_entries;
+DROP TYPE IF EXISTS journal_entry_kind;
+DROP TYPE IF EXISTS ledger_account;
@@ -0,0 +1,94 @@
+-- A double-entry ledger. Every charge, receipt, credit, refund and write-off is a
+-- journal entry whose lines move money between accounts and always balance.
+CREATE TYPE ledger_account AS ENUM (
+    'cash',            -- Money held, from receipts and deposits
+    'rent_receivable', -- What tenants owe; negative when they are in credit
+    'deposits_held',   -- Deposits owed back to tenants
+    'rent_income',
+    'fee_income',      -- Late fees
+    'bad_debt'         -- Debts written off
+);
+
+CREATE TYPE journal_entry_kind AS ENUM (
+    'charge', 'receipt', 'credit', 'refund', 'write_off', 'deposit_received', 'deposit_returned'
+);
+
+CREATE TABLE journal_entries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    kind journal_entry_kind NOT NULL,
+    user_id UUID NOT NULL REFERENCES users(id), -- The tenant the entry concerns
+    property_id UUID REFERENCES properties(id), -- NULL only for receipts carried over without one
+    currency TEXT NOT NULL,
+    entry_date DATE NOT NULL,
+    memo TEXT,
+    payment_id UUID REFERENCES payments(id), -- The charge posted, credited or written off
+    receipt_id UUID REFERENCES receipts(id),
+    created_by UUID REFERENCES users(id),    -- Who recorded the receipt, waiver or manual entry
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX journal_entries_user_id_entry_date_idx ON journal_entries (user_id, entry_date);
+CREATE INDEX journal_entries_property_id_entry_date_idx ON journal_entries (property_id, entry_date);
+
+CREATE TABLE journal_lines (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    entry_id UUID NOT NULL REFERENCES journal_entries(id),
+    account ledger_account NOT NULL,
+    debit DECIMAL(12, 2) NOT NULL DEFAULT 0 CHECK (debit >= 0),
+    credit DECIMAL(12, 2) NOT NULL DEFAULT 0 CHECK (credit >= 0),
+    CHECK ((debit = 0) <> (credit = 0)) -- Each line is either a debit or a credit
+);
+
+CREATE INDEX journal_lines_entry_id_idx ON journal_lines (entry_id);
+
+-- Checked when the transaction commits, once all of an entry's lines are in
+CREATE FUNCTION check_journal_entry_balanced() RETURNS trigger AS $$
+BEGIN
+    IF (SELECT SUM(debit) <> SUM(credit) FROM journal_lines WHERE entry_id = NEW.entry_id) THEN
+        RAISE EXCEPTION 'Journal entry % does not balance', NEW.entry_id
+            USING ERRCODE = 'check_violation';
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE CONSTRAINT TRIGGER journal_lines_balanced
+AFTER INSERT OR UPDATE ON journal_lines
+DEFERRABLE INITIALLY DEFERRED
+FOR EACH ROW EXECUTE FUNCTION check_journal_entry_balanced();
+
+-- Post what happened before the ledger existed: every charge when it fell due, and
+-- every receipt when it was received
+CREATE TEMPORARY TABLE ledger_backfill AS
+SELECT gen_random_uuid() AS entry_id, 'charge'::journal_entry_kind AS kind,
+       p.user_id, p.property_id, p.currency, COALESCE(p.due_date, p.created_at::date) AS entry_date,
+       p.notes AS memo, p.id AS payment_id, NULL::uuid AS receipt_id, p.amount,
+       'rent_receivable'::ledger_account AS debit_account,
+       CASE p.kind WHEN 'late_fee' THEN 'fee_income' ELSE 'rent_income' END::ledger_account AS credit_account
+FROM payments p
+UNION ALL
+-- Waived and failed charges are credited with what was still due
+SELECT gen_random_uuid(), 'credit', p.user_id, p.property_id, p.currency, p.updated_at::date,
+       CASE p.status WHEN 'waived' THEN 'Late fee waived' ELSE 'Payment failed' END, p.id, NULL,
+       p.balance_due,
+       CASE p.kind WHEN 'late_fee' THEN 'fee_income' ELSE 'rent_income' END::ledger_account,
+       'rent_receivable'
+FROM payments p
+WHERE p.status IN ('waived', 'failed') AND p.balance_due > 0
+UNION ALL
+SELECT gen_random_uuid(), 'receipt', r.user_id,
+       COALESCE(r.property_id, (SELECT property_id FROM payments WHERE id = r.id)),
+       r.currency, r.received_on, r.notes, NULL, r.id, r.amount, 'cash', 'rent_receivable'
+FROM receipts r;
+
+INSERT INTO journal_entries (id, kind, user_id, property_id, currency, entry_date, memo, payment_id, receipt_id)
+SELECT entry_id, kind, user_id, property_id, currency, entry_date, memo, payment_id, receipt_id
+FROM ledger_backfill;
+
+INSERT INTO journal_lines (entry_id, account, debit)
+SELECT entry_id, debit_account, amount FROM ledger_backfill;
+
+INSERT INTO journal_lines (entry_id, account, credit)
+SELECT entry_id, credit_account, amount FROM ledger_backfill;
+
+DROP TABLE ledger_backfill;
@@ -0,0 +1,2 @@
+ALTER TABLE leases DROP CONSTRAINT IF EXISTS leases_primary_tenant_fkey;
+ALTER TABLE leases DROP COLUMN IF EXISTS primary_tenant_id;
@@ -0,0 +1,20 @@
+-- The tenant a lease's rent is charged to. Existing leases keep charging the tenant
+-- the rent scheduler picked so far.
+ALTER TABLE leases ADD COLUMN primary_tenant_id UUID;
+
+UPDATE leases l
+SET primary_tenant_id = (
+    SELECT lt.tenant_id FROM lease_tenants lt
+    WHERE lt.lease_id = l.id
+    ORDER BY lt.tenant_id
+    LIMIT 1
+);
+
+ALTER TABLE leases ALTER COLUMN primary_tenant_id SET NOT NULL;
+
+-- Must be one of the lease's tenants. Deferred, as tenants are replaced by deleting
+-- and re-adding them.
+ALTER TABLE leases
+ADD CONSTRAINT leases_primary_tenant_fkey
+FOREIGN KEY (id, primary_tenant_id) REFERENCES lease_tenants (lease_id, tenant_id)
+DEFERRABLE INITIALLY DEFERRED;
@@ -0,0 +1,56 @@
+// src/auth/api_keys.rs
+
+use time::OffsetDateTime;
+
+use crate::AppState;
+use crate::auth::AuthUser;
+use crate::auth::tokens::{generate_opaque_token, hash_token};
+use crate::db;
+use crate::errors::AppError;
+use crate::models::ApiScope;
+
+/// Every API key starts with this, which is how they are told apart from JWTs.
+pub const API_KEY_PREFIX: &str = "rak_";
+
+/// Characters of the key kept in clear so users can recognise their keys.
+const DISPLAY_PREFIX_LEN: usize = API_KEY_PREFIX.len() + 8;
+
+/// Generates a new API key, returning the key and its display prefix.
+pub fn generate_api_key() -> (String, String) {
+    let key = format!("{}{}", API_KEY_PREFIX, generate_opaque_token());
+    let prefix = key[..DISPLAY_PREFIX_LEN].to_owned();
+    (key, prefix)
+}
+
+/// Looks up an API key and returns the user it acts as, limited to the key's scopes.
+pub async fn authenticate_api_key(state: &AppState, key: &str) -> Result<AuthUser, AppError> {
+    let credentials = db::find_api_key_credentials(&state.pool, &hash_token(key))
+        .await?
+        .ok_or_else(|| AppError::Unauthorized("Invalid API key".into()))?;
+
+    if credentials.revoked_at.is_some() {
+        return Err(AppError::Unauthorized("API key has been revoked".into()));
+    }
+
+    if credentials
+        .expires_at
+        .is_some_and(|expires_at| expires_at <= OffsetDateTime::now_utc())
+    {
+        return Err(AppError::Unauthorized("API key has expired".into()));
+    }
+
+    db::touch_api_key(&state.pool, credentials.id).await?;
+
+    Ok(AuthUser {
+        user_id: credentials.user_id,
+        username: credentials.username,
+        role: credentials.role,
+        scopes: Some(
+            credentials
+                .scopes
+                .iter()
+                .filter_map(|scope| ApiScope::parse(scope))
+                .collect(),
+        ),
+    })
+}
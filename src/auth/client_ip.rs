@@ -0,0 +1,76 @@
+// src/auth/client_ip.rs
+
+use axum::{
+    extract::{ConnectInfo, FromRequestParts},
+    http::request::Parts,
+};
+use std::net::{IpAddr, SocketAddr};
+
+use crate::AppState;
+use crate::errors::AppError;
+
+/// The IP address of the client making the request, if it can be determined.
+///
+/// Behind `TRUSTED_PROXIES` proxies the client is the `X-Forwarded-For` entry the
+/// outermost of them appended. Entries to the left of it are sent by the client and
+/// cannot be trusted.
+#[derive(Debug, Clone)]
+pub struct ClientIp(pub Option<IpAddr>);
+
+impl FromRequestParts<AppState> for ClientIp {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let trusted_proxies = state.auth_config.trusted_proxies;
+        if trusted_proxies > 0 {
+            let forwarded = parts
+                .headers
+                .get("x-forwarded-for")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| forwarded_client(value, trusted_proxies));
+
+            if forwarded.is_some() {
+                return Ok(ClientIp(forwarded));
+            }
+        }
+
+        let peer = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip());
+
+        Ok(ClientIp(peer))
+    }
+}
+
+/// Picks the address appended by the outermost of `trusted_proxies` proxies, each of
+/// which adds the address it was reached from to the right of the header.
+fn forwarded_client(header: &str, trusted_proxies: usize) -> Option<IpAddr> {
+    header
+        .rsplit(',')
+        .nth(trusted_proxies - 1)
+        .and_then(|ip| ip.trim().parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spoofed_entries_left_of_the_trusted_proxies_are_ignored() {
+        let header = "6.6.6.6, 203.0.113.7, 10.0.0.2";
+
+        assert_eq!(
+            forwarded_client(header, 1),
+            Some("10.0.0.2".parse().unwrap())
+        );
+        assert_eq!(
+            forwarded_client(header, 2),
+            Some("203.0.113.7".parse().unwrap())
+        );
+        assert_eq!(forwarded_client("203.0.113.7", 2), None);
+    }
+}
@@ -0,0 +1,96 @@
+// src/auth/emails.rs
+
+use time::OffsetDateTime;
+
+use crate::AppState;
+use crate::auth::tokens::{generate_opaque_token, hash_token};
+use crate::db;
+use crate::errors::AppError;
+use crate::mailer::EmailMessage;
+use crate::models::{User, UserTokenPurpose};
+
+/// Issues a fresh email verification token and mails the link to the user.
+pub async fn send_verification_email(app_state: &AppState, user: &User) -> Result<(), AppError> {
+    let Some(email) = user.email.clone() else {
+        return Err(AppError::BadRequest("No email address on file".into()));
+    };
+
+    let token = issue_token(
+        app_state,
+        user,
+        UserTokenPurpose::EmailVerification,
+        app_state.auth_config.email_verification_ttl,
+    )
+    .await?;
+
+    let link = format!(
+        "{}/verify-email?token={}",
+        app_state.auth_config.app_base_url, token
+    );
+    app_state
+        .mailer
+        .send(EmailMessage {
+            to: email,
+            subject: "Verify your email address".into(),
+            body: format!(
+                "Hi {},\n\nConfirm your email address by opening this link:\n\n{}\n\nThe link expires in {} hours.\n",
+                user.username,
+                link,
+                app_state.auth_config.email_verification_ttl.num_hours()
+            ),
+        })
+        .await
+}
+
+/// Issues a password reset token and mails the link to the user.
+pub async fn send_password_reset_email(app_state: &AppState, user: &User) -> Result<(), AppError> {
+    let Some(email) = user.email.clone() else {
+        return Ok(());
+    };
+
+    let token = issue_token(
+        app_state,
+        user,
+        UserTokenPurpose::PasswordReset,
+        app_state.auth_config.password_reset_ttl,
+    )
+    .await?;
+
+    let link = format!(
+        "{}/reset-password?token={}",
+        app_state.auth_config.app_base_url, token
+    );
+    app_state
+        .mailer
+        .send(EmailMessage {
+            to: email,
+            subject: "Reset your password".into(),
+            body: format!(
+                "Hi {},\n\nSomeone asked to reset the password for your account. If it was you, open this link:\n\n{}\n\nThe link expires in {} minutes. If you did not ask for this, you can ignore this email.\n",
+                user.username,
+                link,
+                app_state.auth_config.password_reset_ttl.num_minutes()
+            ),
+        })
+        .await
+}
+
+async fn issue_token(
+    app_state: &AppState,
+    user: &User,
+    purpose: UserTokenPurpose,
+    ttl: chrono::Duration,
+) -> Result<String, AppError> {
+    let token = generate_opaque_token();
+    let expires_at = OffsetDateTime::now_utc() + time::Duration::seconds(ttl.num_seconds());
+    db::create_user_token(
+        &app_state.pool,
+        user.id,
+        purpose,
+        &hash_token(&token),
+        expires_at,
+    )
+    .await?;
+
+    Ok(token)
+}
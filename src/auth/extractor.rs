@@ -0,0 +1,120 @@
+// src/auth/extractor.rs
+
+use axum::{extract::FromRequestParts, http::request::Parts};
+use http::header::AUTHORIZATION;
+use uuid::Uuid;
+
+use crate::AppState;
+use crate::auth::api_keys::{API_KEY_PREFIX, authenticate_api_key};
+use crate::auth::jwt::decode_token;
+use crate::errors::AppError;
+use crate::models::{ApiScope, UserRole};
+
+/// Header integrations may send their API key in instead of `Authorization`.
+const API_KEY_HEADER: &str = "x-api-key";
+
+/// The authenticated caller, taken from a validated `Authorization: Bearer` token
+/// or API key.
+///
+/// Routes behind `require_auth` get this from the request extensions; anywhere
+/// else it decodes the header itself, so it can also be used on its own.
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub user_id: Uuid,
+    pub username: String,
+    pub role: UserRole,
+    pub scopes: Option<Vec<ApiScope>>, // Set when authenticated with an API key
+}
+
+impl AuthUser {
+    /// Returns `Forbidden` unless the caller has one of the given roles.
+    pub fn require_role(&self, roles: &[UserRole]) -> Result<(), AppError> {
+        if roles.contains(&self.role) {
+            Ok(())
+        } else {
+            Err(AppError::Forbidden(
+                "You do not have permission to perform this action".into(),
+            ))
+        }
+    }
+
+    pub fn is_admin(&self) -> bool {
+        self.role == UserRole::Admin
+    }
+
+    /// Returns `Forbidden` if the caller is an API key without the given scope.
+    ///
+    /// User sessions are not limited by scope, only by role.
+    pub fn require_scope(&self, scope: ApiScope) -> Result<(), AppError> {
+        match &self.scopes {
+            Some(scopes) if !scopes.contains(&scope) => Err(AppError::Forbidden(format!(
+                "API key is missing the {} scope",
+                scope.as_str()
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns `Forbidden` if the caller is an API key, for account management
+    /// that only the user themselves may do.
+    pub fn require_session(&self) -> Result<(), AppError> {
+        if self.scopes.is_some() {
+            return Err(AppError::Forbidden(
+                "This action cannot be performed with an API key".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Reads the bearer token or API key from the request headers and validates it.
+    pub async fn from_headers(parts: &Parts, state: &AppState) -> Result<Self, AppError> {
+        if let Some(key) = parts.headers.get(API_KEY_HEADER) {
+            let key = key
+                .to_str()
+                .map_err(|_| AppError::Unauthorized("Invalid API key".into()))?;
+            return authenticate_api_key(state, key.trim()).await;
+        }
+
+        let header = parts
+            .headers
+            .get(AUTHORIZATION)
+            .ok_or_else(|| AppError::Unauthorized("Missing authorization header".into()))?
+            .to_str()
+            .map_err(|_| AppError::Unauthorized("Invalid authorization header".into()))?;
+
+        let token = header
+            .strip_prefix("Bearer ")
+            .map(str::trim)
+            .filter(|token| !token.is_empty())
+            .ok_or_else(|| AppError::Unauthorized("Expected a bearer token".into()))?;
+
+        if token.starts_with(API_KEY_PREFIX) {
+            return authenticate_api_key(state, token).await;
+        }
+
+        let claims = decode_token(&state.jwt_keys, token)?;
+
+        Ok(AuthUser {
+            user_id: claims.sub,
+            username: claims.username,
+            role: claims.role,
+            scopes: None,
+        })
+    }
+}
+
+impl FromRequestParts<AppState> for AuthUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        // Already validated by the `require_auth` layer
+        if let Some(user) = parts.extensions.get::<AuthUser>() {
+            return Ok(user.clone());
+        }
+
+        AuthUser::from_headers(parts, state).await
+    }
+}
@@ -0,0 +1,129 @@
+// src/auth/jwt.rs
+
+use chrono::{Duration, Utc};
+use jsonwebtoken::{Header, Validation, decode, decode_header, encode, errors::ErrorKind};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::auth::keys::JwtKeys;
+use crate::errors::AppError;
+use crate::models::{User, UserRole};
+
+/// Claims carried inside every access token we issue.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: Uuid, // Subject (user ID)
+    pub username: String,
+    pub role: UserRole,
+    pub iss: String, // Issuer
+    pub aud: String, // Audience
+    pub exp: usize,  // Expiration time
+}
+
+/// Claims of the short-lived token handed out between the password and MFA steps.
+///
+/// It has its own audience and none of the access token claims, so it can never
+/// be accepted as an access token.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MfaPendingClaims {
+    pub sub: Uuid,
+    pub iss: String,
+    pub aud: String,
+    pub exp: usize,
+}
+
+const MFA_PENDING_AUDIENCE: &str = "mfa_pending";
+
+/// Signs a new access token for the given user, valid for `ttl`.
+pub fn create_token(keys: &JwtKeys, user: &User, ttl: Duration) -> Result<String, AppError> {
+    let claims = Claims {
+        sub: user.id,
+        username: user.username.clone(),
+        role: user.role,
+        iss: keys.issuer.clone(),
+        aud: keys.audience.clone(),
+        exp: expires_at(ttl)?,
+    };
+
+    sign(keys, &claims)
+}
+
+/// Validates a token's signature, issuer, audience and expiry and returns its claims.
+pub fn decode_token(keys: &JwtKeys, token: &str) -> Result<Claims, AppError> {
+    verify(keys, token, &keys.audience).map_err(|kind| match kind {
+        Some(ErrorKind::ExpiredSignature) => AppError::Unauthorized("Token has expired".into()),
+        _ => AppError::Unauthorized("Invalid authentication token".into()),
+    })
+}
+
+/// Signs a token proving the user passed the password step of an MFA login.
+pub fn create_mfa_pending_token(
+    keys: &JwtKeys,
+    user_id: Uuid,
+    ttl: Duration,
+) -> Result<String, AppError> {
+    let claims = MfaPendingClaims {
+        sub: user_id,
+        iss: keys.issuer.clone(),
+        aud: MFA_PENDING_AUDIENCE.into(),
+        exp: expires_at(ttl)?,
+    };
+
+    sign(keys, &claims)
+}
+
+/// Validates an MFA pending token and returns the id of the user it was issued to.
+pub fn decode_mfa_pending_token(keys: &JwtKeys, token: &str) -> Result<Uuid, AppError> {
+    let claims: MfaPendingClaims =
+        verify(keys, token, MFA_PENDING_AUDIENCE).map_err(|kind| match kind {
+            Some(ErrorKind::ExpiredSignature) => {
+                AppError::Unauthorized("MFA session has expired, please log in again".into())
+            }
+            _ => AppError::Unauthorized("Invalid MFA token".into()),
+        })?;
+
+    Ok(claims.sub)
+}
+
+/// Signs claims with the active key, naming it in the `kid` header.
+fn sign<T: Serialize>(keys: &JwtKeys, claims: &T) -> Result<String, AppError> {
+    let (kid, algorithm, key) = keys.signing_key();
+    let mut header = Header::new(algorithm);
+    header.kid = Some(kid.to_owned());
+
+    encode(&header, claims, key)
+        .map_err(|e| AppError::InternalServerError(format!("Failed to generate JWT: {}", e)))
+}
+
+/// Verifies a token against the key named in its header, accepting only that key's
+/// algorithm, our issuer and the given audience.
+///
+/// Returns the failure kind so callers can word their own errors; `None` means the
+/// token named a key we don't have.
+fn verify<T: DeserializeOwned>(
+    keys: &JwtKeys,
+    token: &str,
+    audience: &str,
+) -> Result<T, Option<ErrorKind>> {
+    let header = decode_header(token).map_err(|e| Some(e.into_kind()))?;
+    let (algorithm, key) = keys.verification_key(header.kid.as_deref()).ok_or(None)?;
+
+    let mut validation = Validation::new(algorithm);
+    validation.set_issuer(&[&keys.issuer]);
+    validation.set_audience(&[audience]);
+    validation.set_required_spec_claims(&["exp", "iss", "aud"]);
+
+    decode::<T>(token, key, &validation)
+        .map(|data| data.claims)
+        .map_err(|e| Some(e.into_kind()))
+}
+
+fn expires_at(ttl: Duration) -> Result<usize, AppError> {
+    Ok(Utc::now()
+        .checked_add_signed(ttl)
+        .ok_or_else(|| {
+            AppError::InternalServerError("Failed to calculate token expiration".into())
+        })?
+        .timestamp() as usize)
+}
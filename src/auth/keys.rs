@@ -0,0 +1,325 @@
+// src/auth/keys.rs
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey};
+use ring::signature::{self, Ed25519KeyPair, KeyPair, RsaKeyPair};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::config::env_or;
+use crate::errors::AppError;
+
+/// Kid used for the shared-secret fallback when no key directory is configured.
+const SHARED_SECRET_KID: &str = "hs256";
+
+/// A public key tokens may be verified with.
+struct VerificationKey {
+    algorithm: Algorithm,
+    key: DecodingKey,
+}
+
+/// Keys used to sign and verify our JWTs, plus the issuer and audience they carry.
+///
+/// With `JWT_KEYS_DIR` set, every `<kid>.pem` file in the directory is loaded as a
+/// PKCS#8 Ed25519 (EdDSA) or RSA (RS256) private key. Tokens are signed with
+/// `JWT_ACTIVE_KID` (default: the last kid in sort order) and verified with any
+/// loaded key, so rotating is a matter of adding a key, switching the active kid,
+/// and deleting the old file once its tokens have expired. Only the public halves
+/// are published at `/.well-known/jwks.json`.
+///
+/// Without `JWT_KEYS_DIR`, tokens are signed with the HS256 `JWT_SECRET`, which is
+/// convenient for local development but cannot be verified by other services.
+pub struct JwtKeys {
+    signing_kid: String,
+    signing_algorithm: Algorithm,
+    signing_key: EncodingKey,
+    verification_keys: HashMap<String, VerificationKey>,
+    jwks: Value,
+    pub issuer: String,
+    pub audience: String,
+}
+
+impl JwtKeys {
+    pub fn from_env() -> Self {
+        let issuer = env_or("JWT_ISSUER", "rust_api".to_string());
+        let audience = env_or("JWT_AUDIENCE", "rust_api".to_string());
+
+        // Empty values count as unset, so docker-compose can pass them through
+        let non_empty = |key| std::env::var(key).ok().filter(|value| !value.is_empty());
+
+        match non_empty("JWT_KEYS_DIR") {
+            Some(dir) => {
+                let active_kid = non_empty("JWT_ACTIVE_KID");
+                Self::from_dir(Path::new(&dir), active_kid, issuer, audience).unwrap_or_else(|e| {
+                    panic!("FATAL: Failed to load JWT keys from {}: {:?}", dir, e)
+                })
+            }
+            None => {
+                let secret = std::env::var("JWT_SECRET")
+                    .expect("FATAL: JWT_KEYS_DIR or JWT_SECRET must be set in .env or environment");
+                Self::from_secret(&secret, issuer, audience)
+            }
+        }
+    }
+
+    pub fn from_secret(secret: &str, issuer: String, audience: String) -> Self {
+        let verification_key = VerificationKey {
+            algorithm: Algorithm::HS256,
+            key: DecodingKey::from_secret(secret.as_bytes()),
+        };
+
+        JwtKeys {
+            signing_kid: SHARED_SECRET_KID.into(),
+            signing_algorithm: Algorithm::HS256,
+            signing_key: EncodingKey::from_secret(secret.as_bytes()),
+            verification_keys: HashMap::from([(SHARED_SECRET_KID.into(), verification_key)]),
+            jwks: json!({ "keys": [] }), // A shared secret must never be published
+            issuer,
+            audience,
+        }
+    }
+
+    fn from_dir(
+        dir: &Path,
+        active_kid: Option<String>,
+        issuer: String,
+        audience: String,
+    ) -> Result<Self, AppError> {
+        let mut kids = Vec::new();
+        for entry in std::fs::read_dir(dir).map_err(key_error)? {
+            let path = entry.map_err(key_error)?.path();
+            let kid = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .filter(|_| path.extension().is_some_and(|ext| ext == "pem"));
+            if let Some(kid) = kid {
+                kids.push(kid.to_owned());
+            }
+        }
+        kids.sort();
+
+        let active_kid = active_kid
+            .or_else(|| kids.last().cloned())
+            .ok_or_else(|| AppError::InternalServerError("No .pem keys found".into()))?;
+
+        let mut signing = None;
+        let mut verification_keys = HashMap::new();
+        let mut jwks = Vec::new();
+
+        for kid in kids {
+            let pem_bytes = std::fs::read(dir.join(format!("{}.pem", kid))).map_err(key_error)?;
+            let (algorithm, encoding_key, decoding_key, jwk) = parse_private_key(&kid, &pem_bytes)?;
+
+            if kid == active_kid {
+                signing = Some((algorithm, encoding_key));
+            }
+            verification_keys.insert(
+                kid,
+                VerificationKey {
+                    algorithm,
+                    key: decoding_key,
+                },
+            );
+            jwks.push(jwk);
+        }
+
+        let (signing_algorithm, signing_key) = signing.ok_or_else(|| {
+            AppError::InternalServerError(format!("Active key '{}' not found", active_kid))
+        })?;
+
+        Ok(JwtKeys {
+            signing_kid: active_kid,
+            signing_algorithm,
+            signing_key,
+            verification_keys,
+            jwks: json!({ "keys": jwks }),
+            issuer,
+            audience,
+        })
+    }
+
+    /// The kid, algorithm and key new tokens are signed with.
+    pub fn signing_key(&self) -> (&str, Algorithm, &EncodingKey) {
+        (&self.signing_kid, self.signing_algorithm, &self.signing_key)
+    }
+
+    /// Looks up the key for a token's `kid` header, along with the only algorithm it may be used with.
+    pub fn verification_key(&self, kid: Option<&str>) -> Option<(Algorithm, &DecodingKey)> {
+        // Tokens signed with the shared secret may predate the `kid` header
+        let kid = kid.unwrap_or(SHARED_SECRET_KID);
+        self.verification_keys
+            .get(kid)
+            .map(|key| (key.algorithm, &key.key))
+    }
+
+    /// The public keys as a JSON Web Key Set.
+    pub fn jwks(&self) -> &Value {
+        &self.jwks
+    }
+}
+
+/// Parses a PKCS#8 PEM private key into signing and verification keys and its public JWK.
+fn parse_private_key(
+    kid: &str,
+    pem_bytes: &[u8],
+) -> Result<(Algorithm, EncodingKey, DecodingKey, Value), AppError> {
+    let der = pem::parse(pem_bytes).map_err(key_error)?;
+    if der.tag() != "PRIVATE KEY" {
+        return Err(AppError::InternalServerError(format!(
+            "{}.pem must be a PKCS#8 private key, found {}",
+            kid,
+            der.tag()
+        )));
+    }
+
+    if let Ok(key_pair) = Ed25519KeyPair::from_pkcs8_maybe_unchecked(der.contents()) {
+        let x = URL_SAFE_NO_PAD.encode(key_pair.public_key().as_ref());
+        let jwk = json!({
+            "kty": "OKP",
+            "crv": "Ed25519",
+            "use": "sig",
+            "alg": "EdDSA",
+            "kid": kid,
+            "x": x,
+        });
+        return Ok((
+            Algorithm::EdDSA,
+            EncodingKey::from_ed_der(der.contents()),
+            DecodingKey::from_ed_components(&x).map_err(key_error)?,
+            jwk,
+        ));
+    }
+
+    if let Ok(key_pair) = RsaKeyPair::from_pkcs8(der.contents()) {
+        let components: signature::RsaPublicKeyComponents<Vec<u8>> = key_pair.public().into();
+        let n = URL_SAFE_NO_PAD.encode(&components.n);
+        let e = URL_SAFE_NO_PAD.encode(&components.e);
+        let jwk = json!({
+            "kty": "RSA",
+            "use": "sig",
+            "alg": "RS256",
+            "kid": kid,
+            "n": n,
+            "e": e,
+        });
+        return Ok((
+            Algorithm::RS256,
+            EncodingKey::from_rsa_pem(pem_bytes).map_err(key_error)?,
+            DecodingKey::from_rsa_components(&n, &e).map_err(key_error)?,
+            jwk,
+        ));
+    }
+
+    Err(AppError::InternalServerError(format!(
+        "{}.pem is not an Ed25519 or RSA (2048 bits or more) private key",
+        kid
+    )))
+}
+
+fn key_error(err: impl std::fmt::Display) -> AppError {
+    AppError::InternalServerError(format!("Invalid JWT key: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::jwt::{
+        create_mfa_pending_token, create_token, decode_mfa_pending_token, decode_token,
+    };
+    use crate::models::{User, UserRole};
+    use chrono::Duration;
+    use jsonwebtoken::decode_header;
+    use ring::rand::SystemRandom;
+    use time::OffsetDateTime;
+    use uuid::Uuid;
+
+    fn user() -> User {
+        User {
+            id: Uuid::new_v4(),
+            username: "tenant".into(),
+            password_hash: String::new(),
+            role: UserRole::Tenant,
+            email: None,
+            full_name: None,
+            phone: None,
+            email_verified_at: None,
+            failed_login_attempts: 0,
+            locked_until: None,
+            totp_secret: None,
+            totp_enabled_at: None,
+            totp_last_used_step: None,
+            created_at: OffsetDateTime::now_utc(),
+            updated_at: OffsetDateTime::now_utc(),
+        }
+    }
+
+    fn write_ed25519_key(dir: &Path, kid: &str) {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        let pem = pem::encode(&pem::Pem::new("PRIVATE KEY", pkcs8.as_ref().to_vec()));
+        std::fs::write(dir.join(format!("{}.pem", kid)), pem).unwrap();
+    }
+
+    fn load(dir: &Path) -> JwtKeys {
+        JwtKeys::from_dir(dir, None, "rust_api".into(), "rust_api".into()).unwrap()
+    }
+
+    #[test]
+    fn tokens_outlive_a_rotation_until_their_key_is_removed() {
+        let dir = std::env::temp_dir().join(format!("jwt-keys-{}", Uuid::new_v4()));
+        std::fs::create_dir(&dir).unwrap();
+        let user = user();
+
+        write_ed25519_key(&dir, "2025-01");
+        let old_token = create_token(&load(&dir), &user, Duration::minutes(15)).unwrap();
+
+        // A newer key takes over signing, and the old one still verifies
+        write_ed25519_key(&dir, "2025-02");
+        let keys = load(&dir);
+        let new_token = create_token(&keys, &user, Duration::minutes(15)).unwrap();
+        assert_eq!(
+            decode_header(&new_token).unwrap().kid.as_deref(),
+            Some("2025-02")
+        );
+        assert_eq!(decode_token(&keys, &old_token).unwrap().sub, user.id);
+        let published = keys.jwks()["keys"].as_array().unwrap();
+        assert_eq!(published.len(), 2);
+        assert!(published.iter().all(|jwk| jwk.get("d").is_none()));
+
+        std::fs::remove_file(dir.join("2025-01.pem")).unwrap();
+        let keys = load(&dir);
+        assert!(matches!(
+            decode_token(&keys, &old_token),
+            Err(AppError::Unauthorized(_))
+        ));
+        assert_eq!(decode_token(&keys, &new_token).unwrap().sub, user.id);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn tokens_are_only_accepted_for_our_issuer_and_their_own_audience() {
+        let ours = JwtKeys::from_secret("secret", "rust_api".into(), "rust_api".into());
+        let user = user();
+        let token = create_token(&ours, &user, Duration::minutes(15)).unwrap();
+
+        for (issuer, audience) in [("someone_else", "rust_api"), ("rust_api", "another_app")] {
+            let theirs = JwtKeys::from_secret("secret", issuer.into(), audience.into());
+            assert!(
+                decode_token(&theirs, &token).is_err(),
+                "{} {}",
+                issuer,
+                audience
+            );
+        }
+
+        // Access tokens and MFA pending tokens cannot stand in for each other
+        let mfa_token = create_mfa_pending_token(&ours, user.id, Duration::minutes(5)).unwrap();
+        assert!(decode_token(&ours, &mfa_token).is_err());
+        assert!(decode_mfa_pending_token(&ours, &token).is_err());
+        assert_eq!(
+            decode_mfa_pending_token(&ours, &mfa_token).unwrap(),
+            user.id
+        );
+    }
+}
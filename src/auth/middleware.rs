@@ -0,0 +1,27 @@
+// src/auth/middleware.rs
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::AppState;
+use crate::auth::AuthUser;
+use crate::errors::AppError;
+
+/// Route layer that rejects any request without a valid bearer token or API key.
+///
+/// On success the `AuthUser` is stored in the request extensions so handlers
+/// can extract it without decoding the token a second time.
+pub async fn require_auth(
+    State(app_state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let (mut parts, body) = request.into_parts();
+    let user = AuthUser::from_headers(&parts, &app_state).await?;
+    parts.extensions.insert(user);
+
+    Ok(next.run(Request::from_parts(parts, body)).await)
+}
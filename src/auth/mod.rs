@@ -0,0 +1,19 @@
+// Declare the sub-modules
+pub mod api_keys;
+pub mod client_ip;
+pub mod emails;
+pub mod extractor;
+pub mod jwt;
+pub mod keys;
+pub mod middleware;
+pub mod oidc;
+pub mod password;
+pub mod session;
+pub mod tokens;
+pub mod totp;
+
+// Re-export the items handlers and routers need
+pub use client_ip::ClientIp;
+pub use extractor::AuthUser;
+pub use middleware::require_auth;
+pub use session::issue_session;
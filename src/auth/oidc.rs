@@ -0,0 +1,245 @@
+// src/auth/oidc.rs
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode, decode_header};
+use reqwest::Url;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use sha2::{Digest, Sha256};
+use tokio::sync::{OnceCell, RwLock};
+
+use crate::config::OidcConfig;
+use crate::errors::AppError;
+
+/// Signature algorithms we accept on ID tokens. Symmetric ones are excluded, since
+/// they would let anyone holding the client secret mint tokens.
+const ID_TOKEN_ALGORITHMS: [Algorithm; 9] = [
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+    Algorithm::ES256,
+    Algorithm::ES384,
+    Algorithm::EdDSA,
+];
+
+/// The parts of the provider's discovery document we use.
+#[derive(Debug, Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+/// Claims read from a validated ID token.
+#[derive(Debug, Deserialize)]
+pub struct IdTokenClaims {
+    pub iss: String,
+    pub sub: String,
+    pub nonce: Option<String>,
+    pub email: Option<String>,
+    #[serde(default)]
+    pub email_verified: bool,
+    pub name: Option<String>,
+    pub preferred_username: Option<String>,
+}
+
+/// Client for the authorization code flow with PKCE against one OpenID Connect provider.
+///
+/// The discovery document is fetched on first use, so the API starts even while
+/// the provider is unreachable. Signing keys are cached and refetched when a token
+/// names a key we have not seen, which is how providers roll their keys.
+pub struct OidcClient {
+    pub config: OidcConfig,
+    http: reqwest::Client,
+    metadata: OnceCell<ProviderMetadata>,
+    jwks: RwLock<JwkSet>,
+}
+
+impl OidcClient {
+    pub fn new(config: OidcConfig) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(10))
+            .build()
+            .expect("FATAL: Failed to build HTTP client");
+
+        OidcClient {
+            config,
+            http,
+            metadata: OnceCell::new(),
+            jwks: RwLock::new(JwkSet { keys: Vec::new() }),
+        }
+    }
+
+    /// Builds the provider URL to send the user's browser to.
+    pub async fn authorization_url(
+        &self,
+        state: &str,
+        nonce: &str,
+        code_verifier: &str,
+    ) -> Result<String, AppError> {
+        let metadata = self.metadata().await?;
+        let mut url = Url::parse(&metadata.authorization_endpoint)
+            .map_err(|e| provider_error(format!("Invalid authorization endpoint: {}", e)))?;
+
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.config.client_id)
+            .append_pair("redirect_uri", &self.config.redirect_url)
+            .append_pair("scope", &self.config.scopes)
+            .append_pair("state", state)
+            .append_pair("nonce", nonce)
+            .append_pair("code_challenge", &pkce_challenge(code_verifier))
+            .append_pair("code_challenge_method", "S256");
+
+        Ok(url.into())
+    }
+
+    /// Redeems an authorization code and returns the claims of the validated ID token.
+    pub async fn exchange_code(
+        &self,
+        code: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> Result<IdTokenClaims, AppError> {
+        let metadata = self.metadata().await?;
+
+        let mut request = self.http.post(&metadata.token_endpoint).form(&[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", &self.config.redirect_url),
+            ("client_id", &self.config.client_id),
+            ("code_verifier", code_verifier),
+        ]);
+        if let Some(secret) = &self.config.client_secret {
+            request = request.basic_auth(&self.config.client_id, Some(secret));
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| provider_error(format!("Token request failed: {}", e)))?;
+        if !response.status().is_success() {
+            tracing::warn!(status = %response.status(), "Identity provider rejected the authorization code");
+            return Err(AppError::Unauthorized(
+                "Identity provider rejected the login".into(),
+            ));
+        }
+        let tokens: TokenResponse = response
+            .json()
+            .await
+            .map_err(|e| provider_error(format!("Invalid token response: {}", e)))?;
+
+        let claims = self.verify_id_token(&tokens.id_token).await?;
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(AppError::Unauthorized("Invalid ID token".into()));
+        }
+
+        Ok(claims)
+    }
+
+    /// Checks the ID token's signature, issuer, audience and expiry.
+    async fn verify_id_token(&self, id_token: &str) -> Result<IdTokenClaims, AppError> {
+        let invalid = || AppError::Unauthorized("Invalid ID token".into());
+
+        let header = decode_header(id_token).map_err(|_| invalid())?;
+        if !ID_TOKEN_ALGORITHMS.contains(&header.alg) {
+            return Err(invalid());
+        }
+        let key = self.decoding_key(header.kid.as_deref()).await?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&self.metadata().await?.issuer]);
+        validation.set_audience(&[&self.config.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+        decode::<IdTokenClaims>(id_token, &key, &validation)
+            .map(|data| data.claims)
+            .map_err(|e| {
+                tracing::warn!("ID token rejected: {}", e);
+                invalid()
+            })
+    }
+
+    /// Finds the provider key a token was signed with, refetching the key set once
+    /// if it is not cached.
+    async fn decoding_key(&self, kid: Option<&str>) -> Result<DecodingKey, AppError> {
+        if let Some(key) = find_key(&*self.jwks.read().await, kid)? {
+            return Ok(key);
+        }
+
+        let jwks: JwkSet = self.get_json(&self.metadata().await?.jwks_uri).await?;
+        let key = find_key(&jwks, kid)?;
+        *self.jwks.write().await = jwks;
+
+        key.ok_or_else(|| AppError::Unauthorized("ID token signed with an unknown key".into()))
+    }
+
+    async fn metadata(&self) -> Result<&ProviderMetadata, AppError> {
+        self.metadata
+            .get_or_try_init(|| async {
+                let url = format!(
+                    "{}/.well-known/openid-configuration",
+                    self.config.issuer_url
+                );
+                let metadata: ProviderMetadata = self.get_json(&url).await?;
+
+                // Required by OpenID Connect Discovery, and what keeps a spoofed
+                // document from vouching for another issuer's tokens
+                if metadata.issuer.trim_end_matches('/') != self.config.issuer_url {
+                    return Err(provider_error(format!(
+                        "Discovery document is for issuer {}",
+                        metadata.issuer
+                    )));
+                }
+                Ok(metadata)
+            })
+            .await
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T, AppError> {
+        self.http
+            .get(url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| provider_error(format!("Request to {} failed: {}", url, e)))?
+            .json()
+            .await
+            .map_err(|e| provider_error(format!("Invalid response from {}: {}", url, e)))
+    }
+}
+
+/// Derives the S256 PKCE code challenge for a verifier.
+fn pkce_challenge(code_verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+}
+
+/// Looks a key up by kid. Tokens without a kid are only accepted from a provider
+/// publishing a single key.
+fn find_key(jwks: &JwkSet, kid: Option<&str>) -> Result<Option<DecodingKey>, AppError> {
+    let jwk = match kid {
+        Some(kid) => jwks.find(kid),
+        None if jwks.keys.len() == 1 => jwks.keys.first(),
+        None => None,
+    };
+
+    jwk.map(|jwk| {
+        DecodingKey::from_jwk(jwk)
+            .map_err(|e| provider_error(format!("Unusable provider key: {}", e)))
+    })
+    .transpose()
+}
+
+fn provider_error(message: String) -> AppError {
+    AppError::InternalServerError(format!("Identity provider error: {}", message))
+}
@@ -0,0 +1,117 @@
+// src/auth/password.rs
+
+use argon2::{
+    Algorithm, Argon2, Params, Version,
+    password_hash::{
+        self, PasswordHash, PasswordHasher, PasswordVerifier, SaltString, rand_core::OsRng,
+    },
+};
+
+use tokio::sync::OnceCell;
+
+use crate::config::PasswordConfig;
+use crate::errors::AppError;
+
+/// Hash of a random password, checked when there is no real hash to compare against.
+static DUMMY_HASH: OnceCell<String> = OnceCell::const_new();
+
+/// Outcome of checking a password against a stored hash.
+#[derive(Debug)]
+pub struct PasswordCheck {
+    pub matches: bool,
+    /// The stored hash uses bcrypt or outdated Argon2 parameters and should be replaced.
+    pub needs_rehash: bool,
+}
+
+/// Hashes a password with Argon2id.
+///
+/// Hashing is deliberately expensive, so it runs on the blocking thread pool
+/// instead of tying up an async worker.
+pub async fn hash_password(config: &PasswordConfig, password: String) -> Result<String, AppError> {
+    let config = config.clone();
+    tokio::task::spawn_blocking(move || {
+        let salt = SaltString::generate(&mut OsRng);
+        let hash = argon2(&config)?.hash_password(password.as_bytes(), &salt)?;
+        Ok(hash.to_string())
+    })
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Password hashing task failed: {}", e)))?
+}
+
+/// Verifies a password against an Argon2 or legacy bcrypt hash.
+pub async fn verify_password(
+    config: &PasswordConfig,
+    password: String,
+    hash: String,
+) -> Result<PasswordCheck, AppError> {
+    let config = config.clone();
+    tokio::task::spawn_blocking(move || {
+        if is_bcrypt_hash(&hash) {
+            let matches = bcrypt::verify(&password, &hash).map_err(|e| {
+                AppError::InternalServerError(format!("Password verification error: {}", e))
+            })?;
+            return Ok(PasswordCheck {
+                matches,
+                needs_rehash: true,
+            });
+        }
+
+        let parsed = PasswordHash::new(&hash)?;
+        let matches = match Argon2::default().verify_password(password.as_bytes(), &parsed) {
+            Ok(()) => true,
+            Err(password_hash::Error::Password) => false,
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(PasswordCheck {
+            matches,
+            needs_rehash: !uses_current_params(&parsed, &config),
+        })
+    })
+    .await
+    .map_err(|e| {
+        AppError::InternalServerError(format!("Password verification task failed: {}", e))
+    })?
+}
+
+/// Spends the same time as `verify_password` without checking anything.
+///
+/// Used for unknown or locked accounts so response times do not reveal
+/// whether an account exists.
+pub async fn dummy_verify_password(config: &PasswordConfig, password: String) {
+    let hash = DUMMY_HASH
+        .get_or_try_init(|| hash_password(config, "dummy password".into()))
+        .await;
+
+    if let Ok(hash) = hash {
+        let _ = verify_password(config, password, hash.clone()).await;
+    }
+}
+
+fn argon2(config: &PasswordConfig) -> Result<Argon2<'static>, AppError> {
+    let params = Params::new(
+        config.memory_kib,
+        config.iterations,
+        config.parallelism,
+        None,
+    )?;
+    Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+}
+
+fn is_bcrypt_hash(hash: &str) -> bool {
+    ["$2a$", "$2b$", "$2x$", "$2y$"]
+        .iter()
+        .any(|prefix| hash.starts_with(prefix))
+}
+
+fn uses_current_params(hash: &PasswordHash, config: &PasswordConfig) -> bool {
+    if hash.algorithm != Algorithm::Argon2id.ident() {
+        return false;
+    }
+
+    Params::try_from(hash).is_ok_and(|params| {
+        params.m_cost() == config.memory_kib
+            && params.t_cost() == config.iterations
+            && params.p_cost() == config.parallelism
+    })
+}
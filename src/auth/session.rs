@@ -0,0 +1,45 @@
+// src/auth/session.rs
+
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::AppState;
+use crate::auth::jwt::create_token;
+use crate::auth::tokens::{generate_opaque_token, hash_token};
+use crate::db;
+use crate::errors::AppError;
+use crate::models::{AuthResponse, User};
+
+/// Issues a new access token and refresh token pair for `user`.
+///
+/// Pass the `family_id` of the refresh token being rotated to keep the new token
+/// in the same family, or `None` to start a new session.
+pub async fn issue_session(
+    app_state: &AppState,
+    user: User,
+    family_id: Option<Uuid>,
+) -> Result<AuthResponse, AppError> {
+    let config = &app_state.auth_config;
+    let token = create_token(&app_state.jwt_keys, &user, config.access_token_ttl)?;
+
+    let refresh_token = generate_opaque_token();
+    let expires_at =
+        OffsetDateTime::now_utc() + time::Duration::seconds(config.refresh_token_ttl.num_seconds());
+    db::create_refresh_token(
+        &app_state.pool,
+        user.id,
+        family_id.unwrap_or_else(Uuid::new_v4),
+        &hash_token(&refresh_token),
+        expires_at,
+    )
+    .await?;
+
+    Ok(AuthResponse {
+        user_id: user.id,
+        username: user.username,
+        role: user.role,
+        token,
+        expires_in: config.access_token_ttl.num_seconds(),
+        refresh_token,
+    })
+}
@@ -0,0 +1,20 @@
+// src/auth/tokens.rs
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use rand::{RngCore, rngs::OsRng};
+use sha2::{Digest, Sha256};
+
+/// Generates a random, URL-safe opaque token with 256 bits of entropy.
+pub fn generate_opaque_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// Hashes an opaque token for storage.
+///
+/// The tokens are random and high-entropy, so a fast unsalted hash is enough;
+/// it only has to stop a database leak from handing out usable tokens.
+pub fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
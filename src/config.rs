@@ -0,0 +1,170 @@
+// src/config.rs
+
+use chrono::Duration;
+
+use crate::models::UserRole;
+
+/// Token lifetimes, email link settings and password hashing settings, read from the environment with sensible defaults.
+#[derive(Debug, Clone)]
+pub struct AuthConfig {
+    pub access_token_ttl: Duration,
+    pub refresh_token_ttl: Duration,
+    pub email_verification_ttl: Duration,
+    pub password_reset_ttl: Duration,
+    pub mfa_token_ttl: Duration, // Time allowed between the password and MFA login steps
+    pub totp_issuer: String,     // Name shown in authenticator apps
+    pub app_base_url: String,    // Used to build the links in emails
+    pub password: PasswordConfig,
+    pub login: LoginProtectionConfig,
+    pub trusted_proxies: usize, // Proxies in front of the app that append to `X-Forwarded-For`
+}
+
+impl AuthConfig {
+    pub fn from_env() -> Self {
+        AuthConfig {
+            access_token_ttl: Duration::minutes(env_or("ACCESS_TOKEN_TTL_MINUTES", 15)),
+            refresh_token_ttl: Duration::days(env_or("REFRESH_TOKEN_TTL_DAYS", 30)),
+            email_verification_ttl: Duration::hours(env_or("EMAIL_VERIFICATION_TTL_HOURS", 24)),
+            password_reset_ttl: Duration::minutes(env_or("PASSWORD_RESET_TTL_MINUTES", 60)),
+            mfa_token_ttl: Duration::minutes(env_or("MFA_TOKEN_TTL_MINUTES", 5)),
+            totp_issuer: env_or("TOTP_ISSUER", "Rust API".to_string()),
+            app_base_url: env_or("APP_BASE_URL", "http://localhost:3000".to_string()),
+            password: PasswordConfig::from_env(),
+            login: LoginProtectionConfig::from_env(),
+            // `TRUST_FORWARDED_FOR=true` on its own means a single load balancer
+            trusted_proxies: env_or(
+                "TRUSTED_PROXIES",
+                usize::from(env_or("TRUST_FORWARDED_FOR", false)),
+            ),
+        }
+    }
+}
+
+/// Argon2id cost parameters. The defaults follow the OWASP recommendation
+/// (19 MiB of memory, 2 iterations, 1 lane).
+#[derive(Debug, Clone)]
+pub struct PasswordConfig {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl PasswordConfig {
+    pub fn from_env() -> Self {
+        PasswordConfig {
+            memory_kib: env_or("ARGON2_MEMORY_KIB", 19 * 1024),
+            iterations: env_or("ARGON2_ITERATIONS", 2),
+            parallelism: env_or("ARGON2_PARALLELISM", 1),
+        }
+    }
+}
+
+/// Brute-force protection thresholds for `/login`.
+#[derive(Debug, Clone)]
+pub struct LoginProtectionConfig {
+    pub max_failures: i32, // Failures before an account is locked
+    pub lockout: Duration, // First lockout, doubled for every further run of failures
+    pub max_lockout: Duration,
+    pub ip_max_failures: i64, // Failures from one IP within `ip_window` before it is throttled
+    pub ip_window: Duration,
+}
+
+impl LoginProtectionConfig {
+    pub fn from_env() -> Self {
+        // Lockouts fall on every `max_failures`-th failure, so it must not be zero
+        let max_failures = env_or("LOGIN_MAX_FAILURES", 5);
+        assert!(
+            max_failures >= 1,
+            "FATAL: LOGIN_MAX_FAILURES must be at least 1, got: {}",
+            max_failures
+        );
+
+        LoginProtectionConfig {
+            max_failures,
+            lockout: Duration::minutes(env_or("LOGIN_LOCKOUT_MINUTES", 15)),
+            max_lockout: Duration::hours(env_or("LOGIN_MAX_LOCKOUT_HOURS", 24)),
+            ip_max_failures: env_or("LOGIN_IP_MAX_FAILURES", 20),
+            ip_window: Duration::minutes(env_or("LOGIN_IP_WINDOW_MINUTES", 15)),
+        }
+    }
+}
+
+/// Settings for signing in through an external OpenID Connect provider.
+#[derive(Debug, Clone)]
+pub struct OidcConfig {
+    pub issuer_url: String,
+    pub client_id: String,
+    pub client_secret: Option<String>, // Not needed by public clients, which rely on PKCE alone
+    pub redirect_url: String,          // Our `/auth/oidc/callback`, as registered with the provider
+    pub scopes: String,
+    pub default_role: UserRole, // Role given to users provisioned on their first login
+    pub login_ttl: Duration,    // Time allowed to complete the login at the provider
+}
+
+impl OidcConfig {
+    /// Returns `None` unless `OIDC_ISSUER_URL` is set to something, leaving OIDC login
+    /// disabled. Empty values count as unset, as Docker Compose passes them for unset variables.
+    pub fn from_env() -> Option<Self> {
+        let issuer_url = non_empty_var("OIDC_ISSUER_URL")?;
+
+        let default_role = match env_or("OIDC_DEFAULT_ROLE", "tenant".to_string()).as_str() {
+            "tenant" => UserRole::Tenant,
+            "landlord" => UserRole::Landlord,
+            other => panic!(
+                "FATAL: OIDC_DEFAULT_ROLE must be tenant or landlord, got: {}",
+                other
+            ),
+        };
+
+        Some(OidcConfig {
+            issuer_url: issuer_url.trim_end_matches('/').to_string(),
+            client_id: non_empty_var("OIDC_CLIENT_ID")
+                .expect("FATAL: OIDC_CLIENT_ID must be set when OIDC_ISSUER_URL is"),
+            client_secret: non_empty_var("OIDC_CLIENT_SECRET"),
+            redirect_url: env_or(
+                "OIDC_REDIRECT_URL",
+                "http://localhost:3000/auth/oidc/callback".to_string(),
+            ),
+            scopes: env_or("OIDC_SCOPES", "openid email profile".to_string()),
+            default_role,
+            login_ttl: Duration::minutes(env_or("OIDC_LOGIN_TTL_MINUTES", 10)),
+        })
+    }
+}
+
+/// Settings for the background jobs run inside the server.
+#[derive(Debug, Clone)]
+pub struct JobsConfig {
+    pub enabled: bool,           // Turn off on replicas that should only serve requests
+    pub interval: Duration,      // Time between runs
+    pub rent_lead_days: i64,     // Rent is charged this many days before it is due
+    pub overdue_grace_days: i64, // Days after its due date an unpaid payment becomes overdue
+}
+
+impl JobsConfig {
+    pub fn from_env() -> Self {
+        JobsConfig {
+            enabled: env_or("JOBS_ENABLED", true),
+            interval: Duration::minutes(env_or("JOBS_INTERVAL_MINUTES", 60)),
+            rent_lead_days: env_or("RENT_LEAD_DAYS", 7),
+            overdue_grace_days: env_or("OVERDUE_GRACE_DAYS", 3),
+        }
+    }
+}
+
+/// Parses an optional environment variable, falling back to `default` when unset.
+pub fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+    match std::env::var(key) {
+        Ok(value) => value
+            .parse()
+            .unwrap_or_else(|_| panic!("FATAL: {} has an invalid value: {}", key, value)),
+        Err(_) => default,
+    }
+}
+
+/// Reads an environment variable, treating an empty value the same as an unset one.
+fn non_empty_var(key: &str) -> Option<String> {
+    std::env::var(key)
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+}
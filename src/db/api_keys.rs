@@ -0,0 +1,132 @@
+use crate::errors::AppError;
+use crate::models::{ApiKey, ApiKeyCredentials, UserRole};
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// Stores a new API key by its hash.
+pub async fn create_api_key(
+    pool: &PgPool,
+    user_id: Uuid,
+    name: &str,
+    prefix: &str,
+    key_hash: &str,
+    scopes: &[String],
+    expires_at: Option<OffsetDateTime>,
+) -> Result<ApiKey, AppError> {
+    let api_key = sqlx::query_as!(
+        ApiKey,
+        r#"
+        INSERT INTO api_keys (user_id, name, prefix, key_hash, scopes, expires_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id, user_id, name, prefix, scopes, last_used_at, expires_at, revoked_at, created_at
+        "#,
+        user_id,
+        name,
+        prefix,
+        key_hash,
+        scopes,
+        expires_at,
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to create API key: {}", e)))?;
+
+    Ok(api_key)
+}
+
+/// Lists a user's API keys, newest first, including revoked and expired ones.
+pub async fn list_api_keys(pool: &PgPool, user_id: Uuid) -> Result<Vec<ApiKey>, AppError> {
+    let api_keys = sqlx::query_as!(
+        ApiKey,
+        r#"
+        SELECT id, user_id, name, prefix, scopes, last_used_at, expires_at, revoked_at, created_at
+        FROM api_keys
+        WHERE user_id = $1
+        ORDER BY created_at DESC
+        "#,
+        user_id
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to list API keys: {}", e)))?;
+
+    Ok(api_keys)
+}
+
+pub async fn find_api_key_by_id(pool: &PgPool, id: Uuid) -> Result<Option<ApiKey>, AppError> {
+    let api_key = sqlx::query_as!(
+        ApiKey,
+        r#"
+        SELECT id, user_id, name, prefix, scopes, last_used_at, expires_at, revoked_at, created_at
+        FROM api_keys
+        WHERE id = $1
+        "#,
+        id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to find API key: {}", e)))?;
+
+    Ok(api_key)
+}
+
+/// Finds a key by the hash of its value, along with the user it acts as.
+pub async fn find_api_key_credentials(
+    pool: &PgPool,
+    key_hash: &str,
+) -> Result<Option<ApiKeyCredentials>, AppError> {
+    let credentials = sqlx::query_as!(
+        ApiKeyCredentials,
+        r#"
+        SELECT k.id, k.scopes, k.expires_at, k.revoked_at,
+               u.id AS user_id, u.username, u.role AS "role: UserRole"
+        FROM api_keys k
+        JOIN users u ON u.id = k.user_id
+        WHERE k.key_hash = $1
+        "#,
+        key_hash
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to find API key: {}", e)))?;
+
+    Ok(credentials)
+}
+
+/// Records that a key was just used.
+///
+/// Only writes once a minute per key, so busy integrations don't turn every
+/// request into an update.
+pub async fn touch_api_key(pool: &PgPool, id: Uuid) -> Result<(), AppError> {
+    sqlx::query!(
+        r#"
+        UPDATE api_keys
+        SET last_used_at = NOW()
+        WHERE id = $1 AND (last_used_at IS NULL OR last_used_at < NOW() - INTERVAL '1 minute')
+        "#,
+        id
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to update API key: {}", e)))?;
+
+    Ok(())
+}
+
+/// Revokes a key, returning `false` if it was already revoked.
+pub async fn revoke_api_key(pool: &PgPool, id: Uuid) -> Result<bool, AppError> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE api_keys
+        SET revoked_at = NOW()
+        WHERE id = $1 AND revoked_at IS NULL
+        "#,
+        id
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to revoke API key: {}", e)))?;
+
+    Ok(result.rows_affected() == 1)
+}
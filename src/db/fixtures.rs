@@ -0,0 +1,123 @@
+// src/db/fixtures.rs
+
+//! Data shared by the database tests.
+
+use async_trait::async_trait;
+use bigdecimal::BigDecimal;
+use sqlx::PgPool;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+use time::Date;
+
+use crate::AppState;
+use crate::auth::keys::JwtKeys;
+use crate::config::AuthConfig;
+use crate::db::{create_lease, create_property, create_user};
+use crate::errors::AppError;
+use crate::mailer::{EmailMessage, Mailer};
+use crate::models::{CreateLease, CreateProperty, Lease, LeaseStatus, RegisterUser, UserRole};
+
+/// Keeps every message instead of sending it, so tests can follow the links in them.
+#[derive(Default)]
+pub struct CapturingMailer {
+    pub sent: Mutex<Vec<EmailMessage>>,
+}
+
+#[async_trait]
+impl Mailer for CapturingMailer {
+    async fn send(&self, message: EmailMessage) -> Result<(), AppError> {
+        self.sent.lock().unwrap().push(message);
+        Ok(())
+    }
+}
+
+/// App state over the test database, signing tokens with a fixed secret and
+/// capturing mail. OIDC is left disabled.
+pub fn app_state(pool: PgPool) -> AppState {
+    AppState {
+        pool,
+        jwt_keys: Arc::new(JwtKeys::from_secret(
+            "test-secret",
+            "rust_api".into(),
+            "rust_api".into(),
+        )),
+        auth_config: AuthConfig::from_env(),
+        mailer: Arc::new(CapturingMailer::default()),
+        oidc: None,
+    }
+}
+
+/// Creates a user with a placeholder password, named and emailed after `username`.
+pub async fn seed_user(pool: &PgPool, username: &str, role: UserRole) -> Uuid {
+    let user = create_user(
+        pool,
+        RegisterUser {
+            username: username.into(),
+            email: format!("{}@example.com", username),
+            password: String::new(),
+            full_name: None,
+            phone: None,
+            role: Some(role),
+        },
+        "not-a-real-hash".into(),
+    )
+    .await
+    .unwrap();
+
+    user.id
+}
+
+/// Creates a tenant and a property for them to pay rent on, owned by a separate
+/// landlord.
+pub async fn seed_tenant_and_property(pool: &PgPool) -> (Uuid, Uuid) {
+    let landlord_id = seed_user(pool, "landlord", UserRole::Landlord).await;
+    let tenant_id = seed_user(pool, "tenant", UserRole::Tenant).await;
+
+    let property = create_property(
+        pool,
+        CreateProperty {
+            address: "1 Test Street".into(),
+            unit_number: None,
+            owner_id: None,
+        },
+        landlord_id,
+    )
+    .await
+    .unwrap();
+
+    (tenant_id, property.id)
+}
+
+/// Creates an active, open-ended lease of the property to the tenant, with rent due
+/// on the 1st.
+pub async fn seed_active_lease(
+    pool: &PgPool,
+    tenant_id: Uuid,
+    property_id: Uuid,
+    start_date: Date,
+    monthly_rent: &str,
+) -> Lease {
+    create_lease(
+        pool,
+        &CreateLease {
+            property_id,
+            tenant_ids: vec![tenant_id],
+            primary_tenant_id: None,
+            start_date,
+            end_date: None,
+            monthly_rent: money(monthly_rent),
+            deposit: None,
+            currency: None,
+            billing_day: None,
+            status: Some(LeaseStatus::Active),
+        },
+    )
+    .await
+    .unwrap()
+}
+
+pub fn money(amount: &str) -> BigDecimal {
+    BigDecimal::from_str(amount).unwrap()
+}
@@ -0,0 +1,15 @@
+// src/db/jobs.rs
+
+use crate::errors::AppError;
+use sqlx::PgConnection;
+
+/// Takes the advisory lock `key` for the rest of the current transaction, so only one
+/// server runs a job at a time. Returns `false` without waiting if another holds it.
+pub async fn try_lock_job(conn: &mut PgConnection, key: i64) -> Result<bool, AppError> {
+    let locked = sqlx::query_scalar!(r#"SELECT pg_try_advisory_xact_lock($1) AS "locked!""#, key)
+        .fetch_one(conn)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Failed to lock job: {}", e)))?;
+
+    Ok(locked)
+}
@@ -0,0 +1,213 @@
+// src/db/late_fee.rs
+
+use crate::db::post_journal_entry;
+use crate::errors::AppError;
+use crate::models::{
+    JournalEntryKind, JournalLine, LateFeeKind, LateFeePolicy, LateRent, LedgerAccount,
+    NewJournalEntry, PaymentKind, SetLateFeePolicy,
+};
+use bigdecimal::BigDecimal;
+use sqlx::{PgConnection, PgPool};
+use time::Date;
+use uuid::Uuid;
+
+/// Finds the late fee policy of a property.
+pub async fn find_late_fee_policy(
+    pool: &PgPool,
+    property_id: Uuid,
+) -> Result<Option<LateFeePolicy>, AppError> {
+    let policy = sqlx::query_as!(
+        LateFeePolicy,
+        r#"
+        SELECT
+            property_id, kind as "kind!: LateFeeKind", amount, percentage, grace_days,
+            max_amount, max_percentage, created_at, updated_at
+        FROM late_fee_policies
+        WHERE property_id = $1
+        "#,
+        property_id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to find late fee policy: {}", e)))?;
+
+    Ok(policy)
+}
+
+/// Lists every late fee policy, for the late fee job.
+pub async fn list_late_fee_policies(
+    conn: &mut PgConnection,
+) -> Result<Vec<LateFeePolicy>, AppError> {
+    let policies = sqlx::query_as!(
+        LateFeePolicy,
+        r#"
+        SELECT
+            property_id, kind as "kind!: LateFeeKind", amount, percentage, grace_days,
+            max_amount, max_percentage, created_at, updated_at
+        FROM late_fee_policies
+        "#
+    )
+    .fetch_all(conn)
+    .await
+    .map_err(|e| {
+        AppError::InternalServerError(format!("Failed to list late fee policies: {}", e))
+    })?;
+
+    Ok(policies)
+}
+
+/// Sets the late fee policy of a property, replacing any it had. Fees already
+/// charged are not changed.
+pub async fn set_late_fee_policy(
+    pool: &PgPool,
+    property_id: Uuid,
+    policy: &SetLateFeePolicy,
+) -> Result<LateFeePolicy, AppError> {
+    let policy = sqlx::query_as!(
+        LateFeePolicy,
+        r#"
+        INSERT INTO late_fee_policies (
+            property_id, kind, amount, percentage, grace_days, max_amount, max_percentage
+        )
+        VALUES ($1, $2, $3, $4, COALESCE($5::smallint, 0), $6, $7)
+        ON CONFLICT (property_id) DO UPDATE
+        SET kind = EXCLUDED.kind,
+            amount = EXCLUDED.amount,
+            percentage = EXCLUDED.percentage,
+            grace_days = EXCLUDED.grace_days,
+            max_amount = EXCLUDED.max_amount,
+            max_percentage = EXCLUDED.max_percentage,
+            updated_at = NOW()
+        RETURNING
+            property_id, kind as "kind!: LateFeeKind", amount, percentage, grace_days,
+            max_amount, max_percentage, created_at, updated_at
+        "#,
+        property_id,
+        policy.kind as LateFeeKind,
+        policy.amount,
+        policy.percentage,
+        policy.grace_days,
+        policy.max_amount,
+        policy.max_percentage,
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to set late fee policy: {}", e)))?;
+
+    Ok(policy)
+}
+
+/// Removes the late fee policy of a property, so no further fees are charged there.
+/// Returns whether it had one.
+pub async fn delete_late_fee_policy(pool: &PgPool, property_id: Uuid) -> Result<bool, AppError> {
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM late_fee_policies
+        WHERE property_id = $1
+        "#,
+        property_id
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| {
+        AppError::InternalServerError(format!("Failed to delete late fee policy: {}", e))
+    })?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Lists overdue rent and manually recorded charges on properties with a late fee
+/// policy, for the late fee job. Late fees themselves never get a fee.
+pub async fn list_late_rent(conn: &mut PgConnection) -> Result<Vec<LateRent>, AppError> {
+    let late_rent = sqlx::query_as!(
+        LateRent,
+        r#"
+        SELECT
+            p.id AS payment_id, p.user_id, p.property_id AS "property_id!", p.lease_id,
+            p.amount, p.currency, p.notes, p.due_date AS "due_date!"
+        FROM payments p
+        JOIN late_fee_policies lfp ON lfp.property_id = p.property_id
+        WHERE p.kind <> 'late_fee' AND p.status = 'overdue' AND p.due_date IS NOT NULL
+        ORDER BY p.due_date, p.id
+        "#
+    )
+    .fetch_all(conn)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to list late rent: {}", e)))?;
+
+    Ok(late_rent)
+}
+
+/// Charges `fee` for paying `late` late, due on `due_date`, or raises the existing
+/// fee to `fee` if it is lower and still unpaid, even if the fee itself has become
+/// overdue, posting the new fee or the rise to the ledger. Returns whether a fee was
+/// created or raised.
+///
+/// Fees are unique per late payment, so running the late fee job again, or on
+/// several servers at once, never charges a payment twice.
+pub async fn upsert_late_fee(
+    conn: &mut PgConnection,
+    late: &LateRent,
+    fee: &BigDecimal,
+    due_date: Date,
+) -> Result<bool, AppError> {
+    let notes = match &late.notes {
+        Some(notes) => format!("Late fee: {}", notes),
+        None => format!("Late fee for the payment due {}", late.due_date),
+    };
+
+    let changed = sqlx::query!(
+        r#"
+        WITH previous AS (
+            SELECT amount FROM payments WHERE parent_payment_id = $4
+        )
+        INSERT INTO payments (
+            user_id, property_id, lease_id, parent_payment_id, kind, amount, currency,
+            status, notes, due_date
+        )
+        VALUES ($1, $2, $3, $4, 'late_fee', $5, $6, 'pending', $7, $8)
+        ON CONFLICT (parent_payment_id) WHERE parent_payment_id IS NOT NULL DO UPDATE
+        SET amount = EXCLUDED.amount, updated_at = NOW()
+        WHERE payments.status IN ('pending', 'overdue', 'partially_paid')
+          AND payments.amount < EXCLUDED.amount
+        RETURNING id, amount - COALESCE((SELECT amount FROM previous), 0) AS "increase!"
+        "#,
+        late.user_id,
+        late.property_id,
+        late.lease_id,
+        late.payment_id,
+        fee,
+        late.currency,
+        notes,
+        due_date,
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to charge late fee: {}", e)))?;
+
+    let Some(changed) = changed else {
+        return Ok(false);
+    };
+    post_journal_entry(
+        conn,
+        &NewJournalEntry {
+            kind: JournalEntryKind::Charge,
+            user_id: late.user_id,
+            property_id: Some(late.property_id),
+            currency: late.currency.clone(),
+            entry_date: due_date,
+            memo: Some(notes),
+            payment_id: Some(changed.id),
+            receipt_id: None,
+            created_by: None,
+            lines: JournalLine::transfer(
+                LedgerAccount::RentReceivable,
+                PaymentKind::LateFee.income_account(),
+                &changed.increase,
+            ),
+        },
+    )
+    .await?;
+
+    Ok(true)
+}
@@ -0,0 +1,437 @@
+use crate::db::{push_keyset_condition, push_order_and_limit};
+use crate::errors::{AppError, FieldError};
+use crate::models::{
+    BillableLease, CreateLease, Cursor, Lease, LeaseStatus, ListLeasesQuery, SortOrder, UpdateLease,
+};
+use sqlx::{PgConnection, PgPool, Postgres, QueryBuilder};
+use time::Date;
+use uuid::Uuid;
+
+/// Postgres error code for a violated exclusion constraint.
+const EXCLUSION_VIOLATION: &str = "23P01";
+
+/// Creates a lease together with its tenants.
+pub async fn create_lease(pool: &PgPool, new_lease: &CreateLease) -> Result<Lease, AppError> {
+    let mut tx = pool.begin().await?;
+
+    let id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO leases (property_id, primary_tenant_id, start_date, end_date, monthly_rent, deposit, currency, billing_day, status)
+        VALUES ($1, $2, $3, $4, $5, COALESCE($6::numeric, 0), COALESCE($7::text, 'USD'), COALESCE($8::smallint, 1), $9)
+        RETURNING id
+        "#,
+        new_lease.property_id,
+        new_lease.primary_tenant_id(),
+        new_lease.start_date,
+        new_lease.end_date,
+        new_lease.monthly_rent,
+        new_lease.deposit,
+        new_lease.currency,
+        new_lease.billing_day,
+        new_lease.status.unwrap_or(LeaseStatus::Draft) as LeaseStatus,
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| lease_write_error(e, "create"))?;
+
+    add_lease_tenants(&mut tx, id, &new_lease.tenant_ids).await?;
+    let lease = fetch_lease(&mut tx, id).await?.ok_or_else(|| {
+        AppError::InternalServerError("Created lease could not be read back".into())
+    })?;
+
+    tx.commit()
+        .await
+        .map_err(|e| lease_write_error(e, "create"))?;
+
+    Ok(lease)
+}
+
+/// Finds a lease by its id.
+pub async fn find_lease_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Lease>, AppError> {
+    let mut conn = pool.acquire().await?;
+    fetch_lease(&mut conn, id).await
+}
+
+/// Lists one page of leases matching the filters, newest start date first,
+/// optionally restricted to properties owned by `owner_id`.
+///
+/// Pass the cursor of the previous page in `after`. Fetch one row more than the page
+/// size to find out whether another page follows.
+pub async fn list_leases(
+    pool: &PgPool,
+    filter: &ListLeasesQuery,
+    owner_id: Option<Uuid>,
+    after: Option<&Cursor>,
+    limit: i64,
+) -> Result<Vec<Lease>, AppError> {
+    let mut builder = QueryBuilder::<Postgres>::new(
+        r#"
+        SELECT
+            l.id, l.property_id,
+            ARRAY(
+                SELECT lt.tenant_id FROM lease_tenants lt
+                WHERE lt.lease_id = l.id
+                ORDER BY lt.tenant_id
+            ) AS tenant_ids,
+            l.primary_tenant_id, l.start_date, l.end_date, l.monthly_rent, l.deposit, l.currency, l.billing_day,
+            l.status, l.created_at, l.updated_at
+        FROM leases l
+        WHERE TRUE
+        "#,
+    );
+
+    if let Some(property_id) = filter.property_id {
+        builder.push(" AND property_id = ").push_bind(property_id);
+    }
+    if let Some(tenant_id) = filter.tenant_id {
+        builder
+            .push(" AND EXISTS (SELECT 1 FROM lease_tenants lt WHERE lt.lease_id = l.id AND lt.tenant_id = ")
+            .push_bind(tenant_id)
+            .push(")");
+    }
+    if let Some(status) = filter.status {
+        builder.push(" AND status = ").push_bind(status);
+    }
+    if let Some(owner_id) = owner_id {
+        builder
+            .push(" AND property_id IN (SELECT id FROM properties WHERE owner_id = ")
+            .push_bind(owner_id)
+            .push(")");
+    }
+
+    let order = SortOrder::Desc;
+    if let Some(cursor) = after {
+        push_keyset_condition(&mut builder, "start_date", "date", order, cursor);
+    }
+    push_order_and_limit(&mut builder, "start_date", order, limit);
+
+    let leases = builder
+        .build_query_as::<Lease>()
+        .fetch_all(pool)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Failed to list leases: {}", e)))?;
+
+    Ok(leases)
+}
+
+/// Changes the terms of a lease, replacing its tenants if new ones are given. The
+/// primary tenant is kept unless another is given or they are no longer a tenant, in
+/// which case the first of the new tenants takes over.
+///
+/// Only applies while the lease still has the `expected` status, so a concurrent
+/// status change cannot let fixed terms of an active lease be edited. Returns
+/// `None` if the lease does not exist or its status has changed.
+pub async fn update_lease(
+    pool: &PgPool,
+    id: Uuid,
+    expected: LeaseStatus,
+    changes: &UpdateLease,
+) -> Result<Option<Lease>, AppError> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query!(
+        r#"
+        UPDATE leases
+        SET start_date = COALESCE($3, start_date),
+            end_date = CASE WHEN $4 THEN $5 ELSE end_date END,
+            monthly_rent = COALESCE($6, monthly_rent),
+            deposit = COALESCE($7, deposit),
+            currency = COALESCE($8, currency),
+            billing_day = COALESCE($9, billing_day),
+            primary_tenant_id = CASE
+                WHEN $10::uuid IS NOT NULL THEN $10
+                WHEN $11::uuid[] IS NOT NULL AND NOT primary_tenant_id = ANY($11) THEN $11[1]
+                ELSE primary_tenant_id
+            END,
+            updated_at = NOW()
+        WHERE id = $1 AND status = $2
+        "#,
+        id,
+        expected as LeaseStatus,
+        changes.start_date,
+        changes.end_date.is_some(),
+        changes.end_date.flatten(),
+        changes.monthly_rent,
+        changes.deposit,
+        changes.currency,
+        changes.billing_day,
+        changes.primary_tenant_id,
+        changes.tenant_ids.as_deref(),
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| lease_write_error(e, "update"))?;
+
+    if result.rows_affected() == 0 {
+        return Ok(None);
+    }
+
+    if let Some(tenant_ids) = &changes.tenant_ids {
+        sqlx::query!(
+            r#"
+            DELETE FROM lease_tenants
+            WHERE lease_id = $1
+            "#,
+            id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Failed to update lease: {}", e)))?;
+
+        add_lease_tenants(&mut tx, id, tenant_ids).await?;
+    }
+
+    let lease = fetch_lease(&mut tx, id).await?;
+    tx.commit()
+        .await
+        .map_err(|e| lease_write_error(e, "update"))?;
+
+    Ok(lease)
+}
+
+/// Moves a lease from one status to another, setting its last day if given.
+///
+/// Returns `None` if the lease no longer has the `from` status, e.g. because a
+/// concurrent request changed it first.
+pub async fn update_lease_status(
+    pool: &PgPool,
+    id: Uuid,
+    from: LeaseStatus,
+    to: LeaseStatus,
+    end_date: Option<Date>,
+) -> Result<Option<Lease>, AppError> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query!(
+        r#"
+        UPDATE leases
+        SET status = $3,
+            end_date = COALESCE($4, end_date),
+            updated_at = NOW()
+        WHERE id = $1 AND status = $2
+        "#,
+        id,
+        from as LeaseStatus,
+        to as LeaseStatus,
+        end_date,
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| lease_write_error(e, "update"))?;
+
+    if result.rows_affected() == 0 {
+        return Ok(None);
+    }
+
+    let lease = fetch_lease(&mut tx, id).await?;
+    tx.commit().await?;
+
+    Ok(lease)
+}
+
+/// Lists the active leases that run for at least part of `from..=until`, for the
+/// rent scheduler.
+pub async fn list_billable_leases(
+    conn: &mut PgConnection,
+    from: Date,
+    until: Date,
+) -> Result<Vec<BillableLease>, AppError> {
+    let leases = sqlx::query_as!(
+        BillableLease,
+        r#"
+        SELECT
+            l.id, l.property_id, l.primary_tenant_id AS tenant_id, l.start_date, l.end_date, l.monthly_rent, l.currency, l.billing_day
+        FROM leases l
+        WHERE l.status = 'active'
+          AND l.start_date <= $2
+          AND (l.end_date IS NULL OR l.end_date >= $1)
+        ORDER BY l.id
+        "#,
+        from,
+        until,
+    )
+    .fetch_all(conn)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to list billable leases: {}", e)))?;
+
+    Ok(leases)
+}
+
+async fn fetch_lease(conn: &mut PgConnection, id: Uuid) -> Result<Option<Lease>, AppError> {
+    let lease = sqlx::query_as!(
+        Lease,
+        r#"
+        SELECT
+            l.id, l.property_id,
+            ARRAY(
+                SELECT lt.tenant_id FROM lease_tenants lt
+                WHERE lt.lease_id = l.id
+                ORDER BY lt.tenant_id
+            ) AS "tenant_ids!",
+            l.primary_tenant_id, l.start_date, l.end_date, l.monthly_rent, l.deposit, l.currency, l.billing_day,
+            l.status AS "status: LeaseStatus", l.created_at, l.updated_at
+        FROM leases l
+        WHERE l.id = $1
+        "#,
+        id
+    )
+    .fetch_optional(conn)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to find lease: {}", e)))?;
+
+    Ok(lease)
+}
+
+/// Adds tenants to a lease, checking they all exist and have tenant accounts.
+async fn add_lease_tenants(
+    conn: &mut PgConnection,
+    lease_id: Uuid,
+    tenant_ids: &[Uuid],
+) -> Result<(), AppError> {
+    let result = sqlx::query!(
+        r#"
+        INSERT INTO lease_tenants (lease_id, tenant_id)
+        SELECT $1, id
+        FROM users
+        WHERE id = ANY($2) AND role = 'tenant'
+        "#,
+        lease_id,
+        tenant_ids,
+    )
+    .execute(conn)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to add lease tenants: {}", e)))?;
+
+    if result.rows_affected() != tenant_ids.len() as u64 {
+        return Err(AppError::Validation(vec![FieldError::new(
+            "tenant_ids",
+            "invalid",
+            "Must all be existing tenant accounts",
+        )]));
+    }
+
+    Ok(())
+}
+
+/// Maps the constraint violations a lease write can run into.
+fn lease_write_error(e: sqlx::Error, action: &str) -> AppError {
+    if let Some(db_err) = e.as_database_error() {
+        if db_err.code().as_deref() == Some(EXCLUSION_VIOLATION) {
+            return AppError::Conflict(
+                "The lease overlaps another active lease for this property".into(),
+            );
+        }
+        let (field, code, message) = match db_err.constraint() {
+            Some("leases_check") => ("end_date", "range", "Must not be before start_date"),
+            Some("leases_monthly_rent_check") => {
+                ("monthly_rent", "range", "Must be greater than zero")
+            }
+            Some("leases_deposit_check") => ("deposit", "range", "Must not be negative"),
+            Some("leases_billing_day_check") => {
+                ("billing_day", "range", "Must be between 1 and 28")
+            }
+            Some("leases_primary_tenant_fkey") => (
+                "primary_tenant_id",
+                "invalid",
+                "Must be one of the lease's tenants",
+            ),
+            Some("leases_property_id_fkey") => {
+                ("property_id", "invalid", "Must be an existing property")
+            }
+            _ => {
+                return AppError::InternalServerError(format!("Failed to {} lease: {}", action, e));
+            }
+        };
+        return AppError::Validation(vec![FieldError::new(field, code, message)]);
+    }
+    AppError::InternalServerError(format!("Failed to {} lease: {}", action, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::find_property_by_id;
+    use crate::db::fixtures::{money, seed_active_lease, seed_tenant_and_property};
+    use time::macros::date;
+
+    #[sqlx::test]
+    async fn landlords_only_list_the_leases_of_their_own_properties(pool: PgPool) {
+        let (tenant_id, property_id) = seed_tenant_and_property(&pool).await;
+        let lease =
+            seed_active_lease(&pool, tenant_id, property_id, date!(2025 - 06 - 01), "1000").await;
+        let landlord_id = find_property_by_id(&pool, property_id)
+            .await
+            .unwrap()
+            .and_then(|property| property.owner_id)
+            .unwrap();
+
+        for (owner_id, expected) in [(landlord_id, vec![lease.id]), (tenant_id, vec![])] {
+            let leases = list_leases(&pool, &ListLeasesQuery::default(), Some(owner_id), None, 10)
+                .await
+                .unwrap();
+            let ids: Vec<_> = leases.iter().map(|lease| lease.id).collect();
+            assert_eq!(ids, expected);
+        }
+    }
+
+    #[sqlx::test]
+    async fn each_broken_constraint_is_reported_on_its_own_field(pool: PgPool) {
+        let (tenant_id, property_id) = seed_tenant_and_property(&pool).await;
+        let lease = || CreateLease {
+            property_id,
+            tenant_ids: vec![tenant_id],
+            primary_tenant_id: None,
+            start_date: date!(2025 - 06 - 01),
+            end_date: None,
+            monthly_rent: money("1000.00"),
+            deposit: None,
+            currency: None,
+            billing_day: None,
+            status: None,
+        };
+
+        let broken = [
+            (
+                CreateLease {
+                    end_date: Some(date!(2025 - 05 - 31)),
+                    ..lease()
+                },
+                "end_date",
+            ),
+            (
+                CreateLease {
+                    monthly_rent: money("0"),
+                    ..lease()
+                },
+                "monthly_rent",
+            ),
+            (
+                CreateLease {
+                    deposit: Some(money("-1")),
+                    ..lease()
+                },
+                "deposit",
+            ),
+            (
+                CreateLease {
+                    billing_day: Some(31),
+                    ..lease()
+                },
+                "billing_day",
+            ),
+            (
+                CreateLease {
+                    property_id: Uuid::new_v4(),
+                    ..lease()
+                },
+                "property_id",
+            ),
+        ];
+        for (new_lease, field) in broken {
+            match create_lease(&pool, &new_lease).await {
+                Err(AppError::Validation(errors)) => assert_eq!(errors[0].field, field),
+                other => panic!("expected a validation error on {}, got {:?}", field, other),
+            }
+        }
+    }
+}
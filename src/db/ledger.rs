@@ -0,0 +1,329 @@
+// src/db/ledger.rs
+
+use bigdecimal::BigDecimal;
+use sqlx::{PgConnection, PgPool};
+use time::Date;
+use uuid::Uuid;
+
+use crate::errors::AppError;
+use crate::models::{
+    JournalEntry, JournalEntryKind, LedgerAccount, LedgerLine, LedgerQuery, LedgerStatement,
+    NewJournalEntry,
+};
+
+/// Posts a journal entry and its lines.
+///
+/// Entries that do not balance are refused here, and again by the database when the
+/// transaction commits, so post every entry in the same transaction as the change
+/// it records.
+pub async fn post_journal_entry(
+    conn: &mut PgConnection,
+    entry: &NewJournalEntry,
+) -> Result<JournalEntry, AppError> {
+    if !entry.is_balanced() {
+        return Err(AppError::InternalServerError(format!(
+            "Refusing to post an unbalanced {:?} entry",
+            entry.kind
+        )));
+    }
+
+    let row = sqlx::query!(
+        r#"
+        INSERT INTO journal_entries (
+            kind, user_id, property_id, currency, entry_date, memo, payment_id, receipt_id, created_by
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING id, created_at
+        "#,
+        entry.kind as JournalEntryKind,
+        entry.user_id,
+        entry.property_id,
+        entry.currency,
+        entry.entry_date,
+        entry.memo,
+        entry.payment_id,
+        entry.receipt_id,
+        entry.created_by,
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to post journal entry: {}", e)))?;
+
+    for line in &entry.lines {
+        sqlx::query!(
+            r#"
+            INSERT INTO journal_lines (entry_id, account, debit, credit)
+            VALUES ($1, $2, $3, $4)
+            "#,
+            row.id,
+            line.account as LedgerAccount,
+            line.debit,
+            line.credit,
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| {
+            AppError::InternalServerError(format!("Failed to post journal line: {}", e))
+        })?;
+    }
+
+    Ok(JournalEntry {
+        id: row.id,
+        kind: entry.kind,
+        user_id: entry.user_id,
+        property_id: entry.property_id,
+        currency: entry.currency.clone(),
+        entry_date: entry.entry_date,
+        memo: entry.memo.clone(),
+        payment_id: entry.payment_id,
+        receipt_id: entry.receipt_id,
+        created_by: entry.created_by,
+        created_at: row.created_at,
+        lines: entry.lines.clone(),
+    })
+}
+
+/// Builds the statement of one account in one currency up to `to`, optionally from
+/// `filter.from`, for the tenant and property in `filter`, optionally restricted to
+/// properties owned by `owner_id`.
+///
+/// Each line carries the account's running balance, counted from its first entry,
+/// in the direction that account normally grows: what tenants owe for rent
+/// receivable, what is held for deposits held, and so on.
+pub async fn ledger_statement(
+    pool: &PgPool,
+    filter: &LedgerQuery,
+    owner_id: Option<Uuid>,
+    account: LedgerAccount,
+    currency: &str,
+    to: Date,
+) -> Result<LedgerStatement, AppError> {
+    let debit_normal = account.is_debit_normal();
+
+    let opening_balance = match filter.from {
+        Some(from) => sqlx::query_scalar!(
+            r#"
+            SELECT COALESCE(SUM(CASE WHEN $5 THEN l.debit - l.credit ELSE l.credit - l.debit END), 0)
+                AS "balance!"
+            FROM journal_lines l
+            JOIN journal_entries e ON e.id = l.entry_id
+            WHERE l.account = $1
+              AND e.currency = $2
+              AND ($3::uuid IS NULL OR e.user_id = $3)
+              AND ($4::uuid IS NULL OR e.property_id = $4)
+              AND ($6::uuid IS NULL OR e.property_id IN (SELECT id FROM properties WHERE owner_id = $6))
+              AND e.entry_date < $7
+            "#,
+            account as LedgerAccount,
+            currency,
+            filter.user_id,
+            filter.property_id,
+            debit_normal,
+            owner_id,
+            from,
+        )
+        .fetch_one(pool)
+        .await
+        .map_err(|e| {
+            AppError::InternalServerError(format!("Failed to sum ledger balance: {}", e))
+        })?,
+        None => BigDecimal::from(0),
+    };
+
+    let lines = sqlx::query_as!(
+        LedgerLine,
+        r#"
+        WITH lines AS (
+            SELECT
+                e.id AS entry_id, e.entry_date, e.kind, e.memo, e.user_id, e.property_id,
+                e.payment_id, e.receipt_id, l.debit, l.credit, e.created_at, l.id AS line_id,
+                SUM(CASE WHEN $5 THEN l.debit - l.credit ELSE l.credit - l.debit END)
+                    OVER (ORDER BY e.entry_date, e.created_at, l.id) AS balance
+            FROM journal_lines l
+            JOIN journal_entries e ON e.id = l.entry_id
+            WHERE l.account = $1
+              AND e.currency = $2
+              AND ($3::uuid IS NULL OR e.user_id = $3)
+              AND ($4::uuid IS NULL OR e.property_id = $4)
+              AND ($6::uuid IS NULL OR e.property_id IN (SELECT id FROM properties WHERE owner_id = $6))
+              AND e.entry_date <= $8
+        )
+        SELECT
+            entry_id AS "entry_id!", entry_date AS "entry_date!",
+            kind AS "kind!: JournalEntryKind", memo, user_id AS "user_id!", property_id,
+            payment_id, receipt_id, debit AS "debit!", credit AS "credit!",
+            balance AS "balance!"
+        FROM lines
+        WHERE $7::date IS NULL OR entry_date >= $7
+        ORDER BY entry_date, created_at, line_id
+        "#,
+        account as LedgerAccount,
+        currency,
+        filter.user_id,
+        filter.property_id,
+        debit_normal,
+        owner_id,
+        filter.from,
+        to,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to list ledger lines: {}", e)))?;
+
+    let closing_balance = lines
+        .last()
+        .map_or_else(|| opening_balance.clone(), |line| line.balance.clone());
+
+    Ok(LedgerStatement {
+        account,
+        currency: currency.to_string(),
+        from: filter.from,
+        to,
+        opening_balance,
+        closing_balance,
+        lines,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::fixtures::{money, seed_tenant_and_property};
+    use crate::db::{create_payment, create_receipt, find_property_by_id};
+    use crate::models::{CreatePayment, CreateReceipt, JournalLine, PaymentStatus};
+    use time::macros::date;
+
+    #[sqlx::test]
+    async fn statements_carry_a_running_balance_of_charges_and_receipts(pool: PgPool) {
+        let (tenant_id, property_id) = seed_tenant_and_property(&pool).await;
+
+        for due_date in [date!(2025 - 05 - 01), date!(2025 - 06 - 01)] {
+            create_payment(
+                &pool,
+                CreatePayment {
+                    user_id: tenant_id,
+                    property_id,
+                    amount: money("1000.00"),
+                    currency: "USD".into(),
+                    notes: None,
+                    due_date: Some(due_date),
+                    period_start: None,
+                    period_end: None,
+                },
+                PaymentStatus::Pending,
+                None,
+            )
+            .await
+            .unwrap();
+        }
+        create_receipt(
+            &pool,
+            &CreateReceipt {
+                user_id: tenant_id,
+                property_id,
+                amount: money("1200.00"),
+                currency: "USD".into(),
+                received_on: Some(date!(2025 - 05 - 20)),
+                transaction_id: None,
+                notes: None,
+                allocations: None,
+            },
+            tenant_id,
+        )
+        .await
+        .unwrap();
+
+        let filter = LedgerQuery {
+            user_id: Some(tenant_id),
+            from: Some(date!(2025 - 05 - 15)),
+            ..Default::default()
+        };
+        let statement = ledger_statement(
+            &pool,
+            &filter,
+            None,
+            LedgerAccount::RentReceivable,
+            "USD",
+            date!(2025 - 06 - 30),
+        )
+        .await
+        .unwrap();
+        assert_eq!(statement.opening_balance, money("1000.00"));
+        let balances: Vec<_> = statement
+            .lines
+            .iter()
+            .map(|line| (line.kind, line.balance.clone()))
+            .collect();
+        assert_eq!(
+            balances,
+            [
+                (JournalEntryKind::Receipt, money("-200.00")),
+                (JournalEntryKind::Charge, money("800.00")),
+            ]
+        );
+        assert_eq!(statement.closing_balance, money("800.00"));
+
+        // Landlords only see the lines of properties they own
+        let landlord_id = find_property_by_id(&pool, property_id)
+            .await
+            .unwrap()
+            .and_then(|property| property.owner_id)
+            .unwrap();
+        for (owner_id, cash_held) in [(landlord_id, "1200.00"), (tenant_id, "0")] {
+            let cash = ledger_statement(
+                &pool,
+                &LedgerQuery::default(),
+                Some(owner_id),
+                LedgerAccount::Cash,
+                "USD",
+                date!(2025 - 06 - 30),
+            )
+            .await
+            .unwrap();
+            assert_eq!(cash.closing_balance, money(cash_held));
+        }
+    }
+
+    #[sqlx::test]
+    async fn the_database_refuses_unbalanced_entries(pool: PgPool) {
+        let (tenant_id, _) = seed_tenant_and_property(&pool).await;
+
+        let mut entry = NewJournalEntry {
+            kind: JournalEntryKind::Credit,
+            user_id: tenant_id,
+            property_id: None,
+            currency: "USD".into(),
+            entry_date: date!(2025 - 06 - 01),
+            memo: None,
+            payment_id: None,
+            receipt_id: None,
+            created_by: None,
+            lines: JournalLine::transfer(
+                LedgerAccount::RentIncome,
+                LedgerAccount::RentReceivable,
+                &money("50.00"),
+            ),
+        };
+        entry.lines[1].credit = money("40.00");
+
+        let mut tx = pool.begin().await.unwrap();
+        assert!(post_journal_entry(&mut tx, &entry).await.is_err());
+
+        // Bypassing the check in Rust still fails when the transaction commits
+        let entry_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO journal_entries (kind, user_id, currency, entry_date) VALUES ('credit', $1, 'USD', '2025-06-01') RETURNING id",
+        )
+        .bind(tenant_id)
+        .fetch_one(&mut *tx)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO journal_lines (entry_id, account, debit) VALUES ($1, 'rent_income', 50)",
+        )
+        .bind(entry_id)
+        .execute(&mut *tx)
+        .await
+        .unwrap();
+        assert!(tx.commit().await.is_err());
+    }
+}
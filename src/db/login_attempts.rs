@@ -0,0 +1,126 @@
+use crate::errors::AppError;
+use chrono::Duration;
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// Appends a login attempt to the audit trail.
+pub async fn record_login_attempt(
+    pool: &PgPool,
+    identifier: &str,
+    user_id: Option<Uuid>,
+    ip_address: Option<&str>,
+    failure_reason: Option<&str>,
+) -> Result<(), AppError> {
+    sqlx::query!(
+        r#"
+        INSERT INTO login_attempts (identifier, user_id, ip_address, succeeded, failure_reason)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+        identifier,
+        user_id,
+        ip_address,
+        failure_reason.is_none(),
+        failure_reason,
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to record login attempt: {}", e)))?;
+
+    Ok(())
+}
+
+/// Works out when an identifier that matches no account stops being locked out, if it is.
+///
+/// Mirrors `record_failed_login` so unknown identifiers lock exactly like real accounts:
+/// every `max_failures` wrong passwords lock them from the last failure for a lockout
+/// that doubles each time, and attempts refused while locked do not count.
+///
+/// As no login ever succeeds to reset them, failures older than `max_lockout`, the
+/// longest any lockout lasts, are forgotten; an identifier probed once is not locked
+/// for good.
+pub async fn unknown_identifier_locked_until(
+    pool: &PgPool,
+    identifier: &str,
+    max_failures: i32,
+    lockout: Duration,
+    max_lockout: Duration,
+) -> Result<Option<OffsetDateTime>, AppError> {
+    let locked_until = sqlx::query_scalar!(
+        r#"
+        WITH failures AS (
+            SELECT COUNT(*) AS count, MAX(created_at) AS last_failed_at
+            FROM login_attempts
+            WHERE identifier = $1
+              AND failure_reason = 'unknown_user'
+              AND created_at > NOW() - $4 * INTERVAL '1 second'
+        )
+        SELECT CASE
+            WHEN count > 0 AND count % $2::int = 0 THEN last_failed_at + LEAST(
+                $3 * POWER(2, count / $2 - 1),
+                $4
+            ) * INTERVAL '1 second'
+        END AS locked_until
+        FROM failures
+        "#,
+        identifier,
+        max_failures,
+        lockout.num_seconds() as f64,
+        max_lockout.num_seconds() as f64,
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to count login attempts: {}", e)))?;
+
+    Ok(locked_until)
+}
+
+/// Counts failed login attempts from an IP address since `since`.
+pub async fn count_failed_logins_for_ip(
+    pool: &PgPool,
+    ip_address: &str,
+    since: OffsetDateTime,
+) -> Result<i64, AppError> {
+    let count = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*) as "count!"
+        FROM login_attempts
+        WHERE ip_address = $1 AND NOT succeeded AND created_at > $2
+        "#,
+        ip_address,
+        since
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to count login attempts: {}", e)))?;
+
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[sqlx::test]
+    async fn unknown_identifiers_forget_failures_older_than_the_longest_lockout(pool: PgPool) {
+        let (lockout, max_lockout) = (Duration::minutes(15), Duration::hours(24));
+        for _ in 0..5 {
+            record_login_attempt(&pool, "ghost", None, None, Some("unknown_user"))
+                .await
+                .unwrap();
+        }
+        let locked_until = unknown_identifier_locked_until(&pool, "ghost", 5, lockout, max_lockout)
+            .await
+            .unwrap();
+        assert!(locked_until.is_some_and(|until| until > OffsetDateTime::now_utc()));
+
+        sqlx::query("UPDATE login_attempts SET created_at = created_at - INTERVAL '25 hours'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let locked_until = unknown_identifier_locked_until(&pool, "ghost", 5, lockout, max_lockout)
+            .await
+            .unwrap();
+        assert_eq!(locked_until, None);
+    }
+}
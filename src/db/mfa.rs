@@ -0,0 +1,154 @@
+use crate::errors::AppError;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// Stores a new, not yet confirmed, TOTP secret for a user.
+pub async fn set_pending_totp_secret(
+    pool: &PgPool,
+    user_id: Uuid,
+    secret: &str,
+) -> Result<(), AppError> {
+    sqlx::query!(
+        r#"
+        UPDATE users
+        SET totp_secret = $2, totp_enabled_at = NULL, totp_last_used_step = NULL, updated_at = NOW()
+        WHERE id = $1
+        "#,
+        user_id,
+        secret
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to store TOTP secret: {}", e)))?;
+
+    Ok(())
+}
+
+/// Activates TOTP for a user and replaces their recovery codes.
+pub async fn enable_totp(
+    pool: &PgPool,
+    user_id: Uuid,
+    used_step: i64,
+    recovery_code_hashes: &[String],
+) -> Result<(), AppError> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE users
+        SET totp_enabled_at = NOW(), totp_last_used_step = $2, updated_at = NOW()
+        WHERE id = $1
+        "#,
+        user_id,
+        used_step
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to enable TOTP: {}", e)))?;
+
+    sqlx::query!(
+        r#"
+        DELETE FROM mfa_recovery_codes
+        WHERE user_id = $1
+        "#,
+        user_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        AppError::InternalServerError(format!("Failed to replace recovery codes: {}", e))
+    })?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO mfa_recovery_codes (user_id, code_hash)
+        SELECT $1, UNNEST($2::text[])
+        "#,
+        user_id,
+        recovery_code_hashes
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to store recovery codes: {}", e)))?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Removes TOTP and all recovery codes from a user.
+pub async fn disable_totp(pool: &PgPool, user_id: Uuid) -> Result<(), AppError> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE users
+        SET totp_secret = NULL, totp_enabled_at = NULL, totp_last_used_step = NULL, updated_at = NOW()
+        WHERE id = $1
+        "#,
+        user_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to disable TOTP: {}", e)))?;
+
+    sqlx::query!(
+        r#"
+        DELETE FROM mfa_recovery_codes
+        WHERE user_id = $1
+        "#,
+        user_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| {
+        AppError::InternalServerError(format!("Failed to delete recovery codes: {}", e))
+    })?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Records the time step of an accepted TOTP code.
+///
+/// Returns `false` if an equal or later step was already used, so each code
+/// can only be accepted once even by concurrent requests.
+pub async fn record_totp_step(pool: &PgPool, user_id: Uuid, step: i64) -> Result<bool, AppError> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE users
+        SET totp_last_used_step = $2
+        WHERE id = $1 AND (totp_last_used_step IS NULL OR totp_last_used_step < $2)
+        "#,
+        user_id,
+        step
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to record TOTP code: {}", e)))?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+/// Marks an unused recovery code as used. Returns `false` if no such code exists.
+pub async fn consume_recovery_code(
+    pool: &PgPool,
+    user_id: Uuid,
+    code_hash: &str,
+) -> Result<bool, AppError> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE mfa_recovery_codes
+        SET used_at = NOW()
+        WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL
+        "#,
+        user_id,
+        code_hash
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to use recovery code: {}", e)))?;
+
+    Ok(result.rows_affected() == 1)
+}
@@ -1,9 +1,35 @@
 // Declare the sub-modules
+pub mod api_keys;
+#[cfg(test)]
+pub mod fixtures;
+pub mod jobs;
+pub mod late_fee;
+pub mod lease;
+pub mod ledger;
+pub mod login_attempts;
+pub mod mfa;
+pub mod oidc;
+pub mod pagination;
 pub mod payment;
 pub mod property;
+pub mod receipt;
+pub mod refresh_tokens;
+pub mod user_tokens;
 pub mod users;
 
 // Re-export functions from sub-modules to make them directly accessible via `db::`
+pub use api_keys::*;
+pub use jobs::*;
+pub use late_fee::*;
+pub use lease::*;
+pub use ledger::*;
+pub use login_attempts::*;
+pub use mfa::*;
+pub use oidc::*;
+pub use pagination::*;
 pub use payment::*;
 pub use property::*;
+pub use receipt::*;
+pub use refresh_tokens::*;
+pub use user_tokens::*;
 pub use users::*;
@@ -0,0 +1,201 @@
+use crate::errors::AppError;
+use crate::models::{OidcLoginState, User, UserRole};
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// Stores an in-flight authorization request, clearing out abandoned ones.
+pub async fn create_oidc_login_state(
+    pool: &PgPool,
+    state_hash: &str,
+    nonce: &str,
+    code_verifier: &str,
+    expires_at: OffsetDateTime,
+) -> Result<(), AppError> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query!(
+        r#"
+        DELETE FROM oidc_login_states
+        WHERE expires_at <= NOW()
+        "#
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to store login state: {}", e)))?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO oidc_login_states (state_hash, nonce, code_verifier, expires_at)
+        VALUES ($1, $2, $3, $4)
+        "#,
+        state_hash,
+        nonce,
+        code_verifier,
+        expires_at,
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to store login state: {}", e)))?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Removes and returns an unexpired authorization request, so each `state` can be used once.
+pub async fn consume_oidc_login_state(
+    pool: &PgPool,
+    state_hash: &str,
+) -> Result<Option<OidcLoginState>, AppError> {
+    let state = sqlx::query_as!(
+        OidcLoginState,
+        r#"
+        DELETE FROM oidc_login_states
+        WHERE state_hash = $1 AND expires_at > NOW()
+        RETURNING nonce, code_verifier
+        "#,
+        state_hash
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to find login state: {}", e)))?;
+
+    Ok(state)
+}
+
+/// Finds the user linked to an external identity, recording the login on the link.
+pub async fn find_user_by_identity(
+    pool: &PgPool,
+    issuer: &str,
+    subject: &str,
+    email: Option<&str>,
+) -> Result<Option<User>, AppError> {
+    let user = sqlx::query_as!(
+        User,
+        r#"
+        WITH identity AS (
+            UPDATE user_identities
+            SET last_login_at = NOW(), email = $3
+            WHERE issuer = $1 AND subject = $2
+            RETURNING user_id
+        )
+        SELECT u.id, u.username, u.password_hash, u.role as "role: UserRole", u.email, u.full_name, u.phone, u.email_verified_at, u.failed_login_attempts, u.locked_until, u.totp_secret, u.totp_enabled_at, u.totp_last_used_step, u.created_at, u.updated_at
+        FROM users u
+        JOIN identity i ON i.user_id = u.id
+        "#,
+        issuer,
+        subject,
+        email,
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to find user: {}", e)))?;
+
+    Ok(user)
+}
+
+/// Links an external identity to an existing user.
+pub async fn link_identity(
+    pool: &PgPool,
+    user_id: Uuid,
+    issuer: &str,
+    subject: &str,
+    email: Option<&str>,
+) -> Result<(), AppError> {
+    sqlx::query!(
+        r#"
+        INSERT INTO user_identities (user_id, issuer, subject, email)
+        VALUES ($1, $2, $3, $4)
+        "#,
+        user_id,
+        issuer,
+        subject,
+        email,
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| {
+        if e.as_database_error()
+            .is_some_and(|db_err| db_err.is_unique_violation())
+        {
+            return AppError::Conflict("This identity is already linked to an account".into());
+        }
+        AppError::InternalServerError(format!("Failed to link identity: {}", e))
+    })?;
+
+    Ok(())
+}
+
+/// Details of a user provisioned on their first login through an identity provider.
+pub struct ProvisionedUser<'a> {
+    pub username: &'a str,
+    pub password_hash: &'a str,
+    pub role: UserRole,
+    pub email: Option<&'a str>,
+    pub email_verified: bool,
+    pub full_name: Option<&'a str>,
+    pub issuer: &'a str,
+    pub subject: &'a str,
+}
+
+/// Creates a user together with the external identity they signed in with.
+///
+/// Returns `None` if the username is taken, so the caller can try another one.
+pub async fn create_user_with_identity(
+    pool: &PgPool,
+    new_user: ProvisionedUser<'_>,
+) -> Result<Option<User>, AppError> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query_as!(
+        User,
+        r#"
+        INSERT INTO users (username, password_hash, role, email, full_name, email_verified_at)
+        VALUES ($1, $2, $3, $4, $5, CASE WHEN $6 THEN NOW() END)
+        RETURNING id, username, password_hash, role as "role: UserRole", email, full_name, phone, email_verified_at, failed_login_attempts, locked_until, totp_secret, totp_enabled_at, totp_last_used_step, created_at, updated_at
+        "#,
+        new_user.username,
+        new_user.password_hash,
+        new_user.role as UserRole,
+        new_user.email,
+        new_user.full_name,
+        new_user.email_verified,
+    )
+    .fetch_one(&mut *tx)
+    .await;
+
+    let user = match result {
+        Ok(user) => user,
+        Err(e) => {
+            return match e.as_database_error().and_then(|db_err| db_err.constraint()) {
+                Some("users_username_key") => Ok(None),
+                Some("users_email_lower_key") => Err(AppError::Conflict(
+                    "An account with this email already exists".into(),
+                )),
+                _ => Err(AppError::InternalServerError(format!(
+                    "Failed to create user: {}",
+                    e
+                ))),
+            };
+        }
+    };
+
+    sqlx::query!(
+        r#"
+        INSERT INTO user_identities (user_id, issuer, subject, email)
+        VALUES ($1, $2, $3, $4)
+        "#,
+        user.id,
+        new_user.issuer,
+        new_user.subject,
+        new_user.email,
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to link identity: {}", e)))?;
+
+    tx.commit().await?;
+
+    Ok(Some(user))
+}
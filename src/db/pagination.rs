@@ -0,0 +1,44 @@
+use sqlx::{Postgres, QueryBuilder};
+
+use crate::models::{Cursor, SortOrder};
+
+// `sort_expr` and `cast` are only ever taken from the sort field enums, never from
+// user input, so pushing them as SQL text is safe. Everything else is bound.
+
+/// Appends the keyset condition that skips to the rows after `cursor`.
+///
+/// Rows are ordered by `(sort_expr, id)`, so comparing that pair continues exactly
+/// where the previous page stopped, even when sort keys repeat.
+pub fn push_keyset_condition(
+    builder: &mut QueryBuilder<'_, Postgres>,
+    sort_expr: &str,
+    cast: &str,
+    order: SortOrder,
+    cursor: &Cursor,
+) {
+    let operator = match order {
+        SortOrder::Asc => ">",
+        SortOrder::Desc => "<",
+    };
+
+    builder.push(format!(" AND ({}, id) {} (", sort_expr, operator));
+    builder.push_bind(cursor.value.clone());
+    builder.push(format!("::{}, ", cast));
+    builder.push_bind(cursor.id);
+    builder.push(")");
+}
+
+/// Appends the `ORDER BY` matching `push_keyset_condition` and the page size.
+pub fn push_order_and_limit(
+    builder: &mut QueryBuilder<'_, Postgres>,
+    sort_expr: &str,
+    order: SortOrder,
+    limit: i64,
+) {
+    builder.push(format!(
+        " ORDER BY {expr} {dir}, id {dir} LIMIT ",
+        expr = sort_expr,
+        dir = order.as_sql()
+    ));
+    builder.push_bind(limit);
+}
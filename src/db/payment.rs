@@ -1,8 +1,15 @@
 // src/db/payment.rs
 
+use crate::db::{post_journal_entry, push_keyset_condition, push_order_and_limit};
 use crate::errors::AppError;
-use crate::models::{CreatePayment, Payment, PaymentStatus};
-use sqlx::{PgPool, Postgres};
+use crate::models::{
+    CreatePayment, Cursor, JournalEntryKind, JournalLine, LedgerAccount, ListPaymentsQuery,
+    NewJournalEntry, NewRentCharge, OverdueBalance, OverdueBalancesQuery, Payment, PaymentKind,
+    PaymentStatus,
+};
+use bigdecimal::{BigDecimal, Zero};
+use sqlx::{PgConnection, PgPool, Postgres, QueryBuilder};
+use time::Date;
 use uuid::Uuid;
 
 /// Creates a new payment record.
@@ -12,6 +19,8 @@ pub async fn create_payment(
     status: PaymentStatus,
     transaction_id: Option<String>,
 ) -> Result<Payment, AppError> {
+    let mut tx = pool.begin().await?;
+
     let payment = sqlx::query_as!(
         Payment,
         r#"
@@ -21,8 +30,10 @@ pub async fn create_payment(
         )
         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
         RETURNING
-            id, user_id, property_id, amount, currency, status as "status!: PaymentStatus",
-            notes, transaction_id, due_date, period_start, period_end, created_at, updated_at
+            id, user_id, property_id, amount, amount_paid, balance_due AS "balance_due!", currency,
+            status as "status!: PaymentStatus",
+            notes, transaction_id, due_date, period_start, period_end,
+            kind as "kind!: PaymentKind", lease_id, parent_payment_id, created_at, updated_at
         "#,
         new_payment.user_id,
         new_payment.property_id,
@@ -35,60 +46,528 @@ pub async fn create_payment(
         new_payment.period_start,
         new_payment.period_end,
     )
-    .fetch_one(pool)
+    .fetch_one(&mut *tx)
     .await
     .map_err(|e| AppError::InternalServerError(format!("Failed to create payment: {}", e)))?;
 
+    post_journal_entry(
+        &mut tx,
+        &NewJournalEntry {
+            kind: JournalEntryKind::Charge,
+            user_id: payment.user_id,
+            property_id: payment.property_id,
+            currency: payment.currency.clone(),
+            entry_date: payment
+                .due_date
+                .unwrap_or_else(|| payment.created_at.date()),
+            memo: payment.notes.clone(),
+            payment_id: Some(payment.id),
+            receipt_id: None,
+            created_by: None,
+            lines: JournalLine::transfer(
+                LedgerAccount::RentReceivable,
+                payment.kind.income_account(),
+                &payment.amount,
+            ),
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
     Ok(payment)
 }
 
-/// Lists payments, potentially filtered by user_id or property_id.
+/// Lists one page of payments matching the filters, optionally restricted to
+/// properties owned by `owner_id`.
+///
+/// Pass the cursor of the previous page in `after`. Fetch one row more than the page
+/// size to find out whether another page follows.
 pub async fn list_payments(
     pool: &PgPool,
-    user_id: Option<Uuid>,
-    property_id: Option<Uuid>,
+    filter: &ListPaymentsQuery,
+    owner_id: Option<Uuid>,
+    after: Option<&Cursor>,
+    limit: i64,
 ) -> Result<Vec<Payment>, AppError> {
-    let mut query_str = r#"
+    let (sort, order) = filter.sort_order();
+    let (sort_expr, cast) = sort.sql();
+
+    let mut builder = QueryBuilder::<Postgres>::new(
+        r#"
         SELECT
-            id, user_id, property_id, amount, currency, status as "status!: PaymentStatus",
-            notes, transaction_id, due_date, period_start, period_end, created_at, updated_at
+            id, user_id, property_id, amount, amount_paid, balance_due, currency, status,
+            notes, transaction_id, due_date, period_start, period_end, kind, lease_id,
+            parent_payment_id, created_at, updated_at
         FROM payments
-    "#
-    .to_string();
+        WHERE TRUE
+        "#,
+    );
 
-    let mut conditions = Vec::new();
-    let mut param_idx = 1;
+    if let Some(user_id) = filter.user_id {
+        builder.push(" AND user_id = ").push_bind(user_id);
+    }
+    if let Some(property_id) = filter.property_id {
+        builder.push(" AND property_id = ").push_bind(property_id);
+    }
+    if let Some(owner_id) = owner_id {
+        builder
+            .push(" AND property_id IN (SELECT id FROM properties WHERE owner_id = ")
+            .push_bind(owner_id)
+            .push(")");
+    }
+    if let Some(status) = &filter.status {
+        builder.push(" AND status = ").push_bind(status.clone());
+    }
+    if let Some(currency) = &filter.currency {
+        builder
+            .push(" AND UPPER(currency) = UPPER(")
+            .push_bind(currency.clone())
+            .push(")");
+    }
+    if let Some(due_from) = filter.due_from {
+        builder.push(" AND due_date >= ").push_bind(due_from);
+    }
+    if let Some(due_to) = filter.due_to {
+        builder.push(" AND due_date <= ").push_bind(due_to);
+    }
+    if let Some(period_start_from) = filter.period_start_from {
+        builder
+            .push(" AND period_start >= ")
+            .push_bind(period_start_from);
+    }
+    if let Some(period_start_to) = filter.period_start_to {
+        builder
+            .push(" AND period_start <= ")
+            .push_bind(period_start_to);
+    }
+    if let Some(amount_min) = &filter.amount_min {
+        builder
+            .push(" AND amount >= ")
+            .push_bind(amount_min.clone());
+    }
+    if let Some(amount_max) = &filter.amount_max {
+        builder
+            .push(" AND amount <= ")
+            .push_bind(amount_max.clone());
+    }
 
-    if user_id.is_some() {
-        conditions.push(format!("user_id = ${}", param_idx));
-        param_idx += 1;
+    if let Some(cursor) = after {
+        push_keyset_condition(&mut builder, sort_expr, cast, order, cursor);
     }
+    push_order_and_limit(&mut builder, sort_expr, order, limit);
+
+    let payments = builder
+        .build_query_as::<Payment>()
+        .fetch_all(pool)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Failed to list payments: {}", e)))?;
+
+    Ok(payments)
+}
+
+/// Finds a payment by its id.
+pub async fn find_payment_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Payment>, AppError> {
+    let payment = sqlx::query_as!(
+        Payment,
+        r#"
+        SELECT
+            id, user_id, property_id, amount, amount_paid, balance_due AS "balance_due!", currency,
+            status as "status!: PaymentStatus",
+            notes, transaction_id, due_date, period_start, period_end,
+            kind as "kind!: PaymentKind", lease_id, parent_payment_id, created_at, updated_at
+        FROM payments
+        WHERE id = $1
+        "#,
+        id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to find payment: {}", e)))?;
+
+    Ok(payment)
+}
+
+/// A change of a payment's status made by a user.
+pub struct PaymentStatusChange<'a> {
+    pub from: PaymentStatus,
+    pub to: PaymentStatus,
+    pub changed_by: Uuid,
+    pub reason: &'a str,
+    pub transaction_id: Option<&'a str>, // Kept on the payment, replacing any earlier one
+}
+
+/// Moves a payment to a new status and records the change and its reason in the
+/// payment's status history. Waiving a payment or marking it failed credits the
+/// tenant with what was still due on it; retrying a failed payment charges it again.
+///
+/// Returns `None` if the payment is no longer in `change.from`, so a concurrent
+/// change cannot be overwritten by a transition checked against a stale status.
+pub async fn update_payment_status(
+    pool: &PgPool,
+    id: Uuid,
+    change: PaymentStatusChange<'_>,
+) -> Result<Option<Payment>, AppError> {
+    let mut tx = pool.begin().await?;
+
+    let payment = sqlx::query_as!(
+        Payment,
+        r#"
+        UPDATE payments
+        SET status = $3, transaction_id = COALESCE($4, transaction_id), updated_at = NOW()
+        WHERE id = $1 AND status = $2
+        RETURNING
+            id, user_id, property_id, amount, amount_paid, balance_due AS "balance_due!", currency,
+            status as "status!: PaymentStatus",
+            notes, transaction_id, due_date, period_start, period_end,
+            kind as "kind!: PaymentKind", lease_id, parent_payment_id, created_at, updated_at
+        "#,
+        id,
+        change.from.clone() as PaymentStatus,
+        change.to.clone() as PaymentStatus,
+        change.transaction_id,
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|e| {
+        if e.as_database_error()
+            .is_some_and(|db_err| db_err.is_unique_violation())
+        {
+            return AppError::Conflict(
+                "Transaction id is already recorded on another payment".into(),
+            );
+        }
+        AppError::InternalServerError(format!("Failed to update payment: {}", e))
+    })?;
 
-    if property_id.is_some() {
-        conditions.push(format!("property_id = ${}", param_idx));
+    if let Some(payment) = &payment {
+        sqlx::query!(
+            r#"
+            INSERT INTO payment_status_events (payment_id, from_status, to_status, reason, changed_by)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+            id,
+            change.from.clone() as PaymentStatus,
+            change.to as PaymentStatus,
+            change.reason,
+            change.changed_by,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| {
+            AppError::InternalServerError(format!("Failed to record payment status change: {}", e))
+        })?;
+
+        // Failed and waived charges are no longer owed, so what was still due on them
+        // is credited back, and charged again if a failed payment is retried
+        let kind = match (&change.from, &payment.status) {
+            (PaymentStatus::Failed, _) => Some(JournalEntryKind::Charge),
+            (_, PaymentStatus::Failed | PaymentStatus::Waived) => Some(JournalEntryKind::Credit),
+            _ => None,
+        };
+        if let Some(kind) = kind.filter(|_| payment.balance_due > BigDecimal::zero()) {
+            let (debit, credit) = match kind {
+                JournalEntryKind::Charge => {
+                    (LedgerAccount::RentReceivable, payment.kind.income_account())
+                }
+                _ => (payment.kind.income_account(), LedgerAccount::RentReceivable),
+            };
+            post_journal_entry(
+                &mut tx,
+                &NewJournalEntry {
+                    kind,
+                    user_id: payment.user_id,
+                    property_id: payment.property_id,
+                    currency: payment.currency.clone(),
+                    entry_date: payment.updated_at.date(),
+                    memo: Some(change.reason.to_string()),
+                    payment_id: Some(payment.id),
+                    receipt_id: None,
+                    created_by: Some(change.changed_by),
+                    lines: JournalLine::transfer(debit, credit, &payment.balance_due),
+                },
+            )
+            .await?;
+        }
     }
 
-    if !conditions.is_empty() {
-        query_str.push_str(" WHERE ");
-        query_str.push_str(&conditions.join(" AND "));
+    tx.commit().await?;
+
+    Ok(payment)
+}
+
+/// Moves every pending or partially paid payment due before `due_before` to overdue,
+/// recording why in each payment's status history. Returns how many payments became
+/// overdue.
+///
+/// Payments a concurrent run or request has already moved are skipped, so running
+/// this on several servers at once is safe.
+pub async fn mark_overdue_payments(
+    pool: &PgPool,
+    due_before: Date,
+    reason: &str,
+) -> Result<u64, AppError> {
+    let result = sqlx::query!(
+        r#"
+        WITH due AS (
+            SELECT id, status
+            FROM payments
+            WHERE status IN ('pending', 'partially_paid') AND due_date < $1
+            FOR UPDATE SKIP LOCKED
+        ),
+        overdue AS (
+            UPDATE payments
+            SET status = 'overdue', updated_at = NOW()
+            FROM due
+            WHERE payments.id = due.id
+            RETURNING payments.id, due.status AS from_status
+        )
+        INSERT INTO payment_status_events (payment_id, from_status, to_status, reason)
+        SELECT id, from_status, 'overdue', $2
+        FROM overdue
+        "#,
+        due_before,
+        reason,
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| {
+        AppError::InternalServerError(format!("Failed to mark overdue payments: {}", e))
+    })?;
+
+    Ok(result.rows_affected())
+}
+
+/// Sums what each tenant owes per property and currency across their overdue
+/// payments, oldest debt first, optionally restricted to properties owned by
+/// `owner_id`.
+pub async fn list_overdue_balances(
+    pool: &PgPool,
+    filter: &OverdueBalancesQuery,
+    owner_id: Option<Uuid>,
+) -> Result<Vec<OverdueBalance>, AppError> {
+    let balances = sqlx::query_as!(
+        OverdueBalance,
+        r#"
+        SELECT
+            user_id,
+            property_id,
+            currency,
+            COUNT(*) AS "payments!",
+            SUM(balance_due) AS "outstanding!",
+            MIN(COALESCE(due_date, created_at::date)) AS "oldest_due_date!"
+        FROM payments
+        WHERE status = 'overdue'
+          AND ($1::uuid IS NULL OR user_id = $1)
+          AND ($2::uuid IS NULL OR property_id = $2)
+          AND ($3::uuid IS NULL OR property_id IN (SELECT id FROM properties WHERE owner_id = $3))
+        GROUP BY user_id, property_id, currency
+        ORDER BY "oldest_due_date!", user_id, property_id, currency
+        "#,
+        filter.user_id,
+        filter.property_id,
+        owner_id,
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| {
+        AppError::InternalServerError(format!("Failed to list overdue balances: {}", e))
+    })?;
+
+    Ok(balances)
+}
+
+/// Records a pending rent charge unless one already exists for the lease and
+/// period, and posts it to the ledger. Returns whether a charge was created.
+pub async fn create_rent_charge(
+    conn: &mut PgConnection,
+    charge: &NewRentCharge,
+) -> Result<bool, AppError> {
+    let created = sqlx::query_scalar!(
+        r#"
+        INSERT INTO payments (
+            user_id, property_id, lease_id, kind, amount, currency, status,
+            notes, due_date, period_start, period_end
+        )
+        VALUES ($1, $2, $3, 'rent', $4, $5, 'pending', $6, $7, $8, $9)
+        ON CONFLICT (lease_id, period_start) WHERE kind = 'rent' DO NOTHING
+        RETURNING id
+        "#,
+        charge.user_id,
+        charge.property_id,
+        charge.lease_id,
+        charge.amount,
+        charge.currency,
+        charge.notes,
+        charge.due_date,
+        charge.period_start,
+        charge.period_end,
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to create rent charge: {}", e)))?;
+
+    let Some(payment_id) = created else {
+        return Ok(false);
+    };
+    post_journal_entry(
+        conn,
+        &NewJournalEntry {
+            kind: JournalEntryKind::Charge,
+            user_id: charge.user_id,
+            property_id: Some(charge.property_id),
+            currency: charge.currency.clone(),
+            entry_date: charge.due_date,
+            memo: Some(charge.notes.clone()),
+            payment_id: Some(payment_id),
+            receipt_id: None,
+            created_by: None,
+            lines: JournalLine::transfer(
+                LedgerAccount::RentReceivable,
+                PaymentKind::Rent.income_account(),
+                &charge.amount,
+            ),
+        },
+    )
+    .await?;
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::fixtures::{money, seed_tenant_and_property};
+
+    const ALL_STATUSES: [PaymentStatus; 6] = [
+        PaymentStatus::Pending,
+        PaymentStatus::Completed,
+        PaymentStatus::Failed,
+        PaymentStatus::Overdue,
+        PaymentStatus::PartiallyPaid,
+        PaymentStatus::Waived,
+    ];
+
+    fn new_payment(user_id: Uuid, property_id: Uuid) -> CreatePayment {
+        CreatePayment {
+            user_id,
+            property_id,
+            amount: money("1000.00"),
+            currency: "USD".into(),
+            notes: None,
+            due_date: None,
+            period_start: None,
+            period_end: None,
+        }
     }
 
-    query_str.push_str(" ORDER BY created_at DESC");
+    #[sqlx::test]
+    async fn every_status_round_trips_through_create_and_list(pool: PgPool) {
+        let (user_id, property_id) = seed_tenant_and_property(&pool).await;
 
-    let mut query = sqlx::query_as::<Postgres, Payment>(&query_str);
+        for status in ALL_STATUSES {
+            let created = create_payment(
+                &pool,
+                new_payment(user_id, property_id),
+                status.clone(),
+                None,
+            )
+            .await
+            .unwrap_or_else(|e| panic!("creating a {:?} payment failed: {:?}", status, e));
+            assert_eq!(created.status, status);
+        }
 
-    if let Some(uid) = user_id {
-        query = query.bind(uid); // Bind the actual Uuid value
+        let filter = ListPaymentsQuery {
+            user_id: Some(user_id),
+            ..Default::default()
+        };
+        let listed = list_payments(&pool, &filter, None, None, 100)
+            .await
+            .unwrap();
+        assert_eq!(listed.len(), ALL_STATUSES.len());
+        for status in ALL_STATUSES {
+            assert!(
+                listed.iter().any(|payment| payment.status == status),
+                "{:?} missing from list_payments",
+                status
+            );
+        }
     }
 
-    if let Some(pid) = property_id {
-        query = query.bind(pid); // Bind the actual Uuid value
+    #[sqlx::test]
+    async fn every_status_is_stored_under_its_database_enum_name(pool: PgPool) {
+        let (user_id, property_id) = seed_tenant_and_property(&pool).await;
+
+        for status in ALL_STATUSES {
+            let payment = create_payment(
+                &pool,
+                new_payment(user_id, property_id),
+                status.clone(),
+                None,
+            )
+            .await
+            .unwrap();
+
+            let stored: String =
+                sqlx::query_scalar("SELECT status::text FROM payments WHERE id = $1")
+                    .bind(payment.id)
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap();
+            assert_eq!(stored, status.as_str());
+        }
     }
 
-    let payments = query
-        .fetch_all(pool)
+    #[sqlx::test]
+    async fn failed_charges_are_credited_and_charged_again_when_retried(pool: PgPool) {
+        let (user_id, property_id) = seed_tenant_and_property(&pool).await;
+        let payment = create_payment(
+            &pool,
+            new_payment(user_id, property_id),
+            PaymentStatus::Pending,
+            None,
+        )
         .await
-        .map_err(|e| AppError::InternalServerError(format!("Failed to list payments: {}", e)))?;
+        .unwrap();
 
-    Ok(payments)
+        let receivable = || async {
+            let balance: BigDecimal = sqlx::query_scalar(
+                "SELECT SUM(debit - credit) FROM journal_lines WHERE account = 'rent_receivable'",
+            )
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+            balance
+        };
+        assert_eq!(receivable().await, money("1000.00"));
+
+        for (from, to, owed) in [
+            (PaymentStatus::Pending, PaymentStatus::Failed, "0.00"),
+            (PaymentStatus::Failed, PaymentStatus::Pending, "1000.00"),
+        ] {
+            let change = PaymentStatusChange {
+                from,
+                to,
+                changed_by: user_id,
+                reason: "Card declined",
+                transaction_id: Some("ch_declined"),
+            };
+            let updated = update_payment_status(&pool, payment.id, change)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(updated.transaction_id.as_deref(), Some("ch_declined"));
+            assert_eq!(receivable().await, money(owed));
+        }
+    }
+
+    #[test]
+    fn json_names_match_database_names() {
+        for status in ALL_STATUSES {
+            assert_eq!(
+                serde_json::to_value(&status).unwrap(),
+                serde_json::Value::from(status.as_str())
+            );
+        }
+    }
 }
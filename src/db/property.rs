@@ -1,23 +1,25 @@
+use crate::db::{push_keyset_condition, push_order_and_limit};
 use crate::errors::AppError;
-use crate::models::{CreateProperty, Property};
-use sqlx::PgPool;
+use crate::models::{CreateProperty, Cursor, ListPropertiesQuery, Property, UpdateProperty};
+use sqlx::{PgPool, Postgres, QueryBuilder};
+use uuid::Uuid;
 
-/// Creates a new property in the database.
+/// Creates a new property in the database. It starts out vacant, without a lease.
 pub async fn create_property(
     pool: &PgPool,
     new_property: CreateProperty,
+    owner_id: Uuid,
 ) -> Result<Property, AppError> {
     let property = sqlx::query_as!(
         Property,
         r#"
-        INSERT INTO properties (address, unit_number, current_rent_amount, current_tenant_id)
-        VALUES ($1, $2, $3, $4)
-        RETURNING id, address, unit_number, current_rent_amount, current_tenant_id, created_at, updated_at
+        INSERT INTO properties (address, unit_number, owner_id)
+        VALUES ($1, $2, $3)
+        RETURNING id, address, unit_number, owner_id, NULL::uuid AS current_lease_id, NULL::numeric AS current_rent_amount, '{}'::uuid[] AS "current_tenant_ids!", created_at, updated_at
         "#,
         new_property.address,
         new_property.unit_number,
-        new_property.current_rent_amount,
-        new_property.current_tenant_id,
+        owner_id,
     )
     .fetch_one(pool)
     .await
@@ -26,19 +28,157 @@ pub async fn create_property(
     Ok(property)
 }
 
-/// Lists all properties in the database.
-pub async fn list_properties(pool: &PgPool) -> Result<Vec<Property>, AppError> {
-    let properties = sqlx::query_as!(
+/// Finds a property by its id, with its current lease.
+pub async fn find_property_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Property>, AppError> {
+    let property = sqlx::query_as!(
         Property,
         r#"
-        SELECT id, address, unit_number, current_rent_amount, current_tenant_id, created_at, updated_at
-        FROM properties
-        ORDER BY address, unit_number
-        "#
+        SELECT id AS "id!", address AS "address!", unit_number, owner_id, current_lease_id, current_rent_amount, current_tenant_ids AS "current_tenant_ids!", created_at AS "created_at!", updated_at AS "updated_at!"
+        FROM property_details
+        WHERE id = $1
+        "#,
+        id
     )
-    .fetch_all(pool)
+    .fetch_optional(pool)
     .await
-    .map_err(|e| AppError::InternalServerError(format!("Failed to list properties: {}", e)))?;
+    .map_err(|e| AppError::InternalServerError(format!("Failed to find property: {}", e)))?;
+
+    Ok(property)
+}
+
+/// Lists one page of properties matching the filters, optionally restricted to an
+/// owner or a tenant on the current lease.
+///
+/// Pass the cursor of the previous page in `after`. Fetch one row more than the page
+/// size to find out whether another page follows.
+pub async fn list_properties(
+    pool: &PgPool,
+    filter: &ListPropertiesQuery,
+    owner_id: Option<Uuid>,
+    tenant_id: Option<Uuid>,
+    after: Option<&Cursor>,
+    limit: i64,
+) -> Result<Vec<Property>, AppError> {
+    let (sort, order) = filter.sort_order();
+    let (sort_expr, cast) = sort.sql();
+
+    let mut builder = QueryBuilder::<Postgres>::new(
+        r#"
+        SELECT id, address, unit_number, owner_id, current_lease_id, current_rent_amount, current_tenant_ids, created_at, updated_at
+        FROM property_details
+        WHERE TRUE
+        "#,
+    );
+
+    if let Some(owner_id) = owner_id {
+        builder.push(" AND owner_id = ").push_bind(owner_id);
+    }
+    if let Some(tenant_id) = tenant_id {
+        builder
+            .push(" AND ")
+            .push_bind(tenant_id)
+            .push(" = ANY(current_tenant_ids)");
+    }
+    if let Some(q) = filter.q.as_deref().filter(|q| !q.is_empty()) {
+        builder
+            .push(" AND address ILIKE ")
+            .push_bind(format!("%{}%", escape_like(q)));
+    }
+    match filter.has_tenant {
+        Some(true) => {
+            builder.push(" AND cardinality(current_tenant_ids) > 0");
+        }
+        Some(false) => {
+            builder.push(" AND cardinality(current_tenant_ids) = 0");
+        }
+        None => {}
+    }
+    if let Some(rent_min) = &filter.rent_min {
+        builder
+            .push(" AND current_rent_amount >= ")
+            .push_bind(rent_min.clone());
+    }
+    if let Some(rent_max) = &filter.rent_max {
+        builder
+            .push(" AND current_rent_amount <= ")
+            .push_bind(rent_max.clone());
+    }
+
+    if let Some(cursor) = after {
+        push_keyset_condition(&mut builder, sort_expr, cast, order, cursor);
+    }
+    push_order_and_limit(&mut builder, sort_expr, order, limit);
+
+    let properties = builder
+        .build_query_as::<Property>()
+        .fetch_all(pool)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Failed to list properties: {}", e)))?;
 
     Ok(properties)
 }
+
+/// Escapes the `LIKE` wildcards in a search term so they match literally.
+fn escape_like(term: &str) -> String {
+    term.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+/// Applies a partial update to a property, returning `None` if it does not exist.
+pub async fn update_property(
+    pool: &PgPool,
+    id: Uuid,
+    changes: UpdateProperty,
+) -> Result<Option<Property>, AppError> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE properties
+        SET address = COALESCE($2, address),
+            unit_number = CASE WHEN $3 THEN $4 ELSE unit_number END,
+            updated_at = NOW()
+        WHERE id = $1
+        "#,
+        id,
+        changes.address,
+        changes.unit_number.is_some(),
+        changes.unit_number.flatten(),
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to update property: {}", e)))?;
+
+    if result.rows_affected() == 0 {
+        return Ok(None);
+    }
+
+    find_property_by_id(pool, id).await
+}
+
+/// Deletes a property, returning `false` if it does not exist.
+///
+/// Fails with `Conflict` while payments or leases still reference the property; the
+/// foreign keys make that check race-free.
+pub async fn delete_property(pool: &PgPool, id: Uuid) -> Result<bool, AppError> {
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM properties
+        WHERE id = $1
+        "#,
+        id
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| {
+        if e.as_database_error()
+            .is_some_and(|db_err| db_err.is_foreign_key_violation())
+        {
+            return AppError::Conflict(
+                "Property has payments or leases and cannot be deleted".into(),
+            );
+        }
+        AppError::InternalServerError(format!("Failed to delete property: {}", e))
+    })?;
+
+    Ok(result.rows_affected() == 1)
+}
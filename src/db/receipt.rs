@@ -0,0 +1,496 @@
+// src/db/receipt.rs
+
+use bigdecimal::{BigDecimal, Zero};
+use sqlx::{PgConnection, PgPool, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+use crate::db::{post_journal_entry, push_keyset_condition, push_order_and_limit};
+use crate::errors::{AppError, FieldError};
+use crate::models::{
+    CreateReceipt, Cursor, JournalEntryKind, JournalLine, LedgerAccount, ListReceiptsQuery,
+    NewJournalEntry, PaymentAllocation, PaymentStatus, Receipt, ReceiptDetails, SortOrder,
+};
+
+/// A charge that still has a balance due, as seen when allocating a receipt.
+struct OutstandingCharge {
+    id: Uuid,
+    balance_due: BigDecimal,
+    status: PaymentStatus,
+}
+
+/// Records a receipt, posts it to the ledger and allocates it against the tenant's
+/// outstanding charges on the property, all in one transaction.
+///
+/// The tenant must be on a lease of the property or have a payment there; otherwise
+/// the receipt is refused with a validation error on `user_id`.
+///
+/// With explicit `allocations`, each must name an outstanding charge of the same
+/// tenant, property and currency and may not exceed its balance due; otherwise the
+/// receipt fails with `409 Conflict`. Without them, the receipt pays the oldest
+/// charges first until it runs out. Whatever is left stays unallocated.
+///
+/// A charge paid in full becomes completed. One paid in part becomes partially paid,
+/// unless it is already overdue, in which case it stays overdue until it is paid off.
+/// Every change of status is kept in the charge's status history.
+pub async fn create_receipt(
+    pool: &PgPool,
+    new_receipt: &CreateReceipt,
+    recorded_by: Uuid,
+) -> Result<ReceiptDetails, AppError> {
+    let mut tx = pool.begin().await?;
+
+    let known_tenant = sqlx::query_scalar!(
+        r#"
+        SELECT
+            EXISTS (
+                SELECT 1
+                FROM lease_tenants lt
+                JOIN leases l ON l.id = lt.lease_id
+                WHERE lt.tenant_id = $1 AND l.property_id = $2
+            )
+            OR EXISTS (SELECT 1 FROM payments WHERE user_id = $1 AND property_id = $2)
+            AS "known_tenant!"
+        "#,
+        new_receipt.user_id,
+        new_receipt.property_id,
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to check tenant: {}", e)))?;
+    if !known_tenant {
+        return Err(AppError::Validation(vec![FieldError::new(
+            "user_id",
+            "invalid",
+            "Must be a tenant on a lease of this property or owe a payment there",
+        )]));
+    }
+
+    let receipt_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO receipts (
+            user_id, property_id, amount, currency, received_on, transaction_id, notes, recorded_by
+        )
+        VALUES ($1, $2, $3, $4, COALESCE($5, CURRENT_DATE), $6, $7, $8)
+        RETURNING id
+        "#,
+        new_receipt.user_id,
+        new_receipt.property_id,
+        new_receipt.amount,
+        new_receipt.currency,
+        new_receipt.received_on,
+        new_receipt.transaction_id,
+        new_receipt.notes,
+        recorded_by,
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| {
+        let db_err = e.as_database_error();
+        if db_err.is_some_and(|db_err| db_err.is_unique_violation()) {
+            return AppError::Conflict(
+                "Transaction id is already recorded on another receipt".into(),
+            );
+        }
+        if db_err.is_some_and(|db_err| db_err.is_foreign_key_violation()) {
+            return AppError::NotFound("User not found".into());
+        }
+        AppError::InternalServerError(format!("Failed to create receipt: {}", e))
+    })?;
+
+    let requested: Option<Vec<Uuid>> = new_receipt
+        .allocations
+        .as_ref()
+        .map(|allocations| allocations.iter().map(|a| a.payment_id).collect());
+    let charges = sqlx::query_as!(
+        OutstandingCharge,
+        r#"
+        SELECT id, balance_due AS "balance_due!", status AS "status!: PaymentStatus"
+        FROM payments
+        WHERE user_id = $1
+          AND property_id = $2
+          AND currency = $3
+          AND status IN ('pending', 'overdue', 'partially_paid')
+          AND balance_due > 0
+          AND ($4::uuid[] IS NULL OR id = ANY($4))
+        ORDER BY COALESCE(due_date, created_at::date), created_at, id
+        FOR UPDATE
+        "#,
+        new_receipt.user_id,
+        new_receipt.property_id,
+        new_receipt.currency,
+        requested.as_deref(),
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(|e| {
+        AppError::InternalServerError(format!("Failed to find outstanding charges: {}", e))
+    })?;
+
+    let allocations = match &new_receipt.allocations {
+        Some(requested) => {
+            let mut allocations = Vec::with_capacity(requested.len());
+            for allocation in requested {
+                let charge = charges
+                    .iter()
+                    .find(|charge| charge.id == allocation.payment_id)
+                    .ok_or_else(|| {
+                        AppError::Conflict(format!(
+                            "Payment {} is not an outstanding charge of this tenant on this property in {}",
+                            allocation.payment_id, new_receipt.currency
+                        ))
+                    })?;
+                if allocation.amount > charge.balance_due {
+                    return Err(AppError::Conflict(format!(
+                        "Payment {} only has {} due",
+                        charge.id, charge.balance_due
+                    )));
+                }
+                allocations.push((charge, allocation.amount.clone()));
+            }
+            allocations
+        }
+        None => {
+            let mut remaining = new_receipt.amount.clone();
+            let mut allocations = Vec::new();
+            for charge in &charges {
+                if remaining <= BigDecimal::zero() {
+                    break;
+                }
+                let amount = remaining.clone().min(charge.balance_due.clone());
+                remaining -= &amount;
+                allocations.push((charge, amount));
+            }
+            allocations
+        }
+    };
+
+    for (charge, amount) in allocations {
+        allocate(&mut tx, receipt_id, charge, &amount, recorded_by).await?;
+    }
+
+    let details = fetch_receipt(&mut tx, receipt_id)
+        .await?
+        .ok_or_else(|| AppError::InternalServerError("Created receipt disappeared".into()))?;
+
+    // The whole amount reduces what the tenant owes, allocated or not, so money
+    // paid in advance leaves them in credit
+    post_journal_entry(
+        &mut tx,
+        &NewJournalEntry {
+            kind: JournalEntryKind::Receipt,
+            user_id: details.receipt.user_id,
+            property_id: details.receipt.property_id,
+            currency: details.receipt.currency.clone(),
+            entry_date: details.receipt.received_on,
+            memo: details.receipt.notes.clone(),
+            payment_id: None,
+            receipt_id: Some(receipt_id),
+            created_by: Some(recorded_by),
+            lines: JournalLine::transfer(
+                LedgerAccount::Cash,
+                LedgerAccount::RentReceivable,
+                &details.receipt.amount,
+            ),
+        },
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(details)
+}
+
+/// Allocates `amount` of a receipt to a charge and moves the charge to the status
+/// that follows from what is now paid.
+async fn allocate(
+    conn: &mut PgConnection,
+    receipt_id: Uuid,
+    charge: &OutstandingCharge,
+    amount: &BigDecimal,
+    recorded_by: Uuid,
+) -> Result<(), AppError> {
+    sqlx::query!(
+        "INSERT INTO payment_allocations (receipt_id, payment_id, amount) VALUES ($1, $2, $3)",
+        receipt_id,
+        charge.id,
+        amount,
+    )
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to allocate receipt: {}", e)))?;
+
+    let status = sqlx::query_scalar!(
+        r#"
+        UPDATE payments
+        SET amount_paid = amount_paid + $2,
+            status = CASE
+                WHEN amount_paid + $2 >= amount THEN 'completed'::payment_status
+                WHEN status = 'overdue' THEN status
+                ELSE 'partially_paid'::payment_status
+            END,
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING status AS "status!: PaymentStatus"
+        "#,
+        charge.id,
+        amount,
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to update payment: {}", e)))?;
+
+    if status != charge.status {
+        sqlx::query!(
+            r#"
+            INSERT INTO payment_status_events (payment_id, from_status, to_status, reason, changed_by)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+            charge.id,
+            charge.status.clone() as PaymentStatus,
+            status as PaymentStatus,
+            format!("Receipt {} allocated", receipt_id),
+            recorded_by,
+        )
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| {
+            AppError::InternalServerError(format!("Failed to record payment status change: {}", e))
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Finds a receipt by its id, along with its allocations.
+pub async fn find_receipt_by_id(
+    pool: &PgPool,
+    id: Uuid,
+) -> Result<Option<ReceiptDetails>, AppError> {
+    let mut conn = pool.acquire().await?;
+    fetch_receipt(&mut conn, id).await
+}
+
+async fn fetch_receipt(
+    conn: &mut PgConnection,
+    id: Uuid,
+) -> Result<Option<ReceiptDetails>, AppError> {
+    let receipt = sqlx::query_as!(
+        Receipt,
+        r#"
+        SELECT
+            r.id, r.user_id, r.property_id, r.amount,
+            r.amount - COALESCE(
+                (SELECT SUM(a.amount) FROM payment_allocations a WHERE a.receipt_id = r.id), 0
+            ) AS "unallocated!",
+            r.currency, r.received_on, r.transaction_id, r.notes, r.recorded_by, r.created_at
+        FROM receipts r
+        WHERE r.id = $1
+        "#,
+        id
+    )
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to find receipt: {}", e)))?;
+
+    let Some(receipt) = receipt else {
+        return Ok(None);
+    };
+
+    let allocations = sqlx::query_as!(
+        PaymentAllocation,
+        r#"
+        SELECT receipt_id, payment_id, amount, created_at
+        FROM payment_allocations
+        WHERE receipt_id = $1
+        ORDER BY created_at, payment_id
+        "#,
+        id
+    )
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(|e| {
+        AppError::InternalServerError(format!("Failed to list receipt allocations: {}", e))
+    })?;
+
+    Ok(Some(ReceiptDetails {
+        receipt,
+        allocations,
+    }))
+}
+
+/// Lists one page of receipts matching the filters, newest first, optionally
+/// restricted to properties owned by `owner_id`.
+///
+/// Pass the cursor of the previous page in `after`. Fetch one row more than the page
+/// size to find out whether another page follows.
+pub async fn list_receipts(
+    pool: &PgPool,
+    filter: &ListReceiptsQuery,
+    owner_id: Option<Uuid>,
+    after: Option<&Cursor>,
+    limit: i64,
+) -> Result<Vec<Receipt>, AppError> {
+    let mut builder = QueryBuilder::<Postgres>::new(
+        r#"
+        SELECT
+            r.id, r.user_id, r.property_id, r.amount,
+            r.amount - COALESCE(
+                (SELECT SUM(a.amount) FROM payment_allocations a WHERE a.receipt_id = r.id), 0
+            ) AS unallocated,
+            r.currency, r.received_on, r.transaction_id, r.notes, r.recorded_by, r.created_at
+        FROM receipts r
+        WHERE TRUE
+        "#,
+    );
+
+    if let Some(user_id) = filter.user_id {
+        builder.push(" AND user_id = ").push_bind(user_id);
+    }
+    if let Some(property_id) = filter.property_id {
+        builder.push(" AND property_id = ").push_bind(property_id);
+    }
+    if let Some(owner_id) = owner_id {
+        builder
+            .push(" AND property_id IN (SELECT id FROM properties WHERE owner_id = ")
+            .push_bind(owner_id)
+            .push(")");
+    }
+
+    let order = SortOrder::Desc;
+    if let Some(cursor) = after {
+        push_keyset_condition(&mut builder, "created_at", "timestamptz", order, cursor);
+    }
+    push_order_and_limit(&mut builder, "created_at", order, limit);
+
+    let receipts = builder
+        .build_query_as::<Receipt>()
+        .fetch_all(pool)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Failed to list receipts: {}", e)))?;
+
+    Ok(receipts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::fixtures::{money, seed_active_lease, seed_tenant_and_property, seed_user};
+    use crate::db::{create_payment, find_payment_by_id};
+    use crate::models::{CreatePayment, NewAllocation, UserRole};
+    use time::macros::date;
+
+    #[sqlx::test]
+    async fn receipts_pay_the_oldest_charges_first(pool: PgPool) {
+        let (tenant_id, property_id) = seed_tenant_and_property(&pool).await;
+
+        let mut charges = Vec::new();
+        for (amount, due_date, status) in [
+            ("500.00", date!(2025 - 07 - 01), PaymentStatus::Pending),
+            ("1000.00", date!(2025 - 06 - 01), PaymentStatus::Overdue),
+        ] {
+            let charge = create_payment(
+                &pool,
+                CreatePayment {
+                    user_id: tenant_id,
+                    property_id,
+                    amount: money(amount),
+                    currency: "USD".into(),
+                    notes: None,
+                    due_date: Some(due_date),
+                    period_start: None,
+                    period_end: None,
+                },
+                status,
+                None,
+            )
+            .await
+            .unwrap();
+            charges.push(charge.id);
+        }
+        let (july, june) = (charges[0], charges[1]);
+
+        let mut receipt = CreateReceipt {
+            user_id: tenant_id,
+            property_id,
+            amount: money("1200.00"),
+            currency: "USD".into(),
+            received_on: None,
+            transaction_id: None,
+            notes: None,
+            allocations: None,
+        };
+        let details = create_receipt(&pool, &receipt, tenant_id).await.unwrap();
+        assert_eq!(details.allocations.len(), 2);
+        assert_eq!(details.receipt.unallocated, BigDecimal::zero());
+
+        let june_charge = find_payment_by_id(&pool, june).await.unwrap().unwrap();
+        assert_eq!(june_charge.status, PaymentStatus::Completed);
+        assert_eq!(june_charge.balance_due, BigDecimal::zero());
+        let july_charge = find_payment_by_id(&pool, july).await.unwrap().unwrap();
+        assert_eq!(july_charge.status, PaymentStatus::PartiallyPaid);
+        assert_eq!(july_charge.balance_due, money("300.00"));
+
+        // More than is due on a charge is refused, and nothing is recorded
+        receipt.allocations = Some(vec![NewAllocation {
+            payment_id: july,
+            amount: money("400.00"),
+        }]);
+        receipt.amount = money("400.00");
+        let result = create_receipt(&pool, &receipt, tenant_id).await;
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+        let receipts: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM receipts")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(receipts, 1);
+
+        // Paying the rest completes it; anything beyond the balance stays unallocated
+        receipt.allocations = None;
+        let details = create_receipt(&pool, &receipt, tenant_id).await.unwrap();
+        assert_eq!(details.receipt.unallocated, money("100.00"));
+        let july_charge = find_payment_by_id(&pool, july).await.unwrap().unwrap();
+        assert_eq!(july_charge.status, PaymentStatus::Completed);
+
+        let events: Vec<(String, String)> = sqlx::query_as(
+            "SELECT from_status::text, to_status::text FROM payment_status_events WHERE payment_id = $1 ORDER BY created_at",
+        )
+        .bind(july)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            events,
+            [
+                ("pending".to_string(), "partially_paid".to_string()),
+                ("partially_paid".to_string(), "completed".to_string()),
+            ]
+        );
+    }
+
+    #[sqlx::test]
+    async fn receipts_are_only_taken_from_the_property_s_tenants(pool: PgPool) {
+        let (tenant_id, property_id) = seed_tenant_and_property(&pool).await;
+        let stranger_id = seed_user(&pool, "stranger", UserRole::Tenant).await;
+
+        let mut receipt = CreateReceipt {
+            user_id: stranger_id,
+            property_id,
+            amount: money("100.00"),
+            currency: "USD".into(),
+            received_on: None,
+            transaction_id: None,
+            notes: None,
+            allocations: None,
+        };
+        match create_receipt(&pool, &receipt, tenant_id).await {
+            Err(AppError::Validation(errors)) => assert_eq!(errors[0].field, "user_id"),
+            other => panic!("expected a validation error, got {:?}", other.map(|_| ())),
+        }
+
+        // A tenant on a lease of the property can pay before anything is charged
+        seed_active_lease(&pool, tenant_id, property_id, date!(2025 - 01 - 01), "1200").await;
+        receipt.user_id = tenant_id;
+        let details = create_receipt(&pool, &receipt, tenant_id).await.unwrap();
+        assert_eq!(details.receipt.unallocated, money("100.00"));
+    }
+}
@@ -0,0 +1,111 @@
+use crate::errors::AppError;
+use crate::models::RefreshToken;
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// Stores the hash of a newly issued refresh token.
+pub async fn create_refresh_token(
+    pool: &PgPool,
+    user_id: Uuid,
+    family_id: Uuid,
+    token_hash: &str,
+    expires_at: OffsetDateTime,
+) -> Result<RefreshToken, AppError> {
+    let token = sqlx::query_as!(
+        RefreshToken,
+        r#"
+        INSERT INTO refresh_tokens (user_id, family_id, token_hash, expires_at)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id, user_id, family_id, expires_at, used_at, revoked_at
+        "#,
+        user_id,
+        family_id,
+        token_hash,
+        expires_at,
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to store refresh token: {}", e)))?;
+
+    Ok(token)
+}
+
+/// Finds a refresh token by the hash of its value.
+pub async fn find_refresh_token_by_hash(
+    pool: &PgPool,
+    token_hash: &str,
+) -> Result<Option<RefreshToken>, AppError> {
+    let token = sqlx::query_as!(
+        RefreshToken,
+        r#"
+        SELECT id, user_id, family_id, expires_at, used_at, revoked_at
+        FROM refresh_tokens
+        WHERE token_hash = $1
+        "#,
+        token_hash
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to find refresh token: {}", e)))?;
+
+    Ok(token)
+}
+
+/// Marks a refresh token as used, returning `false` if it was already used or revoked.
+///
+/// The check and the update happen in one statement so two concurrent refreshes
+/// with the same token cannot both succeed.
+pub async fn mark_refresh_token_used(pool: &PgPool, id: Uuid) -> Result<bool, AppError> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE refresh_tokens
+        SET used_at = NOW()
+        WHERE id = $1 AND used_at IS NULL AND revoked_at IS NULL
+        "#,
+        id
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to rotate refresh token: {}", e)))?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+/// Revokes every token in a refresh token family.
+pub async fn revoke_refresh_token_family(pool: &PgPool, family_id: Uuid) -> Result<(), AppError> {
+    sqlx::query!(
+        r#"
+        UPDATE refresh_tokens
+        SET revoked_at = NOW()
+        WHERE family_id = $1 AND revoked_at IS NULL
+        "#,
+        family_id
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| {
+        AppError::InternalServerError(format!("Failed to revoke refresh tokens: {}", e))
+    })?;
+
+    Ok(())
+}
+
+/// Revokes every outstanding refresh token belonging to a user.
+pub async fn revoke_user_refresh_tokens(pool: &PgPool, user_id: Uuid) -> Result<(), AppError> {
+    sqlx::query!(
+        r#"
+        UPDATE refresh_tokens
+        SET revoked_at = NOW()
+        WHERE user_id = $1 AND revoked_at IS NULL
+        "#,
+        user_id
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| {
+        AppError::InternalServerError(format!("Failed to revoke refresh tokens: {}", e))
+    })?;
+
+    Ok(())
+}
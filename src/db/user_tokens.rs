@@ -0,0 +1,72 @@
+use crate::errors::AppError;
+use crate::models::UserTokenPurpose;
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// Stores a new single-use token, invalidating any unused token the user already
+/// has for the same purpose so only the most recent email works.
+pub async fn create_user_token(
+    pool: &PgPool,
+    user_id: Uuid,
+    purpose: UserTokenPurpose,
+    token_hash: &str,
+    expires_at: OffsetDateTime,
+) -> Result<(), AppError> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query!(
+        r#"
+        DELETE FROM user_tokens
+        WHERE user_id = $1 AND purpose = $2 AND consumed_at IS NULL
+        "#,
+        user_id,
+        purpose as UserTokenPurpose,
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to store token: {}", e)))?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO user_tokens (user_id, purpose, token_hash, expires_at)
+        VALUES ($1, $2, $3, $4)
+        "#,
+        user_id,
+        purpose as UserTokenPurpose,
+        token_hash,
+        expires_at,
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to store token: {}", e)))?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Consumes an unexpired, unused token and returns the id of the user it was issued to.
+///
+/// Returns `None` for unknown, expired, already used or wrong-purpose tokens.
+pub async fn consume_user_token(
+    pool: &PgPool,
+    purpose: UserTokenPurpose,
+    token_hash: &str,
+) -> Result<Option<Uuid>, AppError> {
+    let user_id = sqlx::query_scalar!(
+        r#"
+        UPDATE user_tokens
+        SET consumed_at = NOW()
+        WHERE token_hash = $1 AND purpose = $2 AND consumed_at IS NULL AND expires_at > NOW()
+        RETURNING user_id
+        "#,
+        token_hash,
+        purpose as UserTokenPurpose,
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to consume token: {}", e)))?;
+
+    Ok(user_id)
+}
@@ -1,6 +1,8 @@
 use crate::errors::AppError;
-use crate::models::{RegisterUser, User};
+use crate::models::{RegisterUser, User, UserRole};
+use chrono::Duration;
 use sqlx::PgPool;
+use uuid::Uuid;
 
 /// Creates a new user in the database.
 pub async fn create_user(
@@ -11,40 +13,128 @@ pub async fn create_user(
     let user = sqlx::query_as!(
         User,
         r#"
-        INSERT INTO users (username, password_hash)
-        VALUES ($1, $2)
-        RETURNING id, username, password_hash, created_at, updated_at
+        INSERT INTO users (username, password_hash, role, email, full_name, phone)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id, username, password_hash, role as "role: UserRole", email, full_name, phone, email_verified_at, failed_login_attempts, locked_until, totp_secret, totp_enabled_at, totp_last_used_step, created_at, updated_at
         "#,
         new_user.username,
         password_hash,
+        new_user.role.unwrap_or(UserRole::Tenant) as UserRole,
+        new_user.email,
+        new_user.full_name,
+        new_user.phone,
     )
     .fetch_one(pool)
     .await
-    .map_err(|e| {
-        if let Some(db_err) = e.as_database_error() {
-            if db_err.is_unique_violation() {
-                return AppError::Conflict("Username already taken".into());
+    .map_err(
+        |e| match e.as_database_error().and_then(|db_err| db_err.constraint()) {
+            Some("users_username_key") => AppError::Conflict("Username already taken".into()),
+            Some("users_email_lower_key") => {
+                AppError::Conflict("An account with this email already exists".into())
             }
-        }
-        AppError::InternalServerError(format!("Failed to create user: {}", e))
-    })?;
+            _ => AppError::InternalServerError(format!("Failed to create user: {}", e)),
+        },
+    )?;
 
     Ok(user)
 }
 
-/// Finds a user by their username.
-pub async fn find_user_by_username(
+/// Finds a user by their username or, case-insensitively, their email.
+///
+/// An exact username match wins if the identifier happens to match both.
+pub async fn find_user_by_login(pool: &PgPool, identifier: &str) -> Result<Option<User>, AppError> {
+    let user = sqlx::query_as!(
+        User,
+        r#"
+        SELECT id, username, password_hash, role as "role: UserRole", email, full_name, phone, email_verified_at, failed_login_attempts, locked_until, totp_secret, totp_enabled_at, totp_last_used_step, created_at, updated_at
+        FROM users
+        WHERE username = $1 OR LOWER(email) = LOWER($1)
+        ORDER BY username = $1 DESC
+        LIMIT 1
+        "#,
+        identifier
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to find user: {}", e)))?;
+
+    Ok(user)
+}
+
+/// Finds a user by their id.
+pub async fn find_user_by_id(pool: &PgPool, id: Uuid) -> Result<Option<User>, AppError> {
+    let user = sqlx::query_as!(
+        User,
+        r#"
+        SELECT id, username, password_hash, role as "role: UserRole", email, full_name, phone, email_verified_at, failed_login_attempts, locked_until, totp_secret, totp_enabled_at, totp_last_used_step, created_at, updated_at
+        FROM users
+        WHERE id = $1
+        "#,
+        id
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to find user: {}", e)))?;
+
+    Ok(user)
+}
+
+/// Changes a user's role. Returns `None` if the user does not exist.
+pub async fn update_user_role(
     pool: &PgPool,
-    username: &str,
+    id: Uuid,
+    role: UserRole,
 ) -> Result<Option<User>, AppError> {
     let user = sqlx::query_as!(
         User,
         r#"
-        SELECT id, username, password_hash, created_at, updated_at
+        UPDATE users
+        SET role = $2, updated_at = NOW()
+        WHERE id = $1
+        RETURNING id, username, password_hash, role as "role: UserRole", email, full_name, phone, email_verified_at, failed_login_attempts, locked_until, totp_secret, totp_enabled_at, totp_last_used_step, created_at, updated_at
+        "#,
+        id,
+        role as UserRole,
+    )
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to update user role: {}", e)))?;
+
+    Ok(user)
+}
+
+/// Replaces a user's password hash, e.g. after upgrading it to new hashing parameters.
+pub async fn update_password_hash(
+    pool: &PgPool,
+    id: Uuid,
+    password_hash: &str,
+) -> Result<(), AppError> {
+    sqlx::query!(
+        r#"
+        UPDATE users
+        SET password_hash = $2, updated_at = NOW()
+        WHERE id = $1
+        "#,
+        id,
+        password_hash
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to update password: {}", e)))?;
+
+    Ok(())
+}
+
+/// Finds a user by email, ignoring case.
+pub async fn find_user_by_email(pool: &PgPool, email: &str) -> Result<Option<User>, AppError> {
+    let user = sqlx::query_as!(
+        User,
+        r#"
+        SELECT id, username, password_hash, role as "role: UserRole", email, full_name, phone, email_verified_at, failed_login_attempts, locked_until, totp_secret, totp_enabled_at, totp_last_used_step, created_at, updated_at
         FROM users
-        WHERE username = $1
+        WHERE LOWER(email) = LOWER($1)
         "#,
-        username
+        email
     )
     .fetch_optional(pool)
     .await
@@ -52,3 +142,74 @@ pub async fn find_user_by_username(
 
     Ok(user)
 }
+
+/// Records that a user has proven ownership of their email address.
+pub async fn mark_email_verified(pool: &PgPool, id: Uuid) -> Result<(), AppError> {
+    sqlx::query!(
+        r#"
+        UPDATE users
+        SET email_verified_at = COALESCE(email_verified_at, NOW()), updated_at = NOW()
+        WHERE id = $1
+        "#,
+        id
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to verify email: {}", e)))?;
+
+    Ok(())
+}
+
+/// Records a failed password check, locking the account once `max_failures` is reached.
+///
+/// Each further run of `max_failures` failures doubles the lockout, up to `max_lockout`.
+pub async fn record_failed_login(
+    pool: &PgPool,
+    id: Uuid,
+    max_failures: i32,
+    lockout: Duration,
+    max_lockout: Duration,
+) -> Result<(), AppError> {
+    sqlx::query!(
+        r#"
+        UPDATE users
+        SET failed_login_attempts = failed_login_attempts + 1,
+            locked_until = CASE
+                WHEN (failed_login_attempts + 1) % $2 = 0 THEN NOW() + LEAST(
+                    $3 * POWER(2, (failed_login_attempts + 1) / $2 - 1),
+                    $4
+                ) * INTERVAL '1 second'
+                ELSE locked_until
+            END
+        WHERE id = $1
+        "#,
+        id,
+        max_failures,
+        lockout.num_seconds() as f64,
+        max_lockout.num_seconds() as f64,
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to record login failure: {}", e)))?;
+
+    Ok(())
+}
+
+/// Clears failed login counters and any lockout, after a successful login or an admin unlock.
+///
+/// Returns `false` if the user does not exist.
+pub async fn reset_failed_logins(pool: &PgPool, id: Uuid) -> Result<bool, AppError> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE users
+        SET failed_login_attempts = 0, locked_until = NULL
+        WHERE id = $1
+        "#,
+        id
+    )
+    .execute(pool)
+    .await
+    .map_err(|e| AppError::InternalServerError(format!("Failed to reset login failures: {}", e)))?;
+
+    Ok(result.rows_affected() == 1)
+}
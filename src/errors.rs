@@ -1,42 +1,129 @@
 use axum::{
     Json,
-    http::StatusCode,
+    extract::rejection::JsonRejection,
+    http::{StatusCode, header},
     response::{IntoResponse, Response},
 };
-use serde_json::json;
+use serde::Serialize;
+
+use crate::request_id::current_request_id;
+
+/// A problem with one field of a request, reported back to the client.
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldError {
+    pub field: String,      // Name of the parameter or body field
+    pub code: &'static str, // Machine-readable rule that failed, e.g. "invalid" or "unknown"
+    pub message: String,    // Human-readable explanation
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, code: &'static str, message: impl Into<String>) -> Self {
+        FieldError {
+            field: field.into(),
+            code,
+            message: message.into(),
+        }
+    }
+}
 
 /// Custom application-specific error enum.
 /// This allows us to map various internal errors to standardized HTTP responses.
 #[derive(Debug)]
 pub enum AppError {
-    InternalServerError(String), // For unhandled server errors, with a message
-    // NotFound(String),            // For resources not found (e.g., user, property)
-    Conflict(String),            // For resource conflicts (e.g., username already taken)
-    Unauthorized(String),        // For authentication failures
-    // BadRequest(String),          // For invalid request data
-    // Forbidden(String),           // For authorization failures (e.g., not allowed to access resource)
+    InternalServerError(String),   // For unhandled server errors, with a message
+    NotFound(String),              // For resources not found (e.g., user, property)
+    Conflict(String),              // For resource conflicts (e.g., username already taken)
+    Unauthorized(String),          // For authentication failures
+    BadRequest(String),            // For invalid request data
+    Forbidden(String),             // For authorization failures (e.g., not allowed to access resource)
+    TooManyRequests(String),       // For throttled requests (e.g., repeated failed logins)
+    InvalidQuery(Vec<FieldError>), // For query parameters that are unknown or fail to parse
+    Validation(Vec<FieldError>),   // For request bodies that break validation rules
+    UnsupportedMediaType(String),  // For request bodies sent without a JSON content type
+}
+
+impl AppError {
+    /// HTTP status and stable, machine-readable code for each kind of error.
+    /// Clients may rely on the codes; the detail messages can change.
+    fn status_and_code(&self) -> (StatusCode, &'static str) {
+        match self {
+            AppError::InternalServerError(_) => {
+                (StatusCode::INTERNAL_SERVER_ERROR, "internal_error")
+            }
+            AppError::NotFound(_) => (StatusCode::NOT_FOUND, "not_found"),
+            AppError::Conflict(_) => (StatusCode::CONFLICT, "conflict"),
+            AppError::Unauthorized(_) => (StatusCode::UNAUTHORIZED, "unauthorized"),
+            AppError::BadRequest(_) => (StatusCode::BAD_REQUEST, "bad_request"),
+            AppError::Forbidden(_) => (StatusCode::FORBIDDEN, "forbidden"),
+            AppError::TooManyRequests(_) => (StatusCode::TOO_MANY_REQUESTS, "too_many_requests"),
+            AppError::InvalidQuery(_) => (StatusCode::BAD_REQUEST, "invalid_query"),
+            AppError::Validation(_) => (StatusCode::UNPROCESSABLE_ENTITY, "validation_failed"),
+            AppError::UnsupportedMediaType(_) => {
+                (StatusCode::UNSUPPORTED_MEDIA_TYPE, "unsupported_media_type")
+            }
+        }
+    }
+}
+
+/// Error response body, following RFC 7807 "Problem Details for HTTP APIs".
+/// Sent with the `application/problem+json` content type.
+#[derive(Debug, Serialize)]
+struct ProblemDetails {
+    #[serde(rename = "type")]
+    problem_type: &'static str, // Always "about:blank"; `code` tells problems apart
+    title: &'static str,        // The HTTP status phrase
+    status: u16,
+    code: &'static str,         // Stable error code, see `AppError::status_and_code`
+    detail: String,             // Human-readable explanation of this occurrence
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>, // Same as the `X-Request-Id` response header
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    errors: Vec<FieldError>,    // Per-field problems for invalid queries and bodies
 }
 
 // --- Implement `IntoResponse` for `AppError` ---
 // This tells Axum how to convert our `AppError` enum into an HTTP response.
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        let (status, error_message) = match self {
-            AppError::InternalServerError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
-            // AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
-            AppError::Conflict(msg) => (StatusCode::CONFLICT, msg),
-            AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
-            // AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
-            // AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
+        let (status, code) = self.status_and_code();
+
+        let (detail, errors) = match self {
+            AppError::InternalServerError(msg) => {
+                // The message may hold SQL or other internals, so it only goes to the logs
+                tracing::error!(code, "Internal server error: {}", msg);
+                ("An unexpected error occurred".to_string(), Vec::new())
+            }
+            AppError::InvalidQuery(fields) => (
+                "One or more query parameters are invalid".to_string(),
+                fields,
+            ),
+            AppError::Validation(fields) => ("One or more fields are invalid".to_string(), fields),
+            AppError::NotFound(msg)
+            | AppError::Conflict(msg)
+            | AppError::Unauthorized(msg)
+            | AppError::BadRequest(msg)
+            | AppError::Forbidden(msg)
+            | AppError::TooManyRequests(msg)
+            | AppError::UnsupportedMediaType(msg) => (msg, Vec::new()),
         };
 
-        // Construct a JSON response body
-        let body = Json(json!({
-            "error": error_message,
-        }));
+        let body = ProblemDetails {
+            problem_type: "about:blank",
+            title: status.canonical_reason().unwrap_or("Error"),
+            status: status.as_u16(),
+            code,
+            detail,
+            request_id: current_request_id(),
+            errors,
+        };
 
-        // Return the HTTP response
-        (status, body).into_response()
+        // Return the HTTP response; the header overrides the `application/json` set by `Json`
+        (
+            status,
+            [(header::CONTENT_TYPE, "application/problem+json")],
+            Json(body),
+        )
+            .into_response()
     }
 }
 
@@ -51,6 +138,21 @@ impl From<sqlx::Error> for AppError {
     }
 }
 
+/// Converts axum's JSON body rejections, which are plain text by default.
+impl From<JsonRejection> for AppError {
+    fn from(rejection: JsonRejection) -> Self {
+        match rejection {
+            JsonRejection::MissingJsonContentType(e) => {
+                AppError::UnsupportedMediaType(e.body_text())
+            }
+            JsonRejection::JsonDataError(e) => {
+                AppError::Validation(vec![FieldError::new("body", "invalid", e.body_text())])
+            }
+            other => AppError::BadRequest(other.body_text()),
+        }
+    }
+}
+
 /// Converts `argon2::Error` into `AppError::InternalServerError`.
 impl From<argon2::Error> for AppError {
     fn from(err: argon2::Error) -> Self {
@@ -58,6 +160,13 @@ impl From<argon2::Error> for AppError {
     }
 }
 
+/// Converts `argon2::password_hash::Error` into `AppError::InternalServerError`.
+impl From<argon2::password_hash::Error> for AppError {
+    fn from(err: argon2::password_hash::Error) -> Self {
+        AppError::InternalServerError(format!("Password hashing error: {}", err))
+    }
+}
+
 /// Converts `jsonwebtoken::errors::Error` into `AppError::Unauthorized`.
 impl From<jsonwebtoken::errors::Error> for AppError {
     fn from(err: jsonwebtoken::errors::Error) -> Self {
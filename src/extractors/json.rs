@@ -0,0 +1,57 @@
+// src/extractors/json.rs
+
+use axum::Json;
+use axum::extract::{FromRequest, Request};
+use serde::de::DeserializeOwned;
+
+use crate::errors::{AppError, FieldError};
+use crate::validation::{Validate, Validator};
+
+/// JSON body extractor that checks the body's `Validate` rules.
+///
+/// Bodies that fail to deserialize or break a rule are rejected with `422` and a
+/// list of field errors; a missing content type or malformed JSON gets the same
+/// JSON error format as any other `AppError`, instead of axum's plain text.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValidatedJson<T>(pub T);
+
+impl<T, S> FromRequest<S> for ValidatedJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<serde_json::Value>::from_request(req, state).await?;
+
+        let body: T = serde_path_to_error::deserialize(value).map_err(|e| {
+            AppError::Validation(vec![field_error(e.path().to_string(), e.into_inner())])
+        })?;
+
+        let mut validator = Validator::default();
+        body.validate(&mut validator);
+        validator.finish()?;
+
+        Ok(ValidatedJson(body))
+    }
+}
+
+/// Turns a deserialization error at `path` into a field error. Missing fields are
+/// reported at the path of the field itself rather than of the enclosing object.
+fn field_error(path: String, error: serde_json::Error) -> FieldError {
+    let message = error.to_string();
+    let missing = message
+        .strip_prefix("missing field `")
+        .and_then(|rest| rest.split('`').next());
+
+    match missing {
+        Some(name) if path == "." => FieldError::new(name, "required", "Field is required"),
+        Some(name) => FieldError::new(
+            format!("{}.{}", path, name),
+            "required",
+            "Field is required",
+        ),
+        None => FieldError::new(path, "invalid", message),
+    }
+}
@@ -0,0 +1,7 @@
+// Declare the sub-modules
+pub mod json;
+pub mod query;
+
+// Re-export all public items from sub-modules
+pub use json::*;
+pub use query::*;
@@ -0,0 +1,111 @@
+// src/extractors/query.rs
+
+use axum::extract::FromRequestParts;
+use http::request::Parts;
+use serde::de::DeserializeOwned;
+
+use crate::errors::{AppError, FieldError};
+
+/// Typed query string extractor that reports every bad parameter at once.
+///
+/// Unlike `axum::extract::Query`, which stops at the first problem, a rejected
+/// query lists each unknown, repeated or unparsable parameter. Target structs
+/// should use `#[serde(deny_unknown_fields)]` so a misspelt filter is an error
+/// rather than silently ignored.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValidQuery<T>(pub T);
+
+impl<T, S> FromRequestParts<S> for ValidQuery<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parse_query(parts.uri.query().unwrap_or_default()).map(ValidQuery)
+    }
+}
+
+/// Deserializes a query string, collecting a `FieldError` per offending parameter.
+pub fn parse_query<T: DeserializeOwned>(query: &str) -> Result<T, AppError> {
+    let whole_error = match serde_urlencoded::from_str::<T>(query) {
+        Ok(value) => return Ok(value),
+        Err(e) => e,
+    };
+
+    let pairs: Vec<(String, String)> = serde_urlencoded::from_str(query).unwrap_or_default();
+    let mut errors: Vec<FieldError> = Vec::new();
+
+    for (index, (name, value)) in pairs.iter().enumerate() {
+        if errors.iter().any(|error| &error.field == name) {
+            continue;
+        }
+        if pairs[..index].iter().any(|(earlier, _)| earlier == name) {
+            errors.push(FieldError::new(
+                name,
+                "duplicate",
+                "Parameter was given more than once",
+            ));
+            continue;
+        }
+
+        // Every filter is optional, so each parameter can be checked on its own
+        let single = serde_urlencoded::to_string([(name, value)]).unwrap_or_default();
+        if let Err(e) = serde_urlencoded::from_str::<T>(&single) {
+            let message = e.to_string();
+            let code = if message.starts_with("unknown field") {
+                "unknown"
+            } else {
+                "invalid"
+            };
+            errors.push(FieldError::new(name, code, message));
+        }
+    }
+
+    if errors.is_empty() {
+        // Not down to a single parameter, e.g. a required one is missing
+        errors.push(FieldError::new("query", "invalid", whole_error.to_string()));
+    }
+
+    Err(AppError::InvalidQuery(errors))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Filters {
+        limit: Option<i64>,
+        status: Option<String>,
+    }
+
+    fn field_errors(query: &str) -> Vec<(String, &'static str)> {
+        match parse_query::<Filters>(query) {
+            Err(AppError::InvalidQuery(errors)) => errors
+                .into_iter()
+                .map(|error| (error.field, error.code))
+                .collect(),
+            other => panic!("expected an invalid query, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn each_bad_parameter_gets_its_own_error() {
+        let filters = parse_query::<Filters>("limit=10&status=paid").unwrap();
+        assert_eq!(filters.limit, Some(10));
+        assert_eq!(filters.status.as_deref(), Some("paid"));
+
+        assert_eq!(
+            field_errors("limit=ten&stauts=paid&status=pending&status=failed"),
+            [
+                ("limit".to_string(), "invalid"),
+                ("stauts".to_string(), "unknown"),
+                ("status".to_string(), "duplicate"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,197 @@
+// src/handlers/account.rs
+
+use axum::extract::State;
+use http::StatusCode;
+
+use crate::AppState;
+use crate::auth::AuthUser;
+use crate::auth::emails::{send_password_reset_email, send_verification_email};
+use crate::auth::password::hash_password;
+use crate::auth::tokens::hash_token;
+use crate::db;
+use crate::errors::AppError;
+use crate::extractors::ValidatedJson;
+use crate::models::{
+    ForgotPasswordRequest, ResetPasswordRequest, UserTokenPurpose, VerifyEmailRequest,
+};
+
+/// Sends a new verification email to the authenticated user.
+pub async fn request_email_verification(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+) -> Result<StatusCode, AppError> {
+    auth.require_session()?;
+
+    let user = db::find_user_by_id(&app_state.pool, auth.user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".into()))?;
+
+    if user.email_verified_at.is_some() {
+        return Err(AppError::Conflict(
+            "Email address is already verified".into(),
+        ));
+    }
+
+    send_verification_email(&app_state, &user).await?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+/// Consumes an email verification token.
+pub async fn verify_email(
+    State(app_state): State<AppState>,
+    ValidatedJson(request): ValidatedJson<VerifyEmailRequest>,
+) -> Result<StatusCode, AppError> {
+    let user_id = db::consume_user_token(
+        &app_state.pool,
+        UserTokenPurpose::EmailVerification,
+        &hash_token(&request.token),
+    )
+    .await?
+    .ok_or_else(|| AppError::BadRequest("Invalid or expired verification token".into()))?;
+
+    db::mark_email_verified(&app_state.pool, user_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Starts a password reset by emailing a reset link.
+///
+/// Always answers `202 Accepted` and sends the email in the background, so the
+/// response does not reveal whether an account exists for the address.
+pub async fn forgot_password(
+    State(app_state): State<AppState>,
+    ValidatedJson(request): ValidatedJson<ForgotPasswordRequest>,
+) -> Result<StatusCode, AppError> {
+    tokio::spawn(async move {
+        let result = match db::find_user_by_email(&app_state.pool, &request.email).await {
+            Ok(Some(user)) => send_password_reset_email(&app_state, &user).await,
+            Ok(None) => Ok(()),
+            Err(e) => Err(e),
+        };
+
+        if let Err(e) = result {
+            tracing::error!("Failed to send password reset email: {:?}", e);
+        }
+    });
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+/// Sets a new password using an emailed reset token and ends every existing session.
+pub async fn reset_password(
+    State(app_state): State<AppState>,
+    ValidatedJson(request): ValidatedJson<ResetPasswordRequest>,
+) -> Result<StatusCode, AppError> {
+    let user_id = db::consume_user_token(
+        &app_state.pool,
+        UserTokenPurpose::PasswordReset,
+        &hash_token(&request.token),
+    )
+    .await?
+    .ok_or_else(|| AppError::BadRequest("Invalid or expired password reset token".into()))?;
+
+    let new_hash = hash_password(&app_state.auth_config.password, request.new_password).await?;
+    db::update_password_hash(&app_state.pool, user_id, &new_hash).await?;
+    db::revoke_user_refresh_tokens(&app_state.pool, user_id).await?;
+
+    // Receiving the reset email also proves the user owns the address
+    db::mark_email_verified(&app_state.pool, user_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::issue_session;
+    use crate::db::fixtures::{CapturingMailer, app_state, seed_user};
+    use crate::models::UserRole;
+    use sqlx::PgPool;
+    use std::sync::Arc;
+
+    /// The token in the link of the last email sent.
+    fn last_link_token(mailer: &CapturingMailer) -> String {
+        let sent = mailer.sent.lock().unwrap();
+        let body = &sent.last().unwrap().body;
+        let (_, rest) = body.split_once("token=").unwrap();
+        rest.split_whitespace().next().unwrap().to_string()
+    }
+
+    #[sqlx::test]
+    async fn a_password_reset_link_works_once_and_ends_every_session(pool: PgPool) {
+        let mailer = Arc::new(CapturingMailer::default());
+        let app_state = AppState {
+            mailer: mailer.clone(),
+            ..app_state(pool)
+        };
+        let user_id = seed_user(&app_state.pool, "tenant", UserRole::Tenant).await;
+        let user = db::find_user_by_id(&app_state.pool, user_id)
+            .await
+            .unwrap()
+            .unwrap();
+        let session = issue_session(&app_state, user.clone(), None).await.unwrap();
+
+        send_password_reset_email(&app_state, &user).await.unwrap();
+        let token = last_link_token(&mailer);
+        let reset = |token: String| ResetPasswordRequest {
+            token,
+            new_password: "a brand new password".into(),
+        };
+
+        reset_password(
+            State(app_state.clone()),
+            ValidatedJson(reset(token.clone())),
+        )
+        .await
+        .unwrap();
+        let stored = db::find_user_by_id(&app_state.pool, user_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_ne!(stored.password_hash, user.password_hash);
+        assert!(stored.email_verified_at.is_some());
+        let refreshed =
+            db::find_refresh_token_by_hash(&app_state.pool, &hash_token(&session.refresh_token))
+                .await
+                .unwrap()
+                .unwrap();
+        assert!(refreshed.revoked_at.is_some());
+
+        let reused = reset_password(State(app_state.clone()), ValidatedJson(reset(token))).await;
+        assert!(matches!(reused, Err(AppError::BadRequest(_))));
+    }
+
+    #[sqlx::test]
+    async fn only_the_latest_verification_link_works_and_only_once(pool: PgPool) {
+        let mailer = Arc::new(CapturingMailer::default());
+        let app_state = AppState {
+            mailer: mailer.clone(),
+            ..app_state(pool)
+        };
+        let user_id = seed_user(&app_state.pool, "tenant", UserRole::Tenant).await;
+        let user = db::find_user_by_id(&app_state.pool, user_id)
+            .await
+            .unwrap()
+            .unwrap();
+
+        send_verification_email(&app_state, &user).await.unwrap();
+        let first = last_link_token(&mailer);
+        send_verification_email(&app_state, &user).await.unwrap();
+        let second = last_link_token(&mailer);
+
+        let verify = |token: String| {
+            verify_email(
+                State(app_state.clone()),
+                ValidatedJson(VerifyEmailRequest { token }),
+            )
+        };
+        assert!(matches!(verify(first).await, Err(AppError::BadRequest(_))));
+        verify(second.clone()).await.unwrap();
+        assert!(matches!(verify(second).await, Err(AppError::BadRequest(_))));
+
+        let stored = db::find_user_by_id(&app_state.pool, user_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(stored.email_verified_at.is_some());
+    }
+}
@@ -0,0 +1,182 @@
+// src/handlers/api_key.rs
+
+use axum::extract::{Json, Path, State};
+use http::StatusCode;
+use time::Duration;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::AppState;
+use crate::auth::AuthUser;
+use crate::auth::api_keys::generate_api_key;
+use crate::auth::tokens::hash_token;
+use crate::db;
+use crate::errors::AppError;
+use crate::extractors::ValidatedJson;
+use crate::models::{ApiKey, CreateApiKey, CreatedApiKey};
+
+/// Creates an API key acting as the caller. The key itself is only returned this once.
+pub async fn create_api_key(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    ValidatedJson(request): ValidatedJson<CreateApiKey>,
+) -> Result<Json<CreatedApiKey>, AppError> {
+    auth.require_session()?;
+
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err(AppError::BadRequest("API key name is required".into()));
+    }
+    if request.scopes.is_empty() {
+        return Err(AppError::BadRequest(
+            "API key needs at least one scope".into(),
+        ));
+    }
+
+    let expires_at = request
+        .expires_in_days
+        .map(|days| OffsetDateTime::now_utc() + Duration::days(days));
+
+    let mut scopes: Vec<String> = request
+        .scopes
+        .iter()
+        .map(|scope| scope.as_str().to_owned())
+        .collect();
+    scopes.sort();
+    scopes.dedup();
+
+    let (key, prefix) = generate_api_key();
+    let api_key = db::create_api_key(
+        &app_state.pool,
+        auth.user_id,
+        name,
+        &prefix,
+        &hash_token(&key),
+        &scopes,
+        expires_at,
+    )
+    .await?;
+
+    tracing::info!(user_id = %auth.user_id, api_key_id = %api_key.id, "API key created");
+    Ok(Json(CreatedApiKey { api_key, key }))
+}
+
+/// Lists the caller's API keys. Only the prefix of each key is shown.
+pub async fn list_api_keys(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<Vec<ApiKey>>, AppError> {
+    auth.require_session()?;
+
+    let api_keys = db::list_api_keys(&app_state.pool, auth.user_id).await?;
+    Ok(Json(api_keys))
+}
+
+/// Revokes an API key. Users can revoke their own keys; admins can revoke anyone's.
+pub async fn revoke_api_key(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    auth.require_session()?;
+
+    // Other users' keys are reported as missing rather than forbidden
+    let api_key = db::find_api_key_by_id(&app_state.pool, id)
+        .await?
+        .filter(|api_key| api_key.user_id == auth.user_id || auth.is_admin())
+        .ok_or_else(|| AppError::NotFound("API key not found".into()))?;
+
+    if db::revoke_api_key(&app_state.pool, api_key.id).await? {
+        tracing::info!(user_id = %auth.user_id, api_key_id = %api_key.id, "API key revoked");
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::fixtures::{app_state, seed_user};
+    use crate::handlers::{create_property, get_current_user};
+    use crate::models::{ApiScope, CreateProperty, UserRole};
+    use sqlx::PgPool;
+
+    /// Authenticates a request carrying `key` the way every route does.
+    async fn authenticate(app_state: &AppState, key: &str) -> Result<AuthUser, AppError> {
+        let (parts, _) = http::Request::builder()
+            .header("x-api-key", key)
+            .body(())
+            .unwrap()
+            .into_parts();
+        AuthUser::from_headers(&parts, app_state).await
+    }
+
+    #[sqlx::test]
+    async fn keys_are_limited_to_their_scopes_and_lifetime(pool: PgPool) {
+        let app_state = app_state(pool);
+        let user_id = seed_user(&app_state.pool, "landlord", UserRole::Landlord).await;
+        let session = AuthUser {
+            user_id,
+            username: "landlord".into(),
+            role: UserRole::Landlord,
+            scopes: None,
+        };
+
+        let Json(created) = create_api_key(
+            State(app_state.clone()),
+            session.clone(),
+            ValidatedJson(CreateApiKey {
+                name: "Nightly export".into(),
+                scopes: vec![ApiScope::PaymentsRead, ApiScope::UsersRead],
+                expires_in_days: Some(30),
+            }),
+        )
+        .await
+        .unwrap();
+
+        let auth = authenticate(&app_state, &created.key).await.unwrap();
+        assert_eq!(auth.user_id, user_id);
+        let Json(me) = get_current_user(State(app_state.clone()), auth.clone())
+            .await
+            .unwrap();
+        assert_eq!(me.id, user_id);
+
+        // Out of scope, or account management: 403
+        let property = CreateProperty {
+            address: "1 Test Street".into(),
+            unit_number: None,
+            owner_id: None,
+        };
+        let result = create_property(
+            State(app_state.clone()),
+            auth.clone(),
+            ValidatedJson(property),
+        )
+        .await;
+        assert!(matches!(result, Err(AppError::Forbidden(_))));
+        let result = list_api_keys(State(app_state.clone()), auth).await;
+        assert!(matches!(result, Err(AppError::Forbidden(_))));
+
+        // Revoked or expired: 401
+        revoke_api_key(State(app_state.clone()), session, Path(created.api_key.id))
+            .await
+            .unwrap();
+        let result = authenticate(&app_state, &created.key).await;
+        assert!(matches!(result, Err(AppError::Unauthorized(_))));
+
+        let (key, prefix) = generate_api_key();
+        db::create_api_key(
+            &app_state.pool,
+            user_id,
+            "Expired",
+            &prefix,
+            &hash_token(&key),
+            &["payments:read".to_string()],
+            Some(OffsetDateTime::now_utc() - Duration::minutes(1)),
+        )
+        .await
+        .unwrap();
+        let result = authenticate(&app_state, &key).await;
+        assert!(matches!(result, Err(AppError::Unauthorized(_))));
+    }
+}
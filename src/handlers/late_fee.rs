@@ -0,0 +1,158 @@
+// src/handlers/late_fee.rs
+
+use axum::extract::{Json, Path, State};
+use http::StatusCode;
+use uuid::Uuid;
+
+use crate::AppState;
+use crate::auth::AuthUser;
+use crate::db::{self, PaymentStatusChange};
+use crate::errors::AppError;
+use crate::extractors::ValidatedJson;
+use crate::models::{
+    ApiScope, LateFeePolicy, Payment, PaymentKind, PaymentStatus, Property, SetLateFeePolicy,
+    UserRole, WaiveLateFee,
+};
+
+/// Returns the late fee policy of a property. Its owner, its current tenants and
+/// admins can see it.
+pub async fn get_late_fee_policy(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(property_id): Path<Uuid>,
+) -> Result<Json<LateFeePolicy>, AppError> {
+    auth.require_scope(ApiScope::PropertiesRead)?;
+
+    let property = find_property(&app_state, property_id).await?;
+    let visible = match auth.role {
+        UserRole::Admin => true,
+        UserRole::Landlord => property.owner_id == Some(auth.user_id),
+        UserRole::Tenant => property.current_tenant_ids.contains(&auth.user_id),
+    };
+    if !visible {
+        return Err(AppError::Forbidden(
+            "You are not allowed to view this property".into(),
+        ));
+    }
+
+    let policy = db::find_late_fee_policy(&app_state.pool, property_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("This property has no late fee policy".into()))?;
+    Ok(Json(policy))
+}
+
+/// Sets the late fee policy of a property, replacing any it had. Only its owner or
+/// an admin may do this. Fees already charged keep their amount.
+pub async fn set_late_fee_policy(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(property_id): Path<Uuid>,
+    ValidatedJson(policy): ValidatedJson<SetLateFeePolicy>,
+) -> Result<Json<LateFeePolicy>, AppError> {
+    auth.require_scope(ApiScope::PropertiesWrite)?;
+
+    let property = find_property(&app_state, property_id).await?;
+    require_owner(&auth, &property)?;
+
+    tracing::info!(user_id = %auth.user_id, %property_id, "Setting late fee policy");
+    let policy = db::set_late_fee_policy(&app_state.pool, property_id, &policy).await?;
+    Ok(Json(policy))
+}
+
+/// Removes the late fee policy of a property, so no further fees are charged there.
+pub async fn delete_late_fee_policy(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(property_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    auth.require_scope(ApiScope::PropertiesWrite)?;
+
+    let property = find_property(&app_state, property_id).await?;
+    require_owner(&auth, &property)?;
+
+    if !db::delete_late_fee_policy(&app_state.pool, property_id).await? {
+        return Err(AppError::NotFound(
+            "This property has no late fee policy".into(),
+        ));
+    }
+
+    tracing::info!(user_id = %auth.user_id, %property_id, "Deleted late fee policy");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Waives a late fee that is still owed. Only the property's landlord or an admin
+/// can do this, and must say why; the waiver is kept in the fee's status history.
+pub async fn waive_late_fee(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    ValidatedJson(request): ValidatedJson<WaiveLateFee>,
+) -> Result<Json<Payment>, AppError> {
+    auth.require_scope(ApiScope::PaymentsWrite)?;
+
+    let fee = db::find_payment_by_id(&app_state.pool, id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Payment not found".into()))?;
+
+    if !auth.is_admin() {
+        let property = match fee.property_id {
+            Some(property_id) => db::find_property_by_id(&app_state.pool, property_id).await?,
+            None => None,
+        };
+        let owns_property =
+            property.is_some_and(|property| property.owner_id == Some(auth.user_id));
+        if !owns_property {
+            return Err(AppError::Forbidden(
+                "Only the property's landlord can waive this fee".into(),
+            ));
+        }
+    }
+
+    if fee.kind != PaymentKind::LateFee {
+        return Err(AppError::Conflict("Only late fees can be waived".into()));
+    }
+    if !matches!(
+        fee.status,
+        PaymentStatus::Pending | PaymentStatus::Overdue | PaymentStatus::PartiallyPaid
+    ) {
+        return Err(AppError::Conflict(format!(
+            "A late fee that is {} cannot be waived",
+            fee.status.as_str()
+        )));
+    }
+
+    tracing::info!(user_id = %auth.user_id, payment_id = %id, "Waiving late fee");
+    let waived = db::update_payment_status(
+        &app_state.pool,
+        id,
+        PaymentStatusChange {
+            from: fee.status,
+            to: PaymentStatus::Waived,
+            changed_by: auth.user_id,
+            reason: &request.reason,
+            transaction_id: None,
+        },
+    )
+    .await?
+    .ok_or_else(|| {
+        AppError::Conflict("Payment was changed by another request, please retry".into())
+    })?;
+
+    Ok(Json(waived))
+}
+
+async fn find_property(app_state: &AppState, id: Uuid) -> Result<Property, AppError> {
+    db::find_property_by_id(&app_state.pool, id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Property not found".into()))
+}
+
+fn require_owner(auth: &AuthUser, property: &Property) -> Result<(), AppError> {
+    if auth.is_admin() || property.owner_id == Some(auth.user_id) {
+        Ok(())
+    } else {
+        Err(AppError::Forbidden(
+            "Only the property's owner can change its late fee policy".into(),
+        ))
+    }
+}
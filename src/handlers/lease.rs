@@ -0,0 +1,230 @@
+// src/handlers/lease.rs
+
+use axum::extract::{Json, Path, State};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::AppState;
+use crate::auth::AuthUser;
+use crate::db;
+use crate::errors::{AppError, FieldError};
+use crate::extractors::{ValidQuery, ValidatedJson};
+use crate::models::{
+    ApiScope, CreateLease, Cursor, Lease, LeaseStatus, ListLeasesQuery, Page, SortOrder,
+    UpdateLease, UpdateLeaseStatus, UserRole, page_limit,
+};
+
+/// Creates a lease for a property. Only its owner or an admin may do this.
+///
+/// Leases start out as drafts unless created `active`. Active leases on the same
+/// property may not overlap; one that would fails with `409 Conflict`.
+pub async fn create_lease(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    ValidatedJson(new_lease): ValidatedJson<CreateLease>,
+) -> Result<Json<Lease>, AppError> {
+    auth.require_scope(ApiScope::PropertiesWrite)?;
+    auth.require_role(&[UserRole::Admin, UserRole::Landlord])?;
+    require_property_owner(&app_state, &auth, new_lease.property_id).await?;
+
+    let lease = db::create_lease(&app_state.pool, &new_lease).await?;
+    tracing::info!(user_id = %auth.user_id, lease_id = %lease.id, property_id = %lease.property_id, "Created lease");
+    Ok(Json(lease))
+}
+
+/// Handles listing the leases visible to the caller, newest first, one page at a time.
+///
+/// Admins see every lease, landlords the leases on properties they own and tenants
+/// the leases they are on.
+pub async fn list_leases(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    ValidQuery(mut query): ValidQuery<ListLeasesQuery>,
+) -> Result<Json<Page<Lease>>, AppError> {
+    auth.require_scope(ApiScope::PropertiesRead)?;
+
+    let mut owner_id = None;
+    match auth.role {
+        UserRole::Admin => {}
+        UserRole::Landlord => owner_id = Some(auth.user_id),
+        UserRole::Tenant => {
+            if query
+                .tenant_id
+                .is_some_and(|tenant_id| tenant_id != auth.user_id)
+            {
+                return Err(AppError::Forbidden(
+                    "Tenants can only view their own leases".into(),
+                ));
+            }
+            query.tenant_id = Some(auth.user_id);
+        }
+    }
+
+    let order = SortOrder::Desc;
+    let after = query
+        .cursor
+        .as_deref()
+        .map(|cursor| Cursor::decode(cursor, "start_date", "date", order))
+        .transpose()?;
+    let limit = page_limit(query.limit);
+
+    let leases =
+        db::list_leases(&app_state.pool, &query, owner_id, after.as_ref(), limit + 1).await?;
+
+    Ok(Json(Page::from_rows(leases, limit, |lease| Cursor {
+        sort: "start_date".into(),
+        order,
+        value: lease.start_date.to_string(),
+        id: lease.id,
+    })))
+}
+
+/// Returns a single lease visible to the caller.
+pub async fn get_lease(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Lease>, AppError> {
+    auth.require_scope(ApiScope::PropertiesRead)?;
+
+    let lease = find_lease(&app_state, id).await?;
+    if auth.role == UserRole::Tenant {
+        if !lease.tenant_ids.contains(&auth.user_id) {
+            return Err(AppError::Forbidden(
+                "You are not allowed to view this lease".into(),
+            ));
+        }
+    } else {
+        require_property_owner(&app_state, &auth, lease.property_id).await?;
+    }
+
+    Ok(Json(lease))
+}
+
+/// Changes the terms of a lease. Drafts can change freely; active leases only their
+/// tenants and end date, and ended or terminated leases not at all.
+pub async fn update_lease(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    ValidatedJson(changes): ValidatedJson<UpdateLease>,
+) -> Result<Json<Lease>, AppError> {
+    auth.require_scope(ApiScope::PropertiesWrite)?;
+
+    let lease = find_lease(&app_state, id).await?;
+    require_property_owner(&app_state, &auth, lease.property_id).await?;
+
+    match lease.status {
+        LeaseStatus::Draft => {}
+        LeaseStatus::Active if !changes.changes_fixed_terms() => {}
+        LeaseStatus::Active => {
+            return Err(AppError::Conflict(
+                "Only the tenants and end date of an active lease can change".into(),
+            ));
+        }
+        LeaseStatus::Ended | LeaseStatus::Terminated => {
+            return Err(AppError::Conflict(format!(
+                "A lease that is {} cannot be changed",
+                lease.status.as_str()
+            )));
+        }
+    }
+
+    tracing::info!(user_id = %auth.user_id, lease_id = %id, "Updating lease");
+    let lease = db::update_lease(&app_state.pool, id, lease.status, &changes)
+        .await?
+        .ok_or_else(|| AppError::Conflict("The lease was changed by another request".into()))?;
+    Ok(Json(lease))
+}
+
+/// Activates, ends or terminates a lease.
+///
+/// Only the owner of the property or an admin can do this. Transitions not allowed
+/// by `LeaseStatus::can_transition_to` fail with `409 Conflict`, as does activating
+/// a lease that overlaps another active one.
+pub async fn update_lease_status(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    ValidatedJson(request): ValidatedJson<UpdateLeaseStatus>,
+) -> Result<Json<Lease>, AppError> {
+    auth.require_scope(ApiScope::PropertiesWrite)?;
+
+    let lease = find_lease(&app_state, id).await?;
+    require_property_owner(&app_state, &auth, lease.property_id).await?;
+
+    if !lease.status.can_transition_to(&request.status) {
+        return Err(AppError::Conflict(format!(
+            "Cannot change lease status from {} to {}",
+            lease.status.as_str(),
+            request.status.as_str()
+        )));
+    }
+
+    let end_date = match (lease.status, request.status) {
+        // An abandoned draft never ran, so it keeps the dates it was drafted with
+        (_, LeaseStatus::Active) | (LeaseStatus::Draft, _) => {
+            if request.end_date.is_some() {
+                return Err(AppError::Validation(vec![FieldError::new(
+                    "end_date",
+                    "invalid",
+                    "Only allowed when ending or terminating an active lease",
+                )]));
+            }
+            None
+        }
+        (_, to) => {
+            // A lease that runs its course ends on its scheduled last day; one ended
+            // early, or without a last day, ends today
+            let today = OffsetDateTime::now_utc().date();
+            let scheduled = lease.end_date.filter(|_| to == LeaseStatus::Ended);
+            Some(
+                request
+                    .end_date
+                    .or(scheduled)
+                    .unwrap_or(today.max(lease.start_date)),
+            )
+        }
+    };
+
+    tracing::info!(
+        user_id = %auth.user_id,
+        lease_id = %id,
+        from = lease.status.as_str(),
+        to = request.status.as_str(),
+        "Updating lease status"
+    );
+    let lease =
+        db::update_lease_status(&app_state.pool, id, lease.status, request.status, end_date)
+            .await?
+            .ok_or_else(|| AppError::Conflict("The lease was changed by another request".into()))?;
+    Ok(Json(lease))
+}
+
+async fn find_lease(app_state: &AppState, id: Uuid) -> Result<Lease, AppError> {
+    db::find_lease_by_id(&app_state.pool, id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Lease not found".into()))
+}
+
+/// Checks the caller is an admin or owns the property.
+async fn require_property_owner(
+    app_state: &AppState,
+    auth: &AuthUser,
+    property_id: Uuid,
+) -> Result<(), AppError> {
+    if auth.is_admin() {
+        return Ok(());
+    }
+
+    let property = db::find_property_by_id(&app_state.pool, property_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Property not found".into()))?;
+    if property.owner_id != Some(auth.user_id) {
+        return Err(AppError::Forbidden(
+            "Only the property's owner can manage its leases".into(),
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,209 @@
+// src/handlers/ledger.rs
+
+use axum::extract::{Json, Path, State};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::AppState;
+use crate::auth::AuthUser;
+use crate::db;
+use crate::errors::{AppError, FieldError};
+use crate::extractors::{ValidQuery, ValidatedJson};
+use crate::models::{
+    ApiScope, CreateLedgerEntry, JournalEntry, JournalLine, LedgerAccount, LedgerQuery,
+    LedgerStatement, NewJournalEntry, PaymentKind, UserRole,
+};
+
+/// Returns a tenant's statement: every line of one ledger account for them, with
+/// the running balance, by default what they owe in USD up to today.
+///
+/// Tenants can only see their own statement, and landlords only the lines for
+/// properties they own.
+pub async fn tenant_ledger(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(user_id): Path<Uuid>,
+    ValidQuery(mut query): ValidQuery<LedgerQuery>,
+) -> Result<Json<LedgerStatement>, AppError> {
+    auth.require_scope(ApiScope::PaymentsRead)?;
+
+    let mut owner_id = None;
+    match auth.role {
+        UserRole::Admin => {}
+        UserRole::Landlord => owner_id = Some(auth.user_id),
+        UserRole::Tenant if user_id != auth.user_id => {
+            return Err(AppError::Forbidden(
+                "Tenants can only view their own ledger".into(),
+            ));
+        }
+        UserRole::Tenant => {}
+    }
+    if query.user_id.is_some_and(|filter| filter != user_id) {
+        return Err(AppError::BadRequest(
+            "user_id does not match the tenant in the path".into(),
+        ));
+    }
+    query.user_id = Some(user_id);
+
+    statement(&app_state, &query, owner_id).await
+}
+
+/// Returns a property's statement: every line of one ledger account for it, with
+/// the running balance, by default what its tenants owe in USD up to today.
+///
+/// Only its owner and admins can see every tenant's lines; its tenants see their
+/// own.
+pub async fn property_ledger(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(property_id): Path<Uuid>,
+    ValidQuery(mut query): ValidQuery<LedgerQuery>,
+) -> Result<Json<LedgerStatement>, AppError> {
+    auth.require_scope(ApiScope::PaymentsRead)?;
+
+    let property = db::find_property_by_id(&app_state.pool, property_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Property not found".into()))?;
+    match auth.role {
+        UserRole::Admin => {}
+        UserRole::Landlord if property.owner_id == Some(auth.user_id) => {}
+        UserRole::Landlord => {
+            return Err(AppError::Forbidden(
+                "You are not allowed to view this property's ledger".into(),
+            ));
+        }
+        UserRole::Tenant => {
+            if query.user_id.is_some_and(|user_id| user_id != auth.user_id) {
+                return Err(AppError::Forbidden(
+                    "Tenants can only view their own ledger".into(),
+                ));
+            }
+            query.user_id = Some(auth.user_id);
+        }
+    }
+    if query
+        .property_id
+        .is_some_and(|filter| filter != property_id)
+    {
+        return Err(AppError::BadRequest(
+            "property_id does not match the property in the path".into(),
+        ));
+    }
+    query.property_id = Some(property_id);
+
+    statement(&app_state, &query, None).await
+}
+
+/// Posts a credit, refund, write-off or deposit by hand. Only the property's owner
+/// or an admin may do this.
+///
+/// Unlike waiving a fee, credits and write-offs posted here only change the ledger;
+/// the payments they relate to keep their status and balance due.
+pub async fn create_ledger_entry(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    ValidatedJson(request): ValidatedJson<CreateLedgerEntry>,
+) -> Result<Json<JournalEntry>, AppError> {
+    auth.require_scope(ApiScope::PaymentsWrite)?;
+    auth.require_role(&[UserRole::Admin, UserRole::Landlord])?;
+
+    let property = db::find_property_by_id(&app_state.pool, request.property_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Property not found".into()))?;
+    if !auth.is_admin() && property.owner_id != Some(auth.user_id) {
+        return Err(AppError::Forbidden(
+            "Only the property's landlord can post to its ledger".into(),
+        ));
+    }
+    if db::find_user_by_id(&app_state.pool, request.user_id)
+        .await?
+        .is_none()
+    {
+        return Err(AppError::NotFound("User not found".into()));
+    }
+
+    // The charge a credit, refund or write-off relates to must be the same
+    // tenant's, at this property, in this currency
+    let mut charge_kind = PaymentKind::Manual;
+    if let Some(payment_id) = request.payment_id {
+        let payment = db::find_payment_by_id(&app_state.pool, payment_id).await?;
+        match payment {
+            Some(payment)
+                if payment.user_id == request.user_id
+                    && payment.property_id == Some(request.property_id)
+                    && payment.currency == request.currency =>
+            {
+                charge_kind = payment.kind;
+            }
+            _ => {
+                return Err(AppError::Validation(vec![FieldError::new(
+                    "payment_id",
+                    "invalid",
+                    "Must be a payment of this tenant at this property, in this currency",
+                )]));
+            }
+        }
+    }
+
+    // Validation has already refused the kinds without manual accounts
+    let (debit, credit) = request
+        .kind
+        .manual_accounts(charge_kind)
+        .ok_or_else(|| AppError::BadRequest("This kind of entry cannot be posted".into()))?;
+
+    tracing::info!(
+        user_id = %auth.user_id,
+        tenant_id = %request.user_id,
+        property_id = %request.property_id,
+        kind = ?request.kind,
+        amount = %request.amount,
+        "Posting ledger entry"
+    );
+    let mut tx = app_state.pool.begin().await?;
+    let entry = db::post_journal_entry(
+        &mut tx,
+        &NewJournalEntry {
+            kind: request.kind,
+            user_id: request.user_id,
+            property_id: Some(request.property_id),
+            currency: request.currency,
+            entry_date: request
+                .entry_date
+                .unwrap_or_else(|| OffsetDateTime::now_utc().date()),
+            memo: Some(request.memo),
+            payment_id: request.payment_id,
+            receipt_id: None,
+            created_by: Some(auth.user_id),
+            lines: JournalLine::transfer(debit, credit, &request.amount),
+        },
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(Json(entry))
+}
+
+async fn statement(
+    app_state: &AppState,
+    query: &LedgerQuery,
+    owner_id: Option<Uuid>,
+) -> Result<Json<LedgerStatement>, AppError> {
+    let to = query.to.unwrap_or_else(|| OffsetDateTime::now_utc().date());
+    if query.from.is_some_and(|from| from > to) {
+        return Err(AppError::Validation(vec![FieldError::new(
+            "from",
+            "range",
+            "Must not be after to",
+        )]));
+    }
+    let account = query.account.unwrap_or(LedgerAccount::RentReceivable);
+    let currency = query
+        .currency
+        .as_deref()
+        .unwrap_or("USD")
+        .to_ascii_uppercase();
+
+    let statement =
+        db::ledger_statement(&app_state.pool, query, owner_id, account, &currency, to).await?;
+    Ok(Json(statement))
+}
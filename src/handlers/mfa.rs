@@ -0,0 +1,319 @@
+// src/handlers/mfa.rs
+
+use axum::extract::{Json, State};
+use http::StatusCode;
+use time::OffsetDateTime;
+
+use crate::AppState;
+use crate::auth::jwt::decode_mfa_pending_token;
+use crate::auth::totp::{
+    build_totp, generate_recovery_codes, generate_secret, hash_recovery_code, verify_code,
+};
+use crate::auth::{AuthUser, ClientIp, issue_session};
+use crate::db;
+use crate::errors::AppError;
+use crate::extractors::ValidatedJson;
+use crate::models::{
+    AuthResponse, MfaLoginRequest, RecoveryCodesResponse, TotpCodeRequest, TotpEnrollmentResponse,
+    User, UserRole,
+};
+
+/// Starts TOTP enrollment by generating a secret for the caller's authenticator app.
+///
+/// The secret only takes effect once confirmed with a code from `confirm_totp`.
+pub async fn enroll_totp(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<TotpEnrollmentResponse>, AppError> {
+    auth.require_session()?;
+    auth.require_role(&[UserRole::Admin, UserRole::Landlord])?;
+    let user = find_user(&app_state, &auth).await?;
+
+    if user.totp_enabled_at.is_some() {
+        return Err(AppError::Conflict(
+            "Two-factor authentication is already enabled".into(),
+        ));
+    }
+
+    let secret = generate_secret();
+    let totp = build_totp(&secret, &app_state.auth_config.totp_issuer, &user.username)?;
+    db::set_pending_totp_secret(&app_state.pool, user.id, &secret).await?;
+
+    Ok(Json(TotpEnrollmentResponse {
+        secret,
+        otpauth_uri: totp.get_url(),
+    }))
+}
+
+/// Confirms TOTP enrollment with a first valid code and returns the recovery codes.
+pub async fn confirm_totp(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    ValidatedJson(request): ValidatedJson<TotpCodeRequest>,
+) -> Result<Json<RecoveryCodesResponse>, AppError> {
+    auth.require_session()?;
+    auth.require_role(&[UserRole::Admin, UserRole::Landlord])?;
+    let user = find_user(&app_state, &auth).await?;
+
+    if user.totp_enabled_at.is_some() {
+        return Err(AppError::Conflict(
+            "Two-factor authentication is already enabled".into(),
+        ));
+    }
+
+    let secret = user
+        .totp_secret
+        .as_deref()
+        .ok_or_else(|| AppError::BadRequest("Start TOTP enrollment first".into()))?;
+    let totp = build_totp(secret, &app_state.auth_config.totp_issuer, &user.username)?;
+    let step = verify_code(&totp, &request.code, None)
+        .ok_or_else(|| AppError::BadRequest("Invalid verification code".into()))?;
+
+    let recovery_codes = generate_recovery_codes();
+    let hashes: Vec<String> = recovery_codes
+        .iter()
+        .map(|code| hash_recovery_code(code))
+        .collect();
+    db::enable_totp(&app_state.pool, user.id, step, &hashes).await?;
+
+    tracing::info!(user_id = %user.id, "Two-factor authentication enabled");
+    Ok(Json(RecoveryCodesResponse { recovery_codes }))
+}
+
+/// Turns TOTP off. Requires a current code so a stolen access token alone is not enough.
+///
+/// Wrong codes count towards the same lockout as wrong passwords, so the code cannot be
+/// guessed from a stolen session either.
+pub async fn disable_totp(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    ClientIp(client_ip): ClientIp,
+    ValidatedJson(request): ValidatedJson<TotpCodeRequest>,
+) -> Result<StatusCode, AppError> {
+    auth.require_session()?;
+    let user = find_user(&app_state, &auth).await?;
+
+    if user.totp_enabled_at.is_none() {
+        return Err(AppError::BadRequest(
+            "Two-factor authentication is not enabled".into(),
+        ));
+    }
+
+    if user
+        .locked_until
+        .is_some_and(|locked_until| locked_until > OffsetDateTime::now_utc())
+    {
+        return Err(AppError::TooManyRequests(
+            "Too many failed login attempts. Please try again later.".into(),
+        ));
+    }
+
+    if !check_second_factor(&app_state, &user, Some(&request.code), None).await? {
+        let limits = &app_state.auth_config.login;
+        db::record_failed_login(
+            &app_state.pool,
+            user.id,
+            limits.max_failures,
+            limits.lockout,
+            limits.max_lockout,
+        )
+        .await?;
+        db::record_login_attempt(
+            &app_state.pool,
+            &user.username.to_lowercase(),
+            Some(user.id),
+            client_ip.map(|ip| ip.to_string()).as_deref(),
+            Some("invalid_mfa_code"),
+        )
+        .await?;
+        return Err(AppError::Unauthorized("Invalid verification code".into()));
+    }
+
+    db::disable_totp(&app_state.pool, user.id).await?;
+
+    tracing::info!(user_id = %user.id, "Two-factor authentication disabled");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Completes a login by exchanging an MFA pending token and a code for a full session.
+///
+/// Wrong codes count towards the same lockout as wrong passwords.
+pub async fn login_mfa(
+    State(app_state): State<AppState>,
+    ClientIp(client_ip): ClientIp,
+    ValidatedJson(request): ValidatedJson<MfaLoginRequest>,
+) -> Result<Json<AuthResponse>, AppError> {
+    let user_id = decode_mfa_pending_token(&app_state.jwt_keys, &request.mfa_token)?;
+    let user = db::find_user_by_id(&app_state.pool, user_id)
+        .await?
+        .ok_or_else(|| AppError::Unauthorized("Invalid MFA token".into()))?;
+
+    let identifier = user.username.to_lowercase();
+    let ip_address = client_ip.map(|ip| ip.to_string());
+
+    if user
+        .locked_until
+        .is_some_and(|locked_until| locked_until > OffsetDateTime::now_utc())
+    {
+        return Err(AppError::TooManyRequests(
+            "Too many failed login attempts. Please try again later.".into(),
+        ));
+    }
+
+    let verified = check_second_factor(
+        &app_state,
+        &user,
+        request.code.as_deref(),
+        request.recovery_code.as_deref(),
+    )
+    .await?;
+
+    if !verified {
+        let limits = &app_state.auth_config.login;
+        db::record_failed_login(
+            &app_state.pool,
+            user.id,
+            limits.max_failures,
+            limits.lockout,
+            limits.max_lockout,
+        )
+        .await?;
+        db::record_login_attempt(
+            &app_state.pool,
+            &identifier,
+            Some(user.id),
+            ip_address.as_deref(),
+            Some("invalid_mfa_code"),
+        )
+        .await?;
+        return Err(AppError::Unauthorized("Invalid verification code".into()));
+    }
+
+    db::reset_failed_logins(&app_state.pool, user.id).await?;
+
+    let session = issue_session(&app_state, user, None).await?;
+    Ok(Json(session))
+}
+
+/// Checks a TOTP code or, failing that, a recovery code. Either one is consumed on success.
+async fn check_second_factor(
+    app_state: &AppState,
+    user: &User,
+    code: Option<&str>,
+    recovery_code: Option<&str>,
+) -> Result<bool, AppError> {
+    let Some(secret) = user.totp_secret.as_deref() else {
+        return Ok(false);
+    };
+
+    if let Some(code) = code {
+        let totp = build_totp(secret, &app_state.auth_config.totp_issuer, &user.username)?;
+        if let Some(step) = verify_code(&totp, code, user.totp_last_used_step) {
+            return db::record_totp_step(&app_state.pool, user.id, step).await;
+        }
+    }
+
+    if let Some(recovery_code) = recovery_code {
+        return db::consume_recovery_code(
+            &app_state.pool,
+            user.id,
+            &hash_recovery_code(recovery_code),
+        )
+        .await;
+    }
+
+    Ok(false)
+}
+
+async fn find_user(app_state: &AppState, auth: &AuthUser) -> Result<User, AppError> {
+    db::find_user_by_id(&app_state.pool, auth.user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::jwt::create_mfa_pending_token;
+    use crate::db::fixtures::{app_state, seed_user};
+    use chrono::Duration;
+    use sqlx::PgPool;
+
+    fn auth_user(user_id: uuid::Uuid, role: UserRole) -> AuthUser {
+        AuthUser {
+            user_id,
+            username: "landlord".into(),
+            role,
+            scopes: None,
+        }
+    }
+
+    #[sqlx::test]
+    async fn codes_and_recovery_codes_each_complete_one_login(pool: PgPool) {
+        let app_state = app_state(pool);
+        let user_id = seed_user(&app_state.pool, "landlord", UserRole::Landlord).await;
+        let auth = auth_user(user_id, UserRole::Landlord);
+
+        let Json(enrollment) = enroll_totp(State(app_state.clone()), auth.clone())
+            .await
+            .unwrap();
+        let totp = build_totp(
+            &enrollment.secret,
+            &app_state.auth_config.totp_issuer,
+            "landlord",
+        )
+        .unwrap();
+        let code = totp.generate_current().unwrap();
+        let Json(recovery) = confirm_totp(
+            State(app_state.clone()),
+            auth,
+            ValidatedJson(TotpCodeRequest { code: code.clone() }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(recovery.recovery_codes.len(), 10);
+
+        let login = |code: Option<String>, recovery_code: Option<String>| {
+            let mfa_token =
+                create_mfa_pending_token(&app_state.jwt_keys, user_id, Duration::minutes(5))
+                    .unwrap();
+            login_mfa(
+                State(app_state.clone()),
+                ClientIp(None),
+                ValidatedJson(MfaLoginRequest {
+                    mfa_token,
+                    code,
+                    recovery_code,
+                }),
+            )
+        };
+
+        // The code used to confirm enrollment cannot be replayed
+        assert!(matches!(
+            login(Some(code), None).await,
+            Err(AppError::Unauthorized(_))
+        ));
+
+        let first = recovery.recovery_codes[0].clone();
+        let Json(session) = login(None, Some(first.clone())).await.unwrap();
+        assert_eq!(session.user_id, user_id);
+        assert!(matches!(
+            login(None, Some(first)).await,
+            Err(AppError::Unauthorized(_))
+        ));
+
+        // Recovery codes are accepted however they are typed
+        let second = recovery.recovery_codes[1].replace('-', "").to_uppercase();
+        let Json(session) = login(None, Some(second)).await.unwrap();
+        assert_eq!(session.user_id, user_id);
+    }
+
+    #[sqlx::test]
+    async fn tenants_cannot_enroll(pool: PgPool) {
+        let app_state = app_state(pool);
+        let user_id = seed_user(&app_state.pool, "tenant", UserRole::Tenant).await;
+
+        let result = enroll_totp(State(app_state), auth_user(user_id, UserRole::Tenant)).await;
+        assert!(matches!(result, Err(AppError::Forbidden(_))));
+    }
+}
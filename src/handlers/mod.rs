@@ -1,9 +1,27 @@
 // Declare the sub-modules
+pub mod account;
+pub mod api_key;
+pub mod late_fee;
+pub mod lease;
+pub mod ledger;
+pub mod mfa;
+pub mod oidc;
 pub mod payment;
 pub mod property;
+pub mod receipt;
+pub mod session;
 pub mod user;
 
 // Re-export all public items from sub-modules
+pub use account::*;
+pub use api_key::*;
+pub use late_fee::*;
+pub use lease::*;
+pub use ledger::*;
+pub use mfa::*;
+pub use oidc::*;
 pub use payment::*;
 pub use property::*;
+pub use receipt::*;
+pub use session::*;
 pub use user::*;
@@ -0,0 +1,246 @@
+// src/handlers/oidc.rs
+
+use axum::extract::{Json, Query, State};
+use axum::response::Redirect;
+use time::OffsetDateTime;
+
+use crate::AppState;
+use crate::auth::issue_session;
+use crate::auth::oidc::{IdTokenClaims, OidcClient};
+use crate::auth::password::hash_password;
+use crate::auth::tokens::{generate_opaque_token, hash_token};
+use crate::db::{self, ProvisionedUser};
+use crate::errors::AppError;
+use crate::models::{AuthResponse, OidcCallbackParams, User};
+
+/// Attempts at finding a free username for a provisioned user before giving up.
+const USERNAME_ATTEMPTS: usize = 5;
+
+/// Starts an OpenID Connect login by redirecting to the identity provider.
+pub async fn oidc_login(State(app_state): State<AppState>) -> Result<Redirect, AppError> {
+    let oidc = oidc_client(&app_state)?;
+
+    let state = generate_opaque_token();
+    let nonce = generate_opaque_token();
+    let code_verifier = generate_opaque_token();
+    let expires_at =
+        OffsetDateTime::now_utc() + time::Duration::seconds(oidc.config.login_ttl.num_seconds());
+    db::create_oidc_login_state(
+        &app_state.pool,
+        &hash_token(&state),
+        &nonce,
+        &code_verifier,
+        expires_at,
+    )
+    .await?;
+
+    let url = oidc
+        .authorization_url(&state, &nonce, &code_verifier)
+        .await?;
+    Ok(Redirect::to(&url))
+}
+
+/// Completes an OpenID Connect login when the provider redirects back, issuing our
+/// normal session tokens.
+///
+/// Users signing in for the first time are linked to an existing account with the
+/// same verified email, or provisioned with `OIDC_DEFAULT_ROLE`. The provider is
+/// trusted to enforce its own second factor, so local TOTP is not asked for.
+pub async fn oidc_callback(
+    State(app_state): State<AppState>,
+    Query(params): Query<OidcCallbackParams>,
+) -> Result<Json<AuthResponse>, AppError> {
+    let oidc = oidc_client(&app_state)?;
+
+    if let Some(error) = params.error {
+        tracing::info!(%error, description = ?params.error_description, "OIDC login not completed");
+        return Err(AppError::Unauthorized(format!(
+            "Login was not completed: {}",
+            error
+        )));
+    }
+
+    let (code, state) = params
+        .code
+        .zip(params.state)
+        .ok_or_else(|| AppError::BadRequest("Missing code or state".into()))?;
+
+    let login_state = db::consume_oidc_login_state(&app_state.pool, &hash_token(&state))
+        .await?
+        .ok_or_else(|| AppError::BadRequest("Invalid or expired login state".into()))?;
+
+    let claims = oidc
+        .exchange_code(&code, &login_state.code_verifier, &login_state.nonce)
+        .await?;
+    let user = find_or_provision_user(&app_state, oidc, &claims).await?;
+
+    tracing::info!(user_id = %user.id, issuer = %claims.iss, "OIDC login succeeded");
+    let session = issue_session(&app_state, user, None).await?;
+    Ok(Json(session))
+}
+
+fn oidc_client(app_state: &AppState) -> Result<&OidcClient, AppError> {
+    app_state
+        .oidc
+        .as_deref()
+        .ok_or_else(|| AppError::NotFound("OIDC login is not configured".into()))
+}
+
+async fn find_or_provision_user(
+    app_state: &AppState,
+    oidc: &OidcClient,
+    claims: &IdTokenClaims,
+) -> Result<User, AppError> {
+    let pool = &app_state.pool;
+    let email = claims.email.as_deref();
+
+    if let Some(user) = db::find_user_by_identity(pool, &claims.iss, &claims.sub, email).await? {
+        return Ok(user);
+    }
+
+    // Only link to an existing account when both sides have verified the address;
+    // otherwise anyone could claim an account by registering its email elsewhere.
+    let existing = match email.filter(|_| claims.email_verified) {
+        Some(email) => db::find_user_by_email(pool, email).await?,
+        None => None,
+    };
+    if let Some(user) = existing.filter(|user| user.email_verified_at.is_some()) {
+        db::link_identity(pool, user.id, &claims.iss, &claims.sub, email).await?;
+        tracing::info!(user_id = %user.id, issuer = %claims.iss, "Linked external identity");
+        return Ok(user);
+    }
+
+    // Provisioned users get an unguessable password; they can set one through a reset
+    let password_hash =
+        hash_password(&app_state.auth_config.password, generate_opaque_token()).await?;
+    let base_username = username_from_claims(claims);
+
+    for attempt in 0..USERNAME_ATTEMPTS {
+        let username = match attempt {
+            0 => base_username.clone(),
+            _ => format!("{}-{:04}", base_username, rand::random::<u16>() % 10_000),
+        };
+
+        let provisioned = db::create_user_with_identity(
+            pool,
+            ProvisionedUser {
+                username: &username,
+                password_hash: &password_hash,
+                role: oidc.config.default_role,
+                email,
+                email_verified: claims.email_verified,
+                full_name: claims.name.as_deref(),
+                issuer: &claims.iss,
+                subject: &claims.sub,
+            },
+        )
+        .await?;
+
+        if let Some(user) = provisioned {
+            tracing::info!(user_id = %user.id, issuer = %claims.iss, "Provisioned user from external identity");
+            return Ok(user);
+        }
+    }
+
+    Err(AppError::Conflict(
+        "Could not find a free username for this account".into(),
+    ))
+}
+
+/// Picks a username from the provider's preferred username or the email's local part.
+fn username_from_claims(claims: &IdTokenClaims) -> String {
+    let candidate = claims
+        .preferred_username
+        .as_deref()
+        .or(claims.email.as_deref())
+        .map(|name| name.split('@').next().unwrap_or(name))
+        .unwrap_or("user");
+
+    let username: String = candidate
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+        .take(32)
+        .collect();
+
+    if username.is_empty() {
+        "user".into()
+    } else {
+        username
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OidcConfig;
+    use crate::db::fixtures::{app_state, seed_user};
+    use crate::models::UserRole;
+    use chrono::Duration;
+    use sqlx::PgPool;
+
+    fn client() -> OidcClient {
+        OidcClient::new(OidcConfig {
+            issuer_url: "https://id.example.com".into(),
+            client_id: "rust_api".into(),
+            client_secret: None,
+            redirect_url: "http://localhost:3000/auth/oidc/callback".into(),
+            scopes: "openid email profile".into(),
+            default_role: UserRole::Tenant,
+            login_ttl: Duration::minutes(10),
+        })
+    }
+
+    fn claims(subject: &str, email: Option<&str>, email_verified: bool) -> IdTokenClaims {
+        IdTokenClaims {
+            iss: "https://id.example.com".into(),
+            sub: subject.into(),
+            nonce: None,
+            email: email.map(Into::into),
+            email_verified,
+            name: None,
+            preferred_username: None,
+        }
+    }
+
+    #[sqlx::test]
+    async fn identities_only_link_by_an_email_both_sides_have_verified(pool: PgPool) {
+        let app_state = app_state(pool);
+        let oidc = client();
+        let tenant_id = seed_user(&app_state.pool, "tenant", UserRole::Tenant).await;
+        db::mark_email_verified(&app_state.pool, tenant_id)
+            .await
+            .unwrap();
+        seed_user(&app_state.pool, "landlord", UserRole::Landlord).await;
+
+        // The provider has not verified the address, so the account is not handed over
+        let unverified = claims("sub-1", Some("tenant@example.com"), false);
+        let result = find_or_provision_user(&app_state, &oidc, &unverified).await;
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+
+        let verified = claims("sub-1", Some("tenant@example.com"), true);
+        let linked = find_or_provision_user(&app_state, &oidc, &verified)
+            .await
+            .unwrap();
+        assert_eq!(linked.id, tenant_id);
+
+        // Later logins find the link by subject alone
+        let returning = claims("sub-1", None, false);
+        let found = find_or_provision_user(&app_state, &oidc, &returning)
+            .await
+            .unwrap();
+        assert_eq!(found.id, tenant_id);
+
+        // Nor is an account whose own address was never verified
+        let landlord = claims("sub-2", Some("landlord@example.com"), true);
+        let result = find_or_provision_user(&app_state, &oidc, &landlord).await;
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+
+        let newcomer = claims("sub-3", Some("new.person@example.com"), true);
+        let provisioned = find_or_provision_user(&app_state, &oidc, &newcomer)
+            .await
+            .unwrap();
+        assert_eq!(provisioned.username, "new.person");
+        assert_eq!(provisioned.role, UserRole::Tenant);
+        assert!(provisioned.email_verified_at.is_some());
+    }
+}
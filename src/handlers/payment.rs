@@ -1,29 +1,57 @@
 // src/handlers/payment.rs
 
-use axum::extract::{Json, Query, State};
-use std::collections::HashMap;
+use axum::extract::{Json, Path, State};
 use uuid::Uuid;
 
 // Import your custom error and models
 use crate::errors::AppError;
-use crate::models::payment::{CreatePayment, Payment, PaymentResponse, PaymentStatus};
+use crate::extractors::{ValidQuery, ValidatedJson};
+use crate::models::payment::{
+    CreatePayment, ListPaymentsQuery, OverdueBalance, OverdueBalancesQuery, Payment,
+    PaymentResponse, PaymentStatus, UpdatePaymentStatus,
+};
+use crate::models::{ApiScope, Cursor, Page, UserRole, page_limit};
 
 // Import database operations
 use crate::AppState;
-use crate::db;
+use crate::auth::AuthUser;
+use crate::db::{self, PaymentStatusChange};
 
 /// Handles creation of a new payment.
 pub async fn create_payment(
     State(app_state): State<AppState>,
-    Json(new_payment): Json<CreatePayment>,
+    auth: AuthUser,
+    ValidatedJson(new_payment): ValidatedJson<CreatePayment>,
 ) -> Result<Json<PaymentResponse>, AppError> {
-    // For now, new payments start as Pending.
-    // In a real app, this might involve a payment gateway.
+    auth.require_scope(ApiScope::PaymentsWrite)?;
+
+    // Admins can record any payment. Landlords can record payments against properties
+    // they own, and tenants only their own payments for the property they rent.
+    if !auth.is_admin() {
+        let property = db::find_property_by_id(&app_state.pool, new_payment.property_id).await?;
+        let allowed = match (auth.role, property) {
+            (UserRole::Landlord, Some(property)) => property.owner_id == Some(auth.user_id),
+            (UserRole::Tenant, Some(property)) => {
+                new_payment.user_id == auth.user_id
+                    && property.current_tenant_ids.contains(&auth.user_id)
+            }
+            _ => false,
+        };
+
+        if !allowed {
+            return Err(AppError::Forbidden(
+                "You are not allowed to record payments for this property".into(),
+            ));
+        }
+    }
+
+    tracing::info!(user_id = %auth.user_id, username = %auth.username, "Recording payment");
+
+    // New payments start as Pending. Money received is recorded as a receipt with
+    // its own transaction id; a payment only gets one when a charge for it fails.
     let initial_status = PaymentStatus::Pending;
-    let transaction_id = None; // Placeholder for external transaction ID
 
-    let payment =
-        db::create_payment(&app_state.pool, new_payment, initial_status, transaction_id).await?;
+    let payment = db::create_payment(&app_state.pool, new_payment, initial_status, None).await?;
 
     // Convert the created Payment DB model to a PaymentResponse DTO
     Ok(Json(PaymentResponse {
@@ -31,6 +59,8 @@ pub async fn create_payment(
         user_id: payment.user_id,
         property_id: payment.property_id,
         amount: payment.amount,
+        amount_paid: payment.amount_paid,
+        balance_due: payment.balance_due,
         currency: payment.currency,
         status: payment.status,
         notes: payment.notes,
@@ -38,19 +68,190 @@ pub async fn create_payment(
         due_date: payment.due_date,
         period_start: payment.period_start,
         period_end: payment.period_end,
+        kind: payment.kind,
+        lease_id: payment.lease_id,
+        parent_payment_id: payment.parent_payment_id,
     }))
 }
 
-/// Handles listing payments with optional filters.
+/// Handles listing payments with optional filters, one page at a time.
+///
+/// Tenants only ever see their own payments and landlords only payments for
+/// properties they own; the query filters narrow that set further.
 pub async fn list_payments(
     State(app_state): State<AppState>,
-    Query(params): Query<HashMap<String, String>>,
-) -> Result<Json<Vec<Payment>>, AppError> {
-    let user_id_filter = params.get("user_id").and_then(|s| s.parse::<Uuid>().ok());
-    let property_id_filter = params
-        .get("property_id")
-        .and_then(|s| s.parse::<Uuid>().ok());
-
-    let payments = db::list_payments(&app_state.pool, user_id_filter, property_id_filter).await?;
-    Ok(Json(payments))
+    auth: AuthUser,
+    ValidQuery(mut query): ValidQuery<ListPaymentsQuery>,
+) -> Result<Json<Page<Payment>>, AppError> {
+    auth.require_scope(ApiScope::PaymentsRead)?;
+
+    let mut owner_id_filter = None;
+    match auth.role {
+        UserRole::Admin => {}
+        UserRole::Landlord => owner_id_filter = Some(auth.user_id),
+        UserRole::Tenant => {
+            if query.user_id.is_some_and(|user_id| user_id != auth.user_id) {
+                return Err(AppError::Forbidden(
+                    "Tenants can only view their own payments".into(),
+                ));
+            }
+            query.user_id = Some(auth.user_id);
+        }
+    }
+
+    let (sort, order) = query.sort_order();
+    let (_, cast) = sort.sql();
+    let after = query
+        .cursor
+        .as_deref()
+        .map(|cursor| Cursor::decode(cursor, sort.as_str(), cast, order))
+        .transpose()?;
+    let limit = page_limit(query.limit);
+
+    let payments = db::list_payments(
+        &app_state.pool,
+        &query,
+        owner_id_filter,
+        after.as_ref(),
+        limit + 1,
+    )
+    .await?;
+
+    Ok(Json(Page::from_rows(payments, limit, |payment| Cursor {
+        sort: sort.as_str().into(),
+        order,
+        value: sort.cursor_value(payment),
+        id: payment.id,
+    })))
+}
+
+/// Summarizes what each tenant owes per property across their overdue payments.
+///
+/// Tenants only see their own balances and landlords balances for properties they own.
+pub async fn list_overdue_balances(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    ValidQuery(mut query): ValidQuery<OverdueBalancesQuery>,
+) -> Result<Json<Vec<OverdueBalance>>, AppError> {
+    auth.require_scope(ApiScope::PaymentsRead)?;
+
+    let mut owner_id_filter = None;
+    match auth.role {
+        UserRole::Admin => {}
+        UserRole::Landlord => owner_id_filter = Some(auth.user_id),
+        UserRole::Tenant => {
+            if query.user_id.is_some_and(|user_id| user_id != auth.user_id) {
+                return Err(AppError::Forbidden(
+                    "Tenants can only view their own payments".into(),
+                ));
+            }
+            query.user_id = Some(auth.user_id);
+        }
+    }
+
+    let balances = db::list_overdue_balances(&app_state.pool, &query, owner_id_filter).await?;
+    Ok(Json(balances))
+}
+
+/// Returns a single payment visible to the caller.
+///
+/// Tenants can view their own payments and landlords payments for properties they own.
+pub async fn get_payment(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Payment>, AppError> {
+    auth.require_scope(ApiScope::PaymentsRead)?;
+
+    let payment = find_payment(&app_state, id).await?;
+    let visible = match auth.role {
+        UserRole::Admin => true,
+        UserRole::Landlord => owns_property(&app_state, &auth, &payment).await?,
+        UserRole::Tenant => payment.user_id == auth.user_id,
+    };
+
+    if !visible {
+        return Err(AppError::Forbidden(
+            "You are not allowed to view this payment".into(),
+        ));
+    }
+
+    Ok(Json(payment))
+}
+
+/// Moves a payment to a new status, e.g. marking it failed or sending it back to
+/// pending. Payments are paid by recording receipts, not through this endpoint.
+///
+/// Only admins and the landlord of the property can do this. Transitions not allowed
+/// by `PaymentStatus::can_transition_to` fail with `409 Conflict`.
+pub async fn update_payment_status(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    ValidatedJson(request): ValidatedJson<UpdatePaymentStatus>,
+) -> Result<Json<Payment>, AppError> {
+    auth.require_scope(ApiScope::PaymentsWrite)?;
+
+    let payment = find_payment(&app_state, id).await?;
+    if !auth.is_admin() && !owns_property(&app_state, &auth, &payment).await? {
+        return Err(AppError::Forbidden(
+            "Only the property's landlord can update this payment".into(),
+        ));
+    }
+
+    if !payment.status.can_transition_to(&request.status) {
+        return Err(AppError::Conflict(format!(
+            "Cannot change payment status from {} to {}",
+            payment.status.as_str(),
+            request.status.as_str()
+        )));
+    }
+
+    tracing::info!(
+        user_id = %auth.user_id,
+        payment_id = %id,
+        from = payment.status.as_str(),
+        to = request.status.as_str(),
+        "Updating payment status"
+    );
+    let updated = db::update_payment_status(
+        &app_state.pool,
+        id,
+        PaymentStatusChange {
+            from: payment.status,
+            to: request.status,
+            changed_by: auth.user_id,
+            reason: request
+                .reason
+                .as_deref()
+                .unwrap_or("Changed through the API"),
+            transaction_id: request.transaction_id.as_deref(),
+        },
+    )
+    .await?
+    .ok_or_else(|| {
+        AppError::Conflict("Payment was changed by another request, please retry".into())
+    })?;
+
+    Ok(Json(updated))
+}
+
+async fn find_payment(app_state: &AppState, id: Uuid) -> Result<Payment, AppError> {
+    db::find_payment_by_id(&app_state.pool, id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Payment not found".into()))
+}
+
+/// Whether the caller owns the property a payment was made for.
+async fn owns_property(
+    app_state: &AppState,
+    auth: &AuthUser,
+    payment: &Payment,
+) -> Result<bool, AppError> {
+    let Some(property_id) = payment.property_id else {
+        return Ok(false);
+    };
+
+    let property = db::find_property_by_id(&app_state.pool, property_id).await?;
+    Ok(property.is_some_and(|property| property.owner_id == Some(auth.user_id)))
 }
@@ -1,9 +1,14 @@
 use crate::AppState;
-use axum::extract::{Json, State};
+use crate::auth::AuthUser;
+use axum::extract::{Json, Path, State};
+use http::StatusCode;
+use uuid::Uuid;
 
 // Import your custom error and models
 use crate::errors::AppError;
-use crate::models::property::{CreateProperty, Property};
+use crate::extractors::{ValidQuery, ValidatedJson};
+use crate::models::property::{CreateProperty, ListPropertiesQuery, Property, UpdateProperty};
+use crate::models::{ApiScope, Cursor, Page, UserRole, page_limit};
 
 // Import database operations
 use crate::db;
@@ -11,16 +16,144 @@ use crate::db;
 /// Handles creation of a new property.
 pub async fn create_property(
     State(app_state): State<AppState>,
-    Json(new_property): Json<CreateProperty>,
+    auth: AuthUser,
+    ValidatedJson(new_property): ValidatedJson<CreateProperty>,
 ) -> Result<Json<Property>, AppError> {
-    let property = db::create_property(&app_state.pool, new_property).await?;
+    auth.require_scope(ApiScope::PropertiesWrite)?;
+    auth.require_role(&[UserRole::Admin, UserRole::Landlord])?;
+
+    // Only admins may create a property on behalf of another landlord
+    let owner_id = match new_property.owner_id {
+        Some(owner_id) if owner_id != auth.user_id && !auth.is_admin() => {
+            return Err(AppError::Forbidden(
+                "Only admins can create properties for other landlords".into(),
+            ));
+        }
+        Some(owner_id) => owner_id,
+        None => auth.user_id,
+    };
+
+    tracing::info!(user_id = %auth.user_id, username = %auth.username, "Creating property");
+    let property = db::create_property(&app_state.pool, new_property, owner_id).await?;
     Ok(Json(property))
 }
 
-/// Handles listing all properties.
+/// Handles listing the properties visible to the caller, one page at a time.
+///
+/// Admins see every property, landlords the ones they own and tenants the ones they rent.
 pub async fn list_properties(
     State(app_state): State<AppState>,
-) -> Result<Json<Vec<Property>>, AppError> {
-    let properties = db::list_properties(&app_state.pool).await?;
-    Ok(Json(properties))
+    auth: AuthUser,
+    ValidQuery(query): ValidQuery<ListPropertiesQuery>,
+) -> Result<Json<Page<Property>>, AppError> {
+    auth.require_scope(ApiScope::PropertiesRead)?;
+
+    let (owner_id, tenant_id) = match auth.role {
+        UserRole::Admin => (None, None),
+        UserRole::Landlord => (Some(auth.user_id), None),
+        UserRole::Tenant => (None, Some(auth.user_id)),
+    };
+
+    let (sort, order) = query.sort_order();
+    let (_, cast) = sort.sql();
+    let after = query
+        .cursor
+        .as_deref()
+        .map(|cursor| Cursor::decode(cursor, sort.as_str(), cast, order))
+        .transpose()?;
+    let limit = page_limit(query.limit);
+
+    let properties = db::list_properties(
+        &app_state.pool,
+        &query,
+        owner_id,
+        tenant_id,
+        after.as_ref(),
+        limit + 1,
+    )
+    .await?;
+
+    Ok(Json(Page::from_rows(properties, limit, |property| {
+        Cursor {
+            sort: sort.as_str().into(),
+            order,
+            value: sort.cursor_value(property),
+            id: property.id,
+        }
+    })))
+}
+
+/// Returns a single property visible to the caller.
+pub async fn get_property(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Property>, AppError> {
+    auth.require_scope(ApiScope::PropertiesRead)?;
+
+    let property = find_property(&app_state, id).await?;
+    let visible = match auth.role {
+        UserRole::Admin => true,
+        UserRole::Landlord => property.owner_id == Some(auth.user_id),
+        UserRole::Tenant => property.current_tenant_ids.contains(&auth.user_id),
+    };
+
+    if !visible {
+        return Err(AppError::Forbidden(
+            "You are not allowed to view this property".into(),
+        ));
+    }
+
+    Ok(Json(property))
+}
+
+/// Updates the given fields of a property. Only its owner or an admin may change it.
+pub async fn update_property(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    ValidatedJson(changes): ValidatedJson<UpdateProperty>,
+) -> Result<Json<Property>, AppError> {
+    auth.require_scope(ApiScope::PropertiesWrite)?;
+    require_owner(&auth, &find_property(&app_state, id).await?)?;
+
+    tracing::info!(user_id = %auth.user_id, property_id = %id, "Updating property");
+    let property = db::update_property(&app_state.pool, id, changes)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Property not found".into()))?;
+    Ok(Json(property))
+}
+
+/// Deletes a property that has no payments or leases. Only its owner or an admin may
+/// delete it.
+pub async fn delete_property(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    auth.require_scope(ApiScope::PropertiesWrite)?;
+    require_owner(&auth, &find_property(&app_state, id).await?)?;
+
+    if !db::delete_property(&app_state.pool, id).await? {
+        return Err(AppError::NotFound("Property not found".into()));
+    }
+
+    tracing::info!(user_id = %auth.user_id, property_id = %id, "Deleted property");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn find_property(app_state: &AppState, id: Uuid) -> Result<Property, AppError> {
+    db::find_property_by_id(&app_state.pool, id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Property not found".into()))
+}
+
+fn require_owner(auth: &AuthUser, property: &Property) -> Result<(), AppError> {
+    if auth.is_admin() || property.owner_id == Some(auth.user_id) {
+        Ok(())
+    } else {
+        Err(AppError::Forbidden(
+            "Only the property's owner can change it".into(),
+        ))
+    }
 }
@@ -0,0 +1,122 @@
+// src/handlers/receipt.rs
+
+use axum::extract::{Json, Path, State};
+use time::format_description::well_known::Rfc3339;
+use uuid::Uuid;
+
+use crate::AppState;
+use crate::auth::AuthUser;
+use crate::db;
+use crate::errors::AppError;
+use crate::extractors::{ValidQuery, ValidatedJson};
+use crate::models::{
+    ApiScope, CreateReceipt, Cursor, ListReceiptsQuery, Page, Receipt, ReceiptDetails, SortOrder,
+    UserRole, page_limit,
+};
+
+/// Records money received from a tenant and allocates it against their charges,
+/// which moves those charges to partially paid or completed.
+///
+/// Only admins and the landlord of the property can record receipts.
+pub async fn create_receipt(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    ValidatedJson(new_receipt): ValidatedJson<CreateReceipt>,
+) -> Result<Json<ReceiptDetails>, AppError> {
+    auth.require_scope(ApiScope::PaymentsWrite)?;
+    auth.require_role(&[UserRole::Admin, UserRole::Landlord])?;
+
+    let property = db::find_property_by_id(&app_state.pool, new_receipt.property_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Property not found".into()))?;
+    if !auth.is_admin() && property.owner_id != Some(auth.user_id) {
+        return Err(AppError::Forbidden(
+            "Only the property's landlord can record receipts for it".into(),
+        ));
+    }
+
+    tracing::info!(
+        user_id = %auth.user_id,
+        tenant_id = %new_receipt.user_id,
+        property_id = %new_receipt.property_id,
+        amount = %new_receipt.amount,
+        "Recording receipt"
+    );
+    let receipt = db::create_receipt(&app_state.pool, &new_receipt, auth.user_id).await?;
+    Ok(Json(receipt))
+}
+
+/// Handles listing receipts, newest first, one page at a time.
+///
+/// Tenants only ever see their own receipts and landlords receipts for properties
+/// they own.
+pub async fn list_receipts(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    ValidQuery(mut query): ValidQuery<ListReceiptsQuery>,
+) -> Result<Json<Page<Receipt>>, AppError> {
+    auth.require_scope(ApiScope::PaymentsRead)?;
+
+    let mut owner_id = None;
+    match auth.role {
+        UserRole::Admin => {}
+        UserRole::Landlord => owner_id = Some(auth.user_id),
+        UserRole::Tenant => {
+            if query.user_id.is_some_and(|user_id| user_id != auth.user_id) {
+                return Err(AppError::Forbidden(
+                    "Tenants can only view their own receipts".into(),
+                ));
+            }
+            query.user_id = Some(auth.user_id);
+        }
+    }
+
+    let order = SortOrder::Desc;
+    let after = query
+        .cursor
+        .as_deref()
+        .map(|cursor| Cursor::decode(cursor, "created_at", "timestamptz", order))
+        .transpose()?;
+    let limit = page_limit(query.limit);
+
+    let receipts =
+        db::list_receipts(&app_state.pool, &query, owner_id, after.as_ref(), limit + 1).await?;
+
+    Ok(Json(Page::from_rows(receipts, limit, |receipt| Cursor {
+        sort: "created_at".into(),
+        order,
+        value: receipt.created_at.format(&Rfc3339).unwrap_or_default(),
+        id: receipt.id,
+    })))
+}
+
+/// Returns a single receipt visible to the caller, with the charges it paid.
+pub async fn get_receipt(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ReceiptDetails>, AppError> {
+    auth.require_scope(ApiScope::PaymentsRead)?;
+
+    let details = db::find_receipt_by_id(&app_state.pool, id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Receipt not found".into()))?;
+
+    let visible = match auth.role {
+        UserRole::Admin => true,
+        UserRole::Landlord => match details.receipt.property_id {
+            Some(property_id) => db::find_property_by_id(&app_state.pool, property_id)
+                .await?
+                .is_some_and(|property| property.owner_id == Some(auth.user_id)),
+            None => false,
+        },
+        UserRole::Tenant => details.receipt.user_id == auth.user_id,
+    };
+    if !visible {
+        return Err(AppError::Forbidden(
+            "You are not allowed to view this receipt".into(),
+        ));
+    }
+
+    Ok(Json(details))
+}
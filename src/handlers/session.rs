@@ -0,0 +1,161 @@
+// src/handlers/session.rs
+
+use axum::extract::{Json, State};
+use http::StatusCode;
+use serde_json::Value;
+use time::OffsetDateTime;
+
+use crate::AppState;
+use crate::auth::tokens::hash_token;
+use crate::auth::{AuthUser, issue_session};
+use crate::db;
+use crate::errors::AppError;
+use crate::extractors::ValidatedJson;
+use crate::models::{AuthResponse, RefreshTokenRequest};
+
+/// Exchanges a refresh token for a new access token and a rotated refresh token.
+///
+/// Each refresh token can be used once. Presenting one that was already rotated
+/// means it was copied, so the whole family is revoked and the session ends.
+pub async fn refresh_token(
+    State(app_state): State<AppState>,
+    ValidatedJson(request): ValidatedJson<RefreshTokenRequest>,
+) -> Result<Json<AuthResponse>, AppError> {
+    let stored =
+        db::find_refresh_token_by_hash(&app_state.pool, &hash_token(&request.refresh_token))
+            .await?
+            .ok_or_else(|| AppError::Unauthorized("Invalid refresh token".into()))?;
+
+    if stored.revoked_at.is_some() {
+        return Err(AppError::Unauthorized(
+            "Refresh token has been revoked".into(),
+        ));
+    }
+
+    if stored.expires_at <= OffsetDateTime::now_utc() {
+        return Err(AppError::Unauthorized("Refresh token has expired".into()));
+    }
+
+    // `mark_refresh_token_used` fails if the token was already rotated, including by a
+    // concurrent request, so this is the single place reuse is detected.
+    if stored.used_at.is_some() || !db::mark_refresh_token_used(&app_state.pool, stored.id).await? {
+        tracing::warn!(
+            user_id = %stored.user_id,
+            family_id = %stored.family_id,
+            "Refresh token reuse detected, revoking token family"
+        );
+        db::revoke_refresh_token_family(&app_state.pool, stored.family_id).await?;
+        return Err(AppError::Unauthorized(
+            "Refresh token has been revoked".into(),
+        ));
+    }
+
+    let user = db::find_user_by_id(&app_state.pool, stored.user_id)
+        .await?
+        .ok_or_else(|| AppError::Unauthorized("Invalid refresh token".into()))?;
+
+    let session = issue_session(&app_state, user, Some(stored.family_id)).await?;
+    Ok(Json(session))
+}
+
+/// Logs out the session the refresh token belongs to by revoking its family.
+pub async fn logout(
+    State(app_state): State<AppState>,
+    ValidatedJson(request): ValidatedJson<RefreshTokenRequest>,
+) -> Result<StatusCode, AppError> {
+    // Unknown tokens are ignored so logout is idempotent
+    if let Some(stored) =
+        db::find_refresh_token_by_hash(&app_state.pool, &hash_token(&request.refresh_token)).await?
+    {
+        db::revoke_refresh_token_family(&app_state.pool, stored.family_id).await?;
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Logs the caller out of every session by revoking all of their refresh tokens.
+pub async fn logout_all(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+) -> Result<StatusCode, AppError> {
+    auth.require_session()?;
+    db::revoke_user_refresh_tokens(&app_state.pool, auth.user_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Publishes the public keys access tokens can be verified with, as a JSON Web Key Set.
+pub async fn jwks(State(app_state): State<AppState>) -> Json<Value> {
+    Json(app_state.jwt_keys.jwks().clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::fixtures::{app_state, seed_user};
+    use crate::models::UserRole;
+    use sqlx::PgPool;
+
+    async fn refresh(app_state: &AppState, token: &str) -> Result<AuthResponse, AppError> {
+        let request = RefreshTokenRequest {
+            refresh_token: token.into(),
+        };
+        refresh_token(State(app_state.clone()), ValidatedJson(request))
+            .await
+            .map(|Json(session)| session)
+    }
+
+    #[sqlx::test]
+    async fn reusing_a_rotated_refresh_token_ends_the_whole_session(pool: PgPool) {
+        let app_state = app_state(pool);
+        let user_id = seed_user(&app_state.pool, "tenant", UserRole::Tenant).await;
+        let user = db::find_user_by_id(&app_state.pool, user_id)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let first = issue_session(&app_state, user, None).await.unwrap();
+        let second = refresh(&app_state, &first.refresh_token).await.unwrap();
+        assert_ne!(second.refresh_token, first.refresh_token);
+
+        // The copied first token is refused, and takes the rotated one down with it
+        let reused = refresh(&app_state, &first.refresh_token).await;
+        assert!(matches!(reused, Err(AppError::Unauthorized(_))));
+        let rotated = refresh(&app_state, &second.refresh_token).await;
+        assert!(matches!(rotated, Err(AppError::Unauthorized(_))));
+    }
+
+    #[sqlx::test]
+    async fn logging_out_everywhere_revokes_every_session(pool: PgPool) {
+        let app_state = app_state(pool);
+        let user_id = seed_user(&app_state.pool, "tenant", UserRole::Tenant).await;
+        let user = db::find_user_by_id(&app_state.pool, user_id)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let laptop = issue_session(&app_state, user.clone(), None).await.unwrap();
+        let phone = issue_session(&app_state, user, None).await.unwrap();
+
+        // Logging out one session leaves the other alone
+        let request = RefreshTokenRequest {
+            refresh_token: laptop.refresh_token.clone(),
+        };
+        logout(State(app_state.clone()), ValidatedJson(request))
+            .await
+            .unwrap();
+        assert!(refresh(&app_state, &laptop.refresh_token).await.is_err());
+        let phone = refresh(&app_state, &phone.refresh_token).await.unwrap();
+
+        let auth = AuthUser {
+            user_id,
+            username: "tenant".into(),
+            role: UserRole::Tenant,
+            scopes: None,
+        };
+        logout_all(State(app_state.clone()), auth).await.unwrap();
+        assert!(matches!(
+            refresh(&app_state, &phone.refresh_token).await,
+            Err(AppError::Unauthorized(_))
+        ));
+    }
+}
@@ -1,87 +1,336 @@
-use axum::extract::{Json, State};
+use axum::extract::{Json, Path, State};
+use http::StatusCode;
+use time::OffsetDateTime;
 use uuid::Uuid;
 
-// For password hashing
-use bcrypt::{hash, verify};
-
-// For JWT
-use chrono::{Duration, Utc};
-use jsonwebtoken::{EncodingKey, Header, encode};
-use serde::{Deserialize, Serialize};
-
+use crate::auth::emails::send_verification_email;
+use crate::auth::jwt::create_mfa_pending_token;
+use crate::auth::password::{dummy_verify_password, hash_password, verify_password};
+use crate::auth::{AuthUser, ClientIp, issue_session};
 use crate::errors::AppError;
-use crate::models::user::{AuthResponse, LoginUser, RegisterUser, User};
+use crate::extractors::ValidatedJson;
+use crate::models::api_key::ApiScope;
+use crate::models::mfa::{LoginResponse, MfaChallengeResponse};
+use crate::models::user::{LoginUser, RegisterUser, UpdateUserRole, UserResponse, UserRole};
 
 use crate::AppState;
 use crate::db;
 
-#[derive(Debug, Serialize, Deserialize)]
-struct Claims {
-    sub: Uuid, // Subject (user ID)
-    username: String,
-    exp: usize, // Expiration time
-}
-
 /// Handles user registration.
 pub async fn register_user(
     State(app_state): State<AppState>,
-    Json(new_user): Json<RegisterUser>,
-) -> Result<Json<User>, AppError> {
+    ValidatedJson(new_user): ValidatedJson<RegisterUser>,
+) -> Result<Json<UserResponse>, AppError> {
+    // Landlord and admin accounts are granted by an admin at `/users/{id}/role`
+    if new_user.role.is_some_and(|role| role != UserRole::Tenant) {
+        return Err(AppError::Forbidden(
+            "Only tenant accounts can be self-registered".into(),
+        ));
+    }
+
     // Hash the password
-    let hashed_password = hash(&new_user.password, 10)
-        .map_err(|e| AppError::InternalServerError(format!("Failed to hash password: {}", e)))?;
+    let hashed_password =
+        hash_password(&app_state.auth_config.password, new_user.password.clone()).await?;
 
     // Create user in DB
     let user = db::create_user(&app_state.pool, new_user, hashed_password).await?;
 
-    Ok(Json(user))
+    // The account is usable right away, so a mail failure should not fail registration;
+    // the user can ask for a new link from `/email/verification`.
+    if let Err(e) = send_verification_email(&app_state, &user).await {
+        tracing::warn!(user_id = %user.id, "Failed to send verification email: {:?}", e);
+    }
+
+    Ok(Json(user.into()))
 }
 
 /// Handles user login and JWT generation.
+///
+/// Accounts with two-factor authentication get an MFA challenge instead of tokens,
+/// to be completed at `/login/mfa`.
+///
+/// Failed attempts are throttled per client IP and per account. Unknown usernames
+/// are throttled and timed just like real ones, so neither the status code nor the
+/// response time reveals whether an account exists.
 pub async fn login_user(
     State(app_state): State<AppState>,
-    Json(login_data): Json<LoginUser>,
-) -> Result<Json<AuthResponse>, AppError> {
-    // Find user by username
-    let user = db::find_user_by_username(&app_state.pool, &login_data.username)
-        .await?
-        .ok_or_else(|| AppError::Unauthorized("Invalid username or password".into()))?;
+    ClientIp(client_ip): ClientIp,
+    ValidatedJson(login_data): ValidatedJson<LoginUser>,
+) -> Result<Json<LoginResponse>, AppError> {
+    let limits = &app_state.auth_config.login;
+    let password_config = &app_state.auth_config.password;
+    let identifier = login_data.username.trim().to_lowercase();
+    let ip_address = client_ip.map(|ip| ip.to_string());
+    let now = OffsetDateTime::now_utc();
+
+    // Throttle clients spraying many accounts from one address
+    if let Some(ip) = &ip_address {
+        let since = now - time::Duration::seconds(limits.ip_window.num_seconds());
+        if db::count_failed_logins_for_ip(&app_state.pool, ip, since).await?
+            >= limits.ip_max_failures
+        {
+            dummy_verify_password(password_config, login_data.password).await;
+            db::record_login_attempt(
+                &app_state.pool,
+                &identifier,
+                None,
+                ip_address.as_deref(),
+                Some("ip_throttled"),
+            )
+            .await?;
+            return Err(too_many_attempts());
+        }
+    }
+
+    // Find user by username or email
+    let Some(user) = db::find_user_by_login(&app_state.pool, login_data.username.trim()).await?
+    else {
+        // Mirror the per-account lockout for identifiers that do not exist, so the
+        // responses cannot tell the two apart
+        let locked_until = db::unknown_identifier_locked_until(
+            &app_state.pool,
+            &identifier,
+            limits.max_failures,
+            limits.lockout,
+            limits.max_lockout,
+        )
+        .await?;
+        let locked = locked_until.is_some_and(|locked_until| locked_until > now);
+        dummy_verify_password(password_config, login_data.password).await;
+        db::record_login_attempt(
+            &app_state.pool,
+            &identifier,
+            None,
+            ip_address.as_deref(),
+            Some(if locked { "locked" } else { "unknown_user" }),
+        )
+        .await?;
+
+        return Err(if locked {
+            too_many_attempts()
+        } else {
+            invalid_credentials()
+        });
+    };
+
+    // Locked accounts are refused even with the right password
+    if user
+        .locked_until
+        .is_some_and(|locked_until| locked_until > now)
+    {
+        dummy_verify_password(password_config, login_data.password).await;
+        db::record_login_attempt(
+            &app_state.pool,
+            &identifier,
+            Some(user.id),
+            ip_address.as_deref(),
+            Some("locked"),
+        )
+        .await?;
+        return Err(too_many_attempts());
+    }
 
     // Verify password
-    let passwords_match = verify(&login_data.password, &user.password_hash).map_err(|e| {
-        AppError::InternalServerError(format!("Password verification error: {}", e))
-    })?;
+    let check = verify_password(
+        password_config,
+        login_data.password.clone(),
+        user.password_hash.clone(),
+    )
+    .await?;
+
+    if !check.matches {
+        db::record_failed_login(
+            &app_state.pool,
+            user.id,
+            limits.max_failures,
+            limits.lockout,
+            limits.max_lockout,
+        )
+        .await?;
+        db::record_login_attempt(
+            &app_state.pool,
+            &identifier,
+            Some(user.id),
+            ip_address.as_deref(),
+            Some("invalid_password"),
+        )
+        .await?;
+        return Err(invalid_credentials());
+    }
+
+    db::record_login_attempt(
+        &app_state.pool,
+        &identifier,
+        Some(user.id),
+        ip_address.as_deref(),
+        None,
+    )
+    .await?;
 
-    if !passwords_match {
-        return Err(AppError::Unauthorized(
-            "Invalid username or password".into(),
+    // Upgrade legacy bcrypt or outdated Argon2 hashes now that we have the plaintext.
+    // A failure here should not block the login, the next one will try again.
+    if check.needs_rehash {
+        match hash_password(password_config, login_data.password).await {
+            Ok(new_hash) => {
+                if let Err(e) = db::update_password_hash(&app_state.pool, user.id, &new_hash).await
+                {
+                    tracing::warn!(user_id = %user.id, "Failed to upgrade password hash: {:?}", e);
+                }
+            }
+            Err(e) => tracing::warn!(user_id = %user.id, "Failed to rehash password: {:?}", e),
+        }
+    }
+
+    // The password is right, but a second factor is still required. Failures are only
+    // cleared once it is given, or logging in again would reset the count of wrong codes.
+    if user.totp_enabled_at.is_some() {
+        let ttl = app_state.auth_config.mfa_token_ttl;
+        let mfa_token = create_mfa_pending_token(&app_state.jwt_keys, user.id, ttl)?;
+
+        return Ok(Json(LoginResponse::MfaRequired(MfaChallengeResponse {
+            mfa_required: true,
+            mfa_token,
+            expires_in: ttl.num_seconds(),
+        })));
+    }
+
+    db::reset_failed_logins(&app_state.pool, user.id).await?;
+
+    // Issue a short-lived access token and a refresh token
+    let session = issue_session(&app_state, user, None).await?;
+
+    Ok(Json(LoginResponse::Authenticated(session)))
+}
+
+/// Clears the failed login counter and any lockout on an account. Admin only.
+pub async fn unlock_user(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(user_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    auth.require_session()?;
+    auth.require_role(&[UserRole::Admin])?;
+
+    if !db::reset_failed_logins(&app_state.pool, user_id).await? {
+        return Err(AppError::NotFound("User not found".into()));
+    }
+
+    tracing::info!(admin_id = %auth.user_id, %user_id, "Account unlocked");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Changes a user's role, e.g. making a newly registered user a landlord. Admin only.
+///
+/// Sessions pick up the new role when their access token is next refreshed.
+pub async fn update_user_role(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(user_id): Path<Uuid>,
+    ValidatedJson(request): ValidatedJson<UpdateUserRole>,
+) -> Result<Json<UserResponse>, AppError> {
+    auth.require_session()?;
+    auth.require_role(&[UserRole::Admin])?;
+
+    let user = db::update_user_role(&app_state.pool, user_id, request.role)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".into()))?;
+
+    tracing::info!(admin_id = %auth.user_id, %user_id, role = ?user.role, "Role changed");
+    Ok(Json(user.into()))
+}
+
+fn invalid_credentials() -> AppError {
+    AppError::Unauthorized("Invalid username or password".into())
+}
+
+fn too_many_attempts() -> AppError {
+    AppError::TooManyRequests("Too many failed login attempts. Please try again later.".into())
+}
+
+/// Returns the profile of the authenticated user.
+pub async fn get_current_user(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<UserResponse>, AppError> {
+    auth.require_scope(ApiScope::UsersRead)?;
+
+    let user = db::find_user_by_id(&app_state.pool, auth.user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".into()))?;
+
+    Ok(Json(user.into()))
+}
+
+/// Returns a user's profile. Users can view their own profile; admins can view anyone's.
+pub async fn get_user(
+    State(app_state): State<AppState>,
+    auth: AuthUser,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<UserResponse>, AppError> {
+    auth.require_scope(ApiScope::UsersRead)?;
+
+    if user_id != auth.user_id && !auth.is_admin() {
+        return Err(AppError::Forbidden(
+            "You can only view your own profile".into(),
         ));
     }
 
-    // Generate JWT token
-    let expiration_time = Utc::now()
-        .checked_add_signed(Duration::hours(24)) // Token valid for 24 hours
-        .ok_or_else(|| {
-            AppError::InternalServerError("Failed to calculate token expiration".into())
-        })?
-        .timestamp() as usize;
-
-    let claims = Claims {
-        sub: user.id,
-        username: user.username.clone(),
-        exp: expiration_time,
-    };
+    let user = db::find_user_by_id(&app_state.pool, user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".into()))?;
 
-    let token = encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(&app_state.jwt_secret.0.as_bytes()),
-    )
-    .map_err(|e| AppError::InternalServerError(format!("Failed to generate JWT: {}", e)))?;
+    Ok(Json(user.into()))
+}
 
-    Ok(Json(AuthResponse {
-        user_id: user.id,
-        username: user.username,
-        token,
-    }))
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::fixtures::{app_state, seed_user};
+    use sqlx::PgPool;
+
+    async fn login(app_state: &AppState, password: &str) -> Result<LoginResponse, AppError> {
+        let login_data = LoginUser {
+            username: "tenant".into(),
+            password: password.into(),
+        };
+        login_user(
+            State(app_state.clone()),
+            ClientIp(None),
+            ValidatedJson(login_data),
+        )
+        .await
+        .map(|Json(response)| response)
+    }
+
+    #[sqlx::test]
+    async fn logging_in_upgrades_a_bcrypt_hash_to_argon2id(pool: PgPool) {
+        let app_state = app_state(pool);
+        let user_id = seed_user(&app_state.pool, "tenant", UserRole::Tenant).await;
+        let bcrypt_hash = bcrypt::hash("correct horse", 4).unwrap();
+        db::update_password_hash(&app_state.pool, user_id, &bcrypt_hash)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            login(&app_state, "wrong horse").await,
+            Err(AppError::Unauthorized(_))
+        ));
+        let stored = db::find_user_by_id(&app_state.pool, user_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.password_hash, bcrypt_hash);
+
+        let response = login(&app_state, "correct horse").await.unwrap();
+        assert!(matches!(response, LoginResponse::Authenticated(_)));
+        let stored = db::find_user_by_id(&app_state.pool, user_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(stored.password_hash.starts_with("$argon2id$"));
+
+        // The upgraded hash still takes the same password
+        let response = login(&app_state, "correct horse").await.unwrap();
+        assert!(matches!(response, LoginResponse::Authenticated(_)));
+    }
 }
@@ -0,0 +1,138 @@
+// src/jobs/late_fee.rs
+
+use std::collections::HashMap;
+
+use sqlx::PgPool;
+use time::Date;
+
+use crate::db;
+use crate::errors::AppError;
+
+/// Advisory lock held while charging late fees, so replicas take turns.
+const LATE_FEE_JOB_LOCK: i64 = 0x6c61_7465; // "late"
+
+/// Charges late fees on overdue charges other than late fees under each property's
+/// late fee policy, due
+/// `today`. Returns how many fees were created or raised, or `None` if another
+/// server is charging late fees right now.
+///
+/// A late payment gets one fee. Flat and percentage fees are charged once; daily
+/// fees are raised on every run until they reach their cap, the rent is paid or
+/// the fee is waived. Fees are never lowered, even if the policy changes.
+pub async fn charge_late_fees(pool: &PgPool, today: Date) -> Result<Option<u64>, AppError> {
+    let mut tx = pool.begin().await?;
+
+    if !db::try_lock_job(&mut tx, LATE_FEE_JOB_LOCK).await? {
+        return Ok(None);
+    }
+
+    let policies: HashMap<_, _> = db::list_late_fee_policies(&mut tx)
+        .await?
+        .into_iter()
+        .map(|policy| (policy.property_id, policy))
+        .collect();
+
+    let mut charged = 0;
+    for late in db::list_late_rent(&mut tx).await? {
+        let Some(policy) = policies.get(&late.property_id) else {
+            continue;
+        };
+        let days_late = (today - late.due_date).whole_days();
+        let Some(fee) = policy.fee_for(&late.amount, days_late) else {
+            continue;
+        };
+
+        if db::upsert_late_fee(&mut tx, &late, &fee, today).await? {
+            tracing::info!(payment_id = %late.payment_id, %fee, "Charged late fee");
+            charged += 1;
+        }
+    }
+
+    tx.commit().await?;
+
+    Ok(Some(charged))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::JobsConfig;
+    use crate::db::fixtures::{money, seed_tenant_and_property};
+    use crate::db::{create_payment, set_late_fee_policy};
+    use crate::jobs::run_jobs;
+    use crate::models::{CreatePayment, LateFeeKind, PaymentStatus, SetLateFeePolicy};
+    use bigdecimal::BigDecimal;
+    use time::macros::date;
+
+    #[sqlx::test]
+    async fn daily_fees_keep_rising_after_the_fee_itself_becomes_overdue(pool: PgPool) {
+        let (tenant_id, property_id) = seed_tenant_and_property(&pool).await;
+        set_late_fee_policy(
+            &pool,
+            property_id,
+            &SetLateFeePolicy {
+                kind: LateFeeKind::Daily,
+                amount: Some(money("10.00")),
+                percentage: None,
+                grace_days: None,
+                max_amount: Some(money("500.00")),
+                max_percentage: None,
+            },
+        )
+        .await
+        .unwrap();
+        let charge = create_payment(
+            &pool,
+            CreatePayment {
+                user_id: tenant_id,
+                property_id,
+                amount: money("1000.00"),
+                currency: "USD".into(),
+                notes: None,
+                due_date: Some(date!(2025 - 06 - 01)),
+                period_start: None,
+                period_end: None,
+            },
+            PaymentStatus::Pending,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let config = JobsConfig {
+            enabled: true,
+            interval: chrono::Duration::minutes(60),
+            rent_lead_days: 7,
+            overdue_grace_days: 3,
+        };
+        let mut today = date!(2025 - 06 - 01);
+        while today <= date!(2025 - 06 - 15) {
+            assert!(run_jobs(&pool, &config, today).await);
+            today = today.next_day().unwrap();
+        }
+
+        let (amount, status): (BigDecimal, String) = sqlx::query_as(
+            "SELECT amount, status::text FROM payments WHERE parent_payment_id = $1",
+        )
+        .bind(charge.id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!((amount, status.as_str()), (money("140.00"), "overdue"));
+
+        // The overdue fee does not get a fee of its own
+        let fees: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM payments WHERE kind = 'late_fee'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(fees, 1);
+
+        let fees_posted: BigDecimal = sqlx::query_scalar(
+            "SELECT SUM(l.credit) FROM journal_lines l WHERE l.account = 'fee_income'",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(fees_posted, money("140.00"));
+    }
+}
@@ -0,0 +1,99 @@
+// src/jobs/mod.rs
+
+// Declare the sub-modules
+pub mod late_fee;
+pub mod overdue;
+pub mod rent;
+
+pub use late_fee::*;
+pub use overdue::*;
+pub use rent::*;
+
+use sqlx::PgPool;
+use time::{Date, OffsetDateTime};
+
+use crate::config::JobsConfig;
+
+/// Runs the background jobs every `config.interval` for as long as the server runs,
+/// starting right away.
+pub fn spawn_scheduler(pool: PgPool, config: JobsConfig) {
+    let interval = config
+        .interval
+        .to_std()
+        .expect("FATAL: JOBS_INTERVAL_MINUTES must be positive");
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            run_jobs(&pool, &config, OffsetDateTime::now_utc().date()).await;
+        }
+    });
+}
+
+/// Runs every job once as of `today`, logging what each did, and returns whether
+/// all of them succeeded. A failing job does not stop the others; it is simply tried
+/// again on the next run.
+pub async fn run_jobs(pool: &PgPool, config: &JobsConfig, today: Date) -> bool {
+    let mut succeeded = true;
+
+    match generate_rent_charges(pool, today, config.rent_lead_days).await {
+        Ok(Some(run)) => tracing::info!(
+            %today,
+            leases = run.leases,
+            charges_created = run.charges_created,
+            "Rent job finished"
+        ),
+        Ok(None) => tracing::debug!("Rent job is already running elsewhere"),
+        Err(e) => {
+            tracing::error!(error = ?e, "Rent job failed");
+            succeeded = false;
+        }
+    }
+
+    match mark_overdue_payments(pool, today, config.overdue_grace_days).await {
+        Ok(marked) => tracing::info!(%today, marked, "Overdue job finished"),
+        Err(e) => {
+            tracing::error!(error = ?e, "Overdue job failed");
+            succeeded = false;
+        }
+    }
+
+    // After the overdue job, so payments that just became overdue are charged today
+    match charge_late_fees(pool, today).await {
+        Ok(Some(charged)) => tracing::info!(%today, charged, "Late fee job finished"),
+        Ok(None) => tracing::debug!("Late fee job is already running elsewhere"),
+        Err(e) => {
+            tracing::error!(error = ?e, "Late fee job failed");
+            succeeded = false;
+        }
+    }
+
+    succeeded
+}
+
+/// Handles `rust_api run-jobs [--date YYYY-MM-DD]`, which runs every job once and
+/// exits, e.g. from cron. `--date` runs them as if it were that day.
+pub async fn run_command(
+    pool: &PgPool,
+    config: &JobsConfig,
+    args: &[String],
+) -> Result<(), String> {
+    const USAGE: &str = "Usage: rust_api [run-jobs [--date YYYY-MM-DD]]";
+
+    let today = match args {
+        [command] if command == "run-jobs" => OffsetDateTime::now_utc().date(),
+        [command, flag, date] if command == "run-jobs" && flag == "--date" => {
+            let format = time::macros::format_description!("[year]-[month]-[day]");
+            Date::parse(date, &format).map_err(|_| format!("Invalid date: {}\n{}", date, USAGE))?
+        }
+        _ => return Err(USAGE.to_string()),
+    };
+
+    if run_jobs(pool, config, today).await {
+        Ok(())
+    } else {
+        Err("A job failed; see the log for details".to_string())
+    }
+}
@@ -0,0 +1,98 @@
+// src/jobs/overdue.rs
+
+use sqlx::PgPool;
+use time::{Date, Duration};
+
+use crate::db;
+use crate::errors::AppError;
+
+/// Moves pending and partially paid payments that still have a balance due
+/// `grace_days` after their due date to overdue. Returns how many payments became overdue.
+///
+/// Each change is kept in the payment's status history with the reason. Payments
+/// without a due date never become overdue.
+pub async fn mark_overdue_payments(
+    pool: &PgPool,
+    today: Date,
+    grace_days: i64,
+) -> Result<u64, AppError> {
+    let due_before = today - Duration::days(grace_days);
+    let reason = format!(
+        "Not paid by its due date plus a grace period of {} days",
+        grace_days
+    );
+
+    db::mark_overdue_payments(pool, due_before, &reason).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::fixtures::seed_tenant_and_property;
+    use crate::db::{create_payment, find_payment_by_id};
+    use crate::models::{CreatePayment, PaymentStatus};
+    use bigdecimal::BigDecimal;
+    use time::macros::date;
+    use uuid::Uuid;
+
+    async fn pending_payment_due(
+        pool: &PgPool,
+        user_id: Uuid,
+        property_id: Uuid,
+        due: Date,
+    ) -> Uuid {
+        let payment = create_payment(
+            pool,
+            CreatePayment {
+                user_id,
+                property_id,
+                amount: BigDecimal::from(1000),
+                currency: "USD".into(),
+                notes: None,
+                due_date: Some(due),
+                period_start: None,
+                period_end: None,
+            },
+            PaymentStatus::Pending,
+            None,
+        )
+        .await
+        .unwrap();
+        payment.id
+    }
+
+    #[sqlx::test]
+    async fn only_payments_past_the_grace_period_become_overdue(pool: PgPool) {
+        let (tenant_id, property_id) = seed_tenant_and_property(&pool).await;
+
+        let late = pending_payment_due(&pool, tenant_id, property_id, date!(2025 - 06 - 01)).await;
+        let in_grace =
+            pending_payment_due(&pool, tenant_id, property_id, date!(2025 - 06 - 02)).await;
+
+        let marked = mark_overdue_payments(&pool, date!(2025 - 06 - 05), 3)
+            .await
+            .unwrap();
+        assert_eq!(marked, 1);
+        let again = mark_overdue_payments(&pool, date!(2025 - 06 - 05), 3)
+            .await
+            .unwrap();
+        assert_eq!(again, 0);
+
+        let late_payment = find_payment_by_id(&pool, late).await.unwrap().unwrap();
+        assert_eq!(late_payment.status, PaymentStatus::Overdue);
+        let grace_payment = find_payment_by_id(&pool, in_grace).await.unwrap().unwrap();
+        assert_eq!(grace_payment.status, PaymentStatus::Pending);
+
+        let (from, to, changed_by): (String, String, Option<Uuid>) = sqlx::query_as(
+            "SELECT from_status::text, to_status::text, changed_by FROM payment_status_events WHERE payment_id = $1",
+        )
+        .bind(late)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            (from.as_str(), to.as_str(), changed_by),
+            ("pending", "overdue", None)
+        );
+    }
+}
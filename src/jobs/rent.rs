@@ -0,0 +1,232 @@
+// src/jobs/rent.rs
+
+use bigdecimal::{BigDecimal, RoundingMode};
+use sqlx::PgPool;
+use time::{Date, Duration};
+
+use crate::db;
+use crate::errors::AppError;
+use crate::models::{BillableLease, NewRentCharge};
+
+/// Advisory lock held while generating rent, so replicas take turns.
+const RENT_JOB_LOCK: i64 = 0x7265_6e74; // "rent"
+
+/// What a run of the rent job did.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct RentRun {
+    pub leases: usize,          // Active leases looked at
+    pub charges_created: usize, // Charges that did not exist yet
+}
+
+/// Charges rent for every period of an active lease that is current or upcoming on
+/// `today` and falls due within `lead_days`.
+///
+/// Periods are calendar months, cut short where the lease starts or ends, and fall
+/// due on the lease's billing day. Partial months are charged pro rata by day.
+/// Periods that ended before `today` are never charged, so restarting after an
+/// outage or adding a lease that began in the past does not backfill old rent.
+///
+/// Charges are unique per lease and period, so running this again, or on several
+/// servers at once, never charges a period twice. Returns `None` if another server
+/// is generating rent right now.
+pub async fn generate_rent_charges(
+    pool: &PgPool,
+    today: Date,
+    lead_days: i64,
+) -> Result<Option<RentRun>, AppError> {
+    let until = today + Duration::days(lead_days);
+    let mut tx = pool.begin().await?;
+
+    if !db::try_lock_job(&mut tx, RENT_JOB_LOCK).await? {
+        return Ok(None);
+    }
+
+    let leases = db::list_billable_leases(&mut tx, today, until).await?;
+    let mut run = RentRun {
+        leases: leases.len(),
+        ..Default::default()
+    };
+    for lease in &leases {
+        for charge in rent_charges(lease, today, until) {
+            if db::create_rent_charge(&mut tx, &charge).await? {
+                tracing::info!(
+                    lease_id = %charge.lease_id,
+                    period_start = %charge.period_start,
+                    amount = %charge.amount,
+                    "Created rent charge"
+                );
+                run.charges_created += 1;
+            }
+        }
+    }
+
+    tx.commit().await?;
+
+    Ok(Some(run))
+}
+
+/// The charges for the periods of `lease` that end on or after `from` and fall due
+/// on or before `until`.
+fn rent_charges(lease: &BillableLease, from: Date, until: Date) -> Vec<NewRentCharge> {
+    let mut charges = Vec::new();
+    let mut month_start = from.max(lease.start_date).replace_day(1).unwrap();
+
+    loop {
+        let days_in_month = month_start.month().length(month_start.year());
+        let month_end = month_start.replace_day(days_in_month).unwrap();
+
+        let period_start = month_start.max(lease.start_date);
+        let period_end = lease.end_date.map_or(month_end, |end| end.min(month_end));
+        if period_end < period_start {
+            break; // The lease ended before this month
+        }
+
+        // Billing days are at most 28, so every month has one
+        let billing_date = month_start.replace_day(lease.billing_day as u8).unwrap();
+        let due_date = billing_date.max(period_start);
+        if due_date > until {
+            break;
+        }
+
+        let days = (period_end - period_start).whole_days() + 1;
+        let amount = if days == i64::from(days_in_month) {
+            lease.monthly_rent.clone()
+        } else {
+            (&lease.monthly_rent * BigDecimal::from(days) / BigDecimal::from(days_in_month))
+                .with_scale_round(2, RoundingMode::HalfUp)
+        };
+
+        charges.push(NewRentCharge {
+            lease_id: lease.id,
+            user_id: lease.tenant_id,
+            property_id: lease.property_id,
+            amount,
+            currency: lease.currency.clone(),
+            notes: format!("Rent for {} {}", month_start.month(), month_start.year()),
+            due_date,
+            period_start,
+            period_end,
+        });
+
+        match month_end.next_day() {
+            Some(next) => month_start = next,
+            None => break,
+        }
+    }
+
+    charges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::fixtures::{seed_active_lease, seed_tenant_and_property};
+    use std::str::FromStr;
+    use time::macros::date;
+    use uuid::Uuid;
+
+    fn lease(start_date: Date, end_date: Option<Date>, billing_day: i16) -> BillableLease {
+        BillableLease {
+            id: Uuid::new_v4(),
+            property_id: Uuid::new_v4(),
+            tenant_id: Uuid::new_v4(),
+            start_date,
+            end_date,
+            monthly_rent: BigDecimal::from_str("3000.00").unwrap(),
+            currency: "USD".into(),
+            billing_day,
+        }
+    }
+
+    #[test]
+    fn charges_the_next_month_once_it_falls_within_the_lead_time() {
+        let lease = lease(date!(2025 - 01 - 01), None, 1);
+
+        let charges = rent_charges(&lease, date!(2025 - 06 - 20), date!(2025 - 06 - 27));
+        assert_eq!(charges.len(), 1);
+        assert_eq!(charges[0].period_start, date!(2025 - 06 - 01));
+
+        let charges = rent_charges(&lease, date!(2025 - 06 - 25), date!(2025 - 07 - 02));
+        let periods: Vec<_> = charges
+            .iter()
+            .map(|c| (c.period_start, c.period_end))
+            .collect();
+        assert_eq!(
+            periods,
+            [
+                (date!(2025 - 06 - 01), date!(2025 - 06 - 30)),
+                (date!(2025 - 07 - 01), date!(2025 - 07 - 31)),
+            ]
+        );
+        assert_eq!(charges[1].due_date, date!(2025 - 07 - 01));
+        assert_eq!(charges[1].amount, lease.monthly_rent);
+    }
+
+    #[test]
+    fn partial_months_are_charged_pro_rata() {
+        // 16 of June's 30 days, and the first 10 of August's 31
+        let lease = lease(date!(2025 - 06 - 15), Some(date!(2025 - 08 - 10)), 5);
+
+        let charges = rent_charges(&lease, date!(2025 - 06 - 01), date!(2025 - 12 - 31));
+        let summary: Vec<_> = charges
+            .iter()
+            .map(|c| (c.due_date, c.period_end, c.amount.to_string()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (
+                    date!(2025 - 06 - 15),
+                    date!(2025 - 06 - 30),
+                    "1600.00".to_string()
+                ),
+                (
+                    date!(2025 - 07 - 05),
+                    date!(2025 - 07 - 31),
+                    "3000.00".to_string()
+                ),
+                (
+                    date!(2025 - 08 - 05),
+                    date!(2025 - 08 - 10),
+                    "967.74".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[sqlx::test]
+    async fn running_again_does_not_charge_a_period_twice(pool: PgPool) {
+        let (tenant, property_id) = seed_tenant_and_property(&pool).await;
+        let lease_id = seed_active_lease(&pool, tenant, property_id, date!(2025 - 01 - 01), "1200")
+            .await
+            .id;
+
+        let today = date!(2025 - 06 - 28);
+        let first = generate_rent_charges(&pool, today, 7).await.unwrap();
+        assert_eq!(
+            first,
+            Some(RentRun {
+                leases: 1,
+                charges_created: 2
+            })
+        );
+        let second = generate_rent_charges(&pool, today, 7).await.unwrap();
+        assert_eq!(
+            second,
+            Some(RentRun {
+                leases: 1,
+                charges_created: 0
+            })
+        );
+
+        let charged: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM payments WHERE lease_id = $1 AND kind = 'rent' AND user_id = $2",
+        )
+        .bind(lease_id)
+        .bind(tenant)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(charged, 2);
+    }
+}
@@ -0,0 +1,97 @@
+// src/mailer/log.rs
+
+use async_trait::async_trait;
+use std::path::PathBuf;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::errors::AppError;
+use crate::mailer::{EmailMessage, Mailer};
+
+/// Development mailer that never talks to a mail server.
+///
+/// Every message is written to the log with the tokens in its links redacted, as
+/// logs are often shipped elsewhere and anyone reading them could otherwise take
+/// over an account. Set `MAIL_LOG_DIR` to also write each message in full to its own
+/// file, so links can be copied out during local testing.
+pub struct LogMailer {
+    dir: Option<PathBuf>,
+}
+
+impl LogMailer {
+    pub fn from_env() -> Self {
+        LogMailer {
+            dir: std::env::var("MAIL_LOG_DIR").ok().map(PathBuf::from),
+        }
+    }
+}
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, message: EmailMessage) -> Result<(), AppError> {
+        tracing::info!(
+            to = %message.to,
+            subject = %message.subject,
+            "Email (not sent, MAILER=log):\n{}",
+            redact_tokens(&message.body)
+        );
+
+        if let Some(dir) = &self.dir {
+            let file_name = format!(
+                "{}-{}.txt",
+                OffsetDateTime::now_utc().unix_timestamp(),
+                Uuid::new_v4()
+            );
+            let contents = format!(
+                "To: {}\nSubject: {}\n\n{}\n",
+                message.to, message.subject, message.body
+            );
+
+            tokio::fs::create_dir_all(dir).await.map_err(|e| {
+                AppError::InternalServerError(format!("Failed to write email: {}", e))
+            })?;
+            tokio::fs::write(dir.join(file_name), contents)
+                .await
+                .map_err(|e| {
+                    AppError::InternalServerError(format!("Failed to write email: {}", e))
+                })?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Replaces the value of every `token=` query parameter in `text`.
+fn redact_tokens(text: &str) -> String {
+    const MARKER: &str = "token=";
+
+    let mut redacted = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(MARKER) {
+        let (before, after) = rest.split_at(start + MARKER.len());
+        redacted.push_str(before);
+        redacted.push_str("[redacted]");
+        let end = after
+            .find(|c: char| c.is_whitespace() || c == '&')
+            .unwrap_or(after.len());
+        rest = &after[end..];
+    }
+    redacted.push_str(rest);
+    redacted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_in_links_are_redacted() {
+        let body =
+            "Open this link:\n\nhttp://localhost:3000/reset-password?token=abc123&x=1\n\nThanks";
+        assert_eq!(
+            redact_tokens(body),
+            "Open this link:\n\nhttp://localhost:3000/reset-password?token=[redacted]&x=1\n\nThanks"
+        );
+        assert_eq!(redact_tokens("no links here"), "no links here");
+    }
+}
@@ -0,0 +1,40 @@
+// Declare the sub-modules
+pub mod log;
+pub mod smtp;
+
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use crate::errors::AppError;
+
+pub use log::LogMailer;
+pub use smtp::SmtpMailer;
+
+/// A plain-text email to a single recipient.
+#[derive(Debug, Clone)]
+pub struct EmailMessage {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Sends transactional email (verification links, password resets).
+///
+/// Handlers only depend on this trait so the SMTP transport can be swapped for
+/// `LogMailer` during local development and testing.
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, message: EmailMessage) -> Result<(), AppError>;
+}
+
+/// Builds the mailer selected by `MAILER` (`smtp` or `log`, defaulting to `log`).
+pub fn from_env() -> Arc<dyn Mailer> {
+    match std::env::var("MAILER").as_deref() {
+        Ok("smtp") => Arc::new(SmtpMailer::from_env()),
+        Ok("log") | Err(_) => Arc::new(LogMailer::from_env()),
+        Ok(other) => panic!(
+            "FATAL: Unknown MAILER '{}', expected 'smtp' or 'log'",
+            other
+        ),
+    }
+}
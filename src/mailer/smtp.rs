@@ -0,0 +1,83 @@
+// src/mailer/smtp.rs
+
+use async_trait::async_trait;
+use lettre::{
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+    message::{Mailbox, header::ContentType},
+    transport::smtp::authentication::Credentials,
+};
+
+use crate::config::env_or;
+use crate::errors::AppError;
+use crate::mailer::{EmailMessage, Mailer};
+
+/// Delivers mail through an SMTP relay.
+///
+/// Configured with `SMTP_HOST`, `SMTP_PORT` (587), `SMTP_USERNAME`, `SMTP_PASSWORD`,
+/// `SMTP_TLS` (`starttls`, `tls` or `none`) and `MAIL_FROM`.
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    pub fn from_env() -> Self {
+        let host =
+            std::env::var("SMTP_HOST").expect("FATAL: SMTP_HOST must be set when MAILER=smtp");
+        let port = env_or("SMTP_PORT", 587);
+
+        let builder = match std::env::var("SMTP_TLS").as_deref() {
+            Ok("none") => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&host),
+            Ok("tls") => AsyncSmtpTransport::<Tokio1Executor>::relay(&host)
+                .expect("FATAL: Invalid SMTP_HOST"),
+            _ => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&host)
+                .expect("FATAL: Invalid SMTP_HOST"),
+        };
+
+        let builder = match (
+            std::env::var("SMTP_USERNAME"),
+            std::env::var("SMTP_PASSWORD"),
+        ) {
+            (Ok(username), Ok(password)) => {
+                builder.credentials(Credentials::new(username, password))
+            }
+            _ => builder,
+        };
+
+        SmtpMailer {
+            transport: builder.port(port).build(),
+            from: mail_from(),
+        }
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, message: EmailMessage) -> Result<(), AppError> {
+        let to: Mailbox = message.to.parse().map_err(|e| {
+            AppError::InternalServerError(format!("Invalid recipient address: {}", e))
+        })?;
+
+        let email = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(message.subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(message.body)
+            .map_err(|e| AppError::InternalServerError(format!("Failed to build email: {}", e)))?;
+
+        self.transport
+            .send(email)
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Failed to send email: {}", e)))?;
+
+        Ok(())
+    }
+}
+
+fn mail_from() -> Mailbox {
+    std::env::var("MAIL_FROM")
+        .unwrap_or_else(|_| "Rust API <no-reply@localhost>".into())
+        .parse()
+        .expect("FATAL: MAIL_FROM must be a valid mailbox")
+}
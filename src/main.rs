@@ -1,44 +1,63 @@
+mod auth;
+mod config;
 mod db;
 mod errors;
+mod extractors;
 mod handlers;
+mod jobs;
+mod mailer;
 mod models;
+mod request_id;
+mod validation;
 
 use axum::{
-    Router,
-    routing::{get, post},
+    Router, middleware,
+    routing::{delete, get, patch, post},
 };
 use dotenvy::dotenv;
 use sqlx::{PgPool, postgres::PgPoolOptions};
 use std::net::SocketAddr;
+use std::sync::Arc;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt}; // For logging
 
 use tokio::net::TcpListener;
 
 // Import all your handler functions
 // Thanks to src/handlers/mod.rs, you can import them all directly.
+use crate::auth::keys::JwtKeys;
+use crate::auth::oidc::OidcClient;
+use crate::config::{AuthConfig, JobsConfig, OidcConfig};
+use crate::errors::AppError;
 use crate::handlers::{
-    create_payment, create_property, list_payments, list_properties, login_user, register_user,
+    confirm_totp, create_api_key, create_lease, create_ledger_entry, create_payment,
+    create_property, create_receipt, delete_late_fee_policy, delete_property, disable_totp,
+    enroll_totp, forgot_password, get_current_user, get_late_fee_policy, get_lease, get_payment,
+    get_property, get_receipt, get_user, jwks, list_api_keys, list_leases, list_overdue_balances,
+    list_payments, list_properties, list_receipts, login_mfa, login_user, logout, logout_all,
+    oidc_callback, oidc_login, property_ledger, refresh_token, register_user,
+    request_email_verification, reset_password, revoke_api_key, set_late_fee_policy, tenant_ledger,
+    unlock_user, update_lease, update_lease_status, update_payment_status, update_property,
+    update_user_role, verify_email, waive_late_fee,
 };
-
-#[derive(Debug, Clone)]
-pub struct JwtSecret(String);
-
-impl From<String> for JwtSecret {
-    fn from(secret: String) -> Self {
-        JwtSecret(secret)
-    }
-}
+use crate::mailer::Mailer;
 
 #[derive(Clone)]
 pub struct AppState {
     pub pool: PgPool,
-    pub jwt_secret: JwtSecret,
+    pub jwt_keys: Arc<JwtKeys>,
+    pub auth_config: AuthConfig,
+    pub mailer: Arc<dyn Mailer>,
+    pub oidc: Option<Arc<OidcClient>>, // Set when an OpenID Connect provider is configured
 }
 
 async fn health_check() -> &'static str {
     "OK"
 }
 
+async fn route_not_found() -> AppError {
+    AppError::NotFound("No such endpoint".into())
+}
+
 #[tokio::main]
 async fn main() {
     // Initialize Tracing for Logging (Optional but Recommended)
@@ -53,11 +72,9 @@ async fn main() {
     // Load environment variables from .env file (for local development)
     dotenv().ok();
 
-    // Get DATABASE_URL and JWT_SECRET from environment variables
+    // Get DATABASE_URL from environment variables
     let database_url = std::env::var("DATABASE_URL")
         .expect("FATAL: DATABASE_URL must be set in .env or environment");
-    let jwt_secret_string =
-        std::env::var("JWT_SECRET").expect("FATAL: JWT_SECRET must be set in .env or environment");
 
     // Create database connection pool
     let pool = PgPoolOptions::new()
@@ -66,27 +83,107 @@ async fn main() {
         .await
         .expect("FATAL: Failed to connect to Postgres database.");
 
-    // Initialize the JwtSecret struct to be passed in Axum State
-    let jwt_secret = JwtSecret(jwt_secret_string);
+    // `rust_api run-jobs` runs the background jobs once and exits instead of serving
+    let jobs_config = JobsConfig::from_env();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        if let Err(message) = jobs::run_command(&pool, &jobs_config, &args).await {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    if jobs_config.enabled {
+        jobs::spawn_scheduler(pool.clone(), jobs_config);
+    }
+
+    // Load the JWT signing keys to be passed in Axum State
+    let jwt_keys = Arc::new(JwtKeys::from_env());
 
     // Create the combined application state
     let app_state = AppState {
-        pool: pool,
-        jwt_secret,
+        pool,
+        jwt_keys,
+        auth_config: AuthConfig::from_env(),
+        mailer: mailer::from_env(),
+        oidc: OidcConfig::from_env().map(|config| Arc::new(OidcClient::new(config))),
     };
 
+    // Routes that require a valid bearer token
+    let protected_routes = Router::new()
+        .route("/logout/all", post(logout_all))
+        // User profile routes
+        .route("/users/me", get(get_current_user))
+        .route("/users/{id}", get(get_user))
+        .route("/users/{id}/role", patch(update_user_role))
+        .route("/users/{id}/unlock", post(unlock_user))
+        .route("/email/verification", post(request_email_verification))
+        // API keys for integrations
+        .route("/api-keys", post(create_api_key).get(list_api_keys))
+        .route("/api-keys/{id}", delete(revoke_api_key))
+        // Two-factor authentication
+        .route("/mfa/totp/enroll", post(enroll_totp))
+        .route("/mfa/totp/confirm", post(confirm_totp))
+        .route("/mfa/totp/disable", post(disable_totp))
+        // Property routes
+        .route("/properties", post(create_property).get(list_properties))
+        .route(
+            "/properties/{id}",
+            get(get_property)
+                .patch(update_property)
+                .delete(delete_property),
+        )
+        .route(
+            "/properties/{id}/late-fee-policy",
+            get(get_late_fee_policy)
+                .put(set_late_fee_policy)
+                .delete(delete_late_fee_policy),
+        )
+        // Lease routes
+        .route("/leases", post(create_lease).get(list_leases))
+        .route("/leases/{id}", get(get_lease).patch(update_lease))
+        .route("/leases/{id}/status", patch(update_lease_status))
+        // Payment routes
+        .route("/payments", post(create_payment).get(list_payments))
+        .route("/payments/overdue", get(list_overdue_balances))
+        .route("/payments/{id}", get(get_payment))
+        .route("/payments/{id}/status", patch(update_payment_status))
+        .route("/payments/{id}/waive", post(waive_late_fee))
+        // Receipt routes
+        .route("/receipts", post(create_receipt).get(list_receipts))
+        .route("/receipts/{id}", get(get_receipt))
+        // Ledger routes
+        .route("/ledger/entries", post(create_ledger_entry))
+        .route("/tenants/{id}/ledger", get(tenant_ledger))
+        .route("/properties/{id}/ledger", get(property_ledger))
+        .route_layer(middleware::from_fn_with_state(
+            app_state.clone(),
+            auth::require_auth,
+        ));
+
     // Define the routes and attach handlers
     let app = Router::new()
         .route("/health_check", get(health_check))
         // User routes
         .route("/register", post(register_user))
         .route("/login", post(login_user))
-        // Property routes
-        .route("/properties", post(create_property).get(list_properties))
-        // Payment routes
-        .route("/payments", post(create_payment).get(list_payments))
-        // Note: For now, these routes are open. We'll add authentication middleware later.
-        // Add the database pool and JWT secret to the application state
+        .route("/login/mfa", post(login_mfa))
+        .route("/token/refresh", post(refresh_token))
+        .route("/logout", post(logout))
+        // Sign in through an external OpenID Connect provider
+        .route("/auth/oidc/login", get(oidc_login))
+        .route("/auth/oidc/callback", get(oidc_callback))
+        .route("/.well-known/jwks.json", get(jwks))
+        // Email verification and password recovery
+        .route("/email/verify", post(verify_email))
+        .route("/password/forgot", post(forgot_password))
+        .route("/password/reset", post(reset_password))
+        .merge(protected_routes)
+        .fallback(route_not_found)
+        // Outermost, so every response and log line carries the request id
+        .layer(middleware::from_fn(request_id::request_id))
+        // Add the database pool and JWT keys to the application state
         .with_state(app_state);
 
     // Define the address to listen on
@@ -97,7 +194,11 @@ async fn main() {
     let listener = TcpListener::bind(&addr)
         .await
         .expect("FATAL: Failed to bind address");
-    axum::serve(listener, app) // Use axum::serve
-        .await
-        .expect("FATAL: Server failed to start");
+    // Expose the peer address so login throttling can key on the client IP
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .expect("FATAL: Server failed to start");
 }
@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::models::UserRole;
+use crate::validation::{Validate, Validator};
+
+/// Longest an API key can be created to last for, in days.
+pub const MAX_API_KEY_EXPIRY_DAYS: i64 = 365;
+
+// --- API Key Scope ENUM ---
+// Stored as plain text in `api_keys.scopes`, e.g. "payments:read".
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ApiScope {
+    #[serde(rename = "payments:read")]
+    PaymentsRead,
+    #[serde(rename = "payments:write")]
+    PaymentsWrite,
+    #[serde(rename = "properties:read")]
+    PropertiesRead,
+    #[serde(rename = "properties:write")]
+    PropertiesWrite,
+    #[serde(rename = "users:read")]
+    UsersRead,
+}
+
+impl ApiScope {
+    pub const ALL: [ApiScope; 5] = [
+        ApiScope::PaymentsRead,
+        ApiScope::PaymentsWrite,
+        ApiScope::PropertiesRead,
+        ApiScope::PropertiesWrite,
+        ApiScope::UsersRead,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiScope::PaymentsRead => "payments:read",
+            ApiScope::PaymentsWrite => "payments:write",
+            ApiScope::PropertiesRead => "properties:read",
+            ApiScope::PropertiesWrite => "properties:write",
+            ApiScope::UsersRead => "users:read",
+        }
+    }
+
+    /// Parses a stored scope, ignoring ones this version no longer knows.
+    pub fn parse(scope: &str) -> Option<ApiScope> {
+        ApiScope::ALL.into_iter().find(|s| s.as_str() == scope)
+    }
+}
+
+// --- API Key Model (Database Representation) ---
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub prefix: String,
+    pub scopes: Vec<String>,
+    pub last_used_at: Option<OffsetDateTime>,
+    pub expires_at: Option<OffsetDateTime>,
+    pub revoked_at: Option<OffsetDateTime>,
+    pub created_at: OffsetDateTime,
+}
+
+// --- API Key DTOs ---
+
+// For creating a new API key (API Request Body)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateApiKey {
+    pub name: String,
+    pub scopes: Vec<ApiScope>,
+    pub expires_in_days: Option<i64>, // Omit for a key that does not expire
+}
+
+impl Validate for CreateApiKey {
+    fn validate(&self, v: &mut Validator) {
+        if let Some(days) = self.expires_in_days {
+            v.check(
+                (1..=MAX_API_KEY_EXPIRY_DAYS).contains(&days),
+                "expires_in_days",
+                "range",
+                &format!("Must be between 1 and {}", MAX_API_KEY_EXPIRY_DAYS),
+            );
+        }
+    }
+}
+
+// For returning a newly created key. The secret is only ever shown here (API Response Body)
+#[derive(Debug, Serialize)]
+pub struct CreatedApiKey {
+    #[serde(flatten)]
+    pub api_key: ApiKey,
+    pub key: String,
+}
+
+// --- API Key Lookup ---
+// A key joined with the user it acts as, for authenticating requests.
+#[derive(Debug, FromRow)]
+pub struct ApiKeyCredentials {
+    pub id: Uuid,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<OffsetDateTime>,
+    pub revoked_at: Option<OffsetDateTime>,
+    pub user_id: Uuid,
+    pub username: String,
+    pub role: UserRole,
+}
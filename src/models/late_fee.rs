@@ -0,0 +1,205 @@
+use bigdecimal::{BigDecimal, RoundingMode, Zero};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use time::{Date, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::validation::{Validate, Validator};
+
+// --- Late Fee Kind ENUM (Database Representation) ---
+#[derive(Clone, Copy, Debug, PartialEq, Eq, sqlx::Type, Deserialize, Serialize)]
+#[sqlx(type_name = "late_fee_kind", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum LateFeeKind {
+    Flat,       // `amount` once
+    Percentage, // `percentage` of the rent once
+    Daily,      // `amount` for every day late, up to `max_amount`
+}
+
+// --- Late Fee Policy Model (Database Representation) ---
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct LateFeePolicy {
+    pub property_id: Uuid,
+    pub kind: LateFeeKind,
+    pub amount: Option<BigDecimal>,
+    pub percentage: Option<BigDecimal>,
+    pub grace_days: i16, // Days past the due date before a fee applies
+    pub max_amount: Option<BigDecimal>, // Cap on the fee for one late payment
+    pub max_percentage: Option<BigDecimal>, // Legal maximum, as a percentage of the rent
+    pub created_at: OffsetDateTime,
+    pub updated_at: OffsetDateTime,
+}
+
+impl LateFeePolicy {
+    /// The fee for rent of `rent` that is `days_late` days past its due date, or
+    /// `None` while it is still within the grace days.
+    ///
+    /// Daily fees count from the end of the grace days. Every fee is capped by
+    /// `max_amount` and `max_percentage`, whichever is lower.
+    pub fn fee_for(&self, rent: &BigDecimal, days_late: i64) -> Option<BigDecimal> {
+        let days_charged = days_late - i64::from(self.grace_days);
+        if days_charged <= 0 {
+            return None;
+        }
+
+        let hundred = BigDecimal::from(100);
+        let zero = BigDecimal::zero();
+        let mut fee = match self.kind {
+            LateFeeKind::Flat => self.amount.clone().unwrap_or(zero),
+            LateFeeKind::Percentage => rent * self.percentage.as_ref().unwrap_or(&zero) / &hundred,
+            LateFeeKind::Daily => {
+                self.amount.as_ref().unwrap_or(&zero) * BigDecimal::from(days_charged)
+            }
+        };
+        if let Some(max_amount) = &self.max_amount {
+            fee = fee.min(max_amount.clone());
+        }
+        if let Some(max_percentage) = &self.max_percentage {
+            fee = fee.min(rent * max_percentage / &hundred);
+        }
+
+        let fee = fee.with_scale_round(2, RoundingMode::Down);
+        (fee > BigDecimal::zero()).then_some(fee)
+    }
+}
+
+// --- Late Fee DTOs ---
+
+// For setting a property's late fee policy (API Request Body)
+#[derive(Debug, Deserialize)]
+pub struct SetLateFeePolicy {
+    pub kind: LateFeeKind,
+    pub amount: Option<BigDecimal>, // Required for flat and daily fees
+    pub percentage: Option<BigDecimal>, // Required for percentage fees
+    pub grace_days: Option<i16>,    // Defaults to none
+    pub max_amount: Option<BigDecimal>, // Required for daily fees
+    pub max_percentage: Option<BigDecimal>,
+}
+
+impl Validate for SetLateFeePolicy {
+    fn validate(&self, v: &mut Validator) {
+        let (amount_used, percentage_used) = match self.kind {
+            LateFeeKind::Flat | LateFeeKind::Daily => (true, false),
+            LateFeeKind::Percentage => (false, true),
+        };
+        match &self.amount {
+            Some(amount) if amount_used => v.amount("amount", amount),
+            Some(_) => v.check(false, "amount", "invalid", "Not used by percentage fees"),
+            None => v.check(
+                !amount_used,
+                "amount",
+                "required",
+                "Required for flat and daily fees",
+            ),
+        }
+        match &self.percentage {
+            Some(percentage) if percentage_used => v.percentage("percentage", percentage),
+            Some(_) => v.check(
+                false,
+                "percentage",
+                "invalid",
+                "Only used by percentage fees",
+            ),
+            None => v.check(
+                !percentage_used,
+                "percentage",
+                "required",
+                "Required for percentage fees",
+            ),
+        }
+        if let Some(grace_days) = self.grace_days {
+            v.check(
+                (0..=90).contains(&grace_days),
+                "grace_days",
+                "range",
+                "Must be between 0 and 90",
+            );
+        }
+        match &self.max_amount {
+            Some(max_amount) => v.amount("max_amount", max_amount),
+            None => v.check(
+                self.kind != LateFeeKind::Daily,
+                "max_amount",
+                "required",
+                "Required for daily fees",
+            ),
+        }
+        if let Some(max_percentage) = &self.max_percentage {
+            v.percentage("max_percentage", max_percentage);
+        }
+    }
+}
+
+// For waiving a late fee (API Request Body)
+#[derive(Debug, Deserialize)]
+pub struct WaiveLateFee {
+    pub reason: String, // Kept in the fee's status history
+}
+
+impl Validate for WaiveLateFee {
+    fn validate(&self, v: &mut Validator) {
+        v.length("reason", &self.reason, 1, 500);
+    }
+}
+
+// --- Late Rent (Late Fee Job Input) ---
+// An overdue rent or manually recorded charge on a property with a late fee policy.
+#[derive(Debug, FromRow, Clone)]
+pub struct LateRent {
+    pub payment_id: Uuid,
+    pub user_id: Uuid,
+    pub property_id: Uuid,
+    pub lease_id: Option<Uuid>,
+    pub amount: BigDecimal,
+    pub currency: String,
+    pub notes: Option<String>,
+    pub due_date: Date,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn policy(kind: LateFeeKind, amount: Option<&str>, percentage: Option<&str>) -> LateFeePolicy {
+        LateFeePolicy {
+            property_id: Uuid::new_v4(),
+            kind,
+            amount: amount.map(|a| BigDecimal::from_str(a).unwrap()),
+            percentage: percentage.map(|p| BigDecimal::from_str(p).unwrap()),
+            grace_days: 5,
+            max_amount: None,
+            max_percentage: None,
+            created_at: OffsetDateTime::UNIX_EPOCH,
+            updated_at: OffsetDateTime::UNIX_EPOCH,
+        }
+    }
+
+    fn fee(policy: &LateFeePolicy, days_late: i64) -> Option<String> {
+        let rent = BigDecimal::from(1500);
+        policy.fee_for(&rent, days_late).map(|fee| fee.to_string())
+    }
+
+    #[test]
+    fn no_fee_within_the_grace_days() {
+        let flat = policy(LateFeeKind::Flat, Some("50"), None);
+        assert_eq!(fee(&flat, 5), None);
+        assert_eq!(fee(&flat, 6).as_deref(), Some("50.00"));
+        assert_eq!(fee(&flat, 30).as_deref(), Some("50.00"));
+
+        let percentage = policy(LateFeeKind::Percentage, None, Some("7.5"));
+        assert_eq!(fee(&percentage, 6).as_deref(), Some("112.50"));
+    }
+
+    #[test]
+    fn daily_fees_accrue_after_the_grace_days_up_to_the_lowest_cap() {
+        let mut daily = policy(LateFeeKind::Daily, Some("10"), None);
+        daily.max_amount = Some(BigDecimal::from(100));
+        assert_eq!(fee(&daily, 8).as_deref(), Some("30.00"));
+        assert_eq!(fee(&daily, 40).as_deref(), Some("100.00"));
+
+        // A legal maximum of 5% of the rent is lower than the cap
+        daily.max_percentage = Some(BigDecimal::from(5));
+        assert_eq!(fee(&daily, 40).as_deref(), Some("75.00"));
+    }
+}
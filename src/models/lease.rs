@@ -0,0 +1,263 @@
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use time::{Date, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::models::iso_date;
+use crate::validation::{Validate, Validator};
+
+/// Most tenants that can share one lease.
+pub const MAX_LEASE_TENANTS: usize = 10;
+
+// --- Lease Status ENUM (Database Representation) ---
+#[derive(Clone, Copy, Debug, PartialEq, Eq, sqlx::Type, Deserialize, Serialize)]
+#[sqlx(type_name = "lease_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum LeaseStatus {
+    Draft,      // Being prepared; can still be changed freely
+    Active,     // Signed; only one active lease per property may cover any day
+    Ended,      // Ran its course
+    Terminated, // Ended early, or a draft that was abandoned
+}
+
+impl LeaseStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LeaseStatus::Draft => "draft",
+            LeaseStatus::Active => "active",
+            LeaseStatus::Ended => "ended",
+            LeaseStatus::Terminated => "terminated",
+        }
+    }
+
+    /// Whether a lease may move from this status to `next`. Ended and terminated
+    /// leases are kept as history and never change again.
+    pub fn can_transition_to(&self, next: &LeaseStatus) -> bool {
+        use LeaseStatus::*;
+
+        matches!(
+            (self, next),
+            (Draft, Active) | (Draft, Terminated) | (Active, Ended) | (Active, Terminated)
+        )
+    }
+}
+
+// --- Lease Model (Database Representation) ---
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct Lease {
+    pub id: Uuid,
+    pub property_id: Uuid,
+    pub tenant_ids: Vec<Uuid>,
+    pub primary_tenant_id: Uuid, // One of `tenant_ids`, who rent is charged to
+    #[serde(with = "iso_date")]
+    pub start_date: Date,
+    #[serde(with = "iso_date::option")]
+    pub end_date: Option<Date>, // Inclusive; `null` for a month-to-month lease
+    pub monthly_rent: BigDecimal,
+    pub deposit: BigDecimal,
+    pub currency: String,
+    pub billing_day: i16, // Day of the month rent is due
+    pub status: LeaseStatus,
+    pub created_at: OffsetDateTime,
+    pub updated_at: OffsetDateTime,
+}
+
+// --- Lease DTOs ---
+
+// For creating a new lease (API Request Body)
+#[derive(Debug, Deserialize)]
+pub struct CreateLease {
+    pub property_id: Uuid,
+    pub tenant_ids: Vec<Uuid>,
+    pub primary_tenant_id: Option<Uuid>, // Defaults to the first of `tenant_ids`
+    #[serde(with = "iso_date")]
+    pub start_date: Date,
+    #[serde(default, with = "iso_date::option")]
+    pub end_date: Option<Date>,
+    pub monthly_rent: BigDecimal,
+    pub deposit: Option<BigDecimal>, // Defaults to no deposit
+    pub currency: Option<String>,    // Defaults to USD
+    pub billing_day: Option<i16>,    // Defaults to the 1st
+    pub status: Option<LeaseStatus>, // `draft` (default) or `active`
+}
+
+impl CreateLease {
+    /// The tenant rent will be charged to.
+    pub fn primary_tenant_id(&self) -> Option<Uuid> {
+        self.primary_tenant_id
+            .or_else(|| self.tenant_ids.first().copied())
+    }
+}
+
+impl Validate for CreateLease {
+    fn validate(&self, v: &mut Validator) {
+        validate_tenant_ids(v, &self.tenant_ids);
+        validate_primary_tenant(v, self.primary_tenant_id, &self.tenant_ids);
+        validate_dates(v, Some(self.start_date), self.end_date);
+        v.amount("monthly_rent", &self.monthly_rent);
+        if let Some(deposit) = &self.deposit {
+            v.non_negative_amount("deposit", deposit);
+        }
+        if let Some(currency) = &self.currency {
+            v.currency("currency", currency);
+        }
+        if let Some(billing_day) = self.billing_day {
+            validate_billing_day(v, billing_day);
+        }
+        if let Some(status) = self.status {
+            v.check(
+                matches!(status, LeaseStatus::Draft | LeaseStatus::Active),
+                "status",
+                "invalid",
+                "New leases are either draft or active",
+            );
+        }
+    }
+}
+
+// For changing the terms of a lease (API Request Body)
+// Drafts can change anything; active leases only their tenants and end date.
+#[derive(Debug, Deserialize)]
+pub struct UpdateLease {
+    pub tenant_ids: Option<Vec<Uuid>>,
+    pub primary_tenant_id: Option<Uuid>, // Kept while still a tenant, otherwise the first of `tenant_ids`
+    #[serde(default, with = "iso_date::option")]
+    pub start_date: Option<Date>,
+    #[serde(default, deserialize_with = "double_option_date")]
+    pub end_date: Option<Option<Date>>, // `null` makes the lease open-ended
+    pub monthly_rent: Option<BigDecimal>,
+    pub deposit: Option<BigDecimal>,
+    pub currency: Option<String>,
+    pub billing_day: Option<i16>,
+}
+
+impl UpdateLease {
+    /// Whether the update touches terms that are fixed once a lease is active.
+    pub fn changes_fixed_terms(&self) -> bool {
+        self.start_date.is_some()
+            || self.monthly_rent.is_some()
+            || self.deposit.is_some()
+            || self.currency.is_some()
+            || self.billing_day.is_some()
+    }
+}
+
+impl Validate for UpdateLease {
+    fn validate(&self, v: &mut Validator) {
+        if let Some(tenant_ids) = &self.tenant_ids {
+            validate_tenant_ids(v, tenant_ids);
+            validate_primary_tenant(v, self.primary_tenant_id, tenant_ids);
+        }
+        validate_dates(v, self.start_date, self.end_date.flatten());
+        if let Some(monthly_rent) = &self.monthly_rent {
+            v.amount("monthly_rent", monthly_rent);
+        }
+        if let Some(deposit) = &self.deposit {
+            v.non_negative_amount("deposit", deposit);
+        }
+        if let Some(currency) = &self.currency {
+            v.currency("currency", currency);
+        }
+        if let Some(billing_day) = self.billing_day {
+            validate_billing_day(v, billing_day);
+        }
+    }
+}
+
+// For activating, ending or terminating a lease (API Request Body)
+#[derive(Debug, Deserialize)]
+pub struct UpdateLeaseStatus {
+    pub status: LeaseStatus,
+    #[serde(default, with = "iso_date::option")]
+    pub end_date: Option<Date>, // Last day of an ended or terminated lease; see `update_lease_status`
+}
+
+impl Validate for UpdateLeaseStatus {}
+
+// For filtering and paging the lease list (Query Parameters)
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ListLeasesQuery {
+    pub property_id: Option<Uuid>,
+    pub tenant_id: Option<Uuid>,
+    pub status: Option<LeaseStatus>,
+    pub limit: Option<i64>,
+    pub cursor: Option<String>,
+}
+
+// --- Billable Lease (Rent Scheduler Input) ---
+// An active lease as the rent scheduler sees it, with the tenant its rent is charged to.
+#[derive(Debug, FromRow, Clone)]
+pub struct BillableLease {
+    pub id: Uuid,
+    pub property_id: Uuid,
+    pub tenant_id: Uuid, // The lease's `primary_tenant_id`
+    pub start_date: Date,
+    pub end_date: Option<Date>,
+    pub monthly_rent: BigDecimal,
+    pub currency: String,
+    pub billing_day: i16,
+}
+
+fn validate_tenant_ids(v: &mut Validator, tenant_ids: &[Uuid]) {
+    v.check(
+        (1..=MAX_LEASE_TENANTS).contains(&tenant_ids.len()),
+        "tenant_ids",
+        "length",
+        &format!("Must list between 1 and {} tenants", MAX_LEASE_TENANTS),
+    );
+    v.check(
+        tenant_ids
+            .iter()
+            .enumerate()
+            .all(|(i, id)| !tenant_ids[..i].contains(id)),
+        "tenant_ids",
+        "duplicate",
+        "Must not list a tenant twice",
+    );
+}
+
+fn validate_primary_tenant(
+    v: &mut Validator,
+    primary_tenant_id: Option<Uuid>,
+    tenant_ids: &[Uuid],
+) {
+    if let Some(primary_tenant_id) = primary_tenant_id {
+        v.check(
+            tenant_ids.contains(&primary_tenant_id),
+            "primary_tenant_id",
+            "invalid",
+            "Must be one of tenant_ids",
+        );
+    }
+}
+
+fn validate_dates(v: &mut Validator, start_date: Option<Date>, end_date: Option<Date>) {
+    if let Some((start, end)) = start_date.zip(end_date) {
+        v.check(
+            end >= start,
+            "end_date",
+            "range",
+            "Must not be before start_date",
+        );
+    }
+}
+
+fn validate_billing_day(v: &mut Validator, billing_day: i16) {
+    // Capped at 28 so every month has the day
+    v.check(
+        (1..=28).contains(&billing_day),
+        "billing_day",
+        "range",
+        "Must be between 1 and 28",
+    );
+}
+
+/// `double_option` for dates written as `YYYY-MM-DD`.
+fn double_option_date<'de, D>(deserializer: D) -> Result<Option<Option<Date>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    iso_date::option::deserialize(deserializer).map(Some)
+}
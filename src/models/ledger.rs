@@ -0,0 +1,299 @@
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use time::{Date, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::models::{PaymentKind, iso_date};
+use crate::validation::{Validate, Validator};
+
+// --- Ledger Account ENUM (Database Representation) ---
+#[derive(Clone, Copy, Debug, PartialEq, Eq, sqlx::Type, Deserialize, Serialize)]
+#[sqlx(type_name = "ledger_account", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum LedgerAccount {
+    Cash,
+    RentReceivable, // What tenants owe; negative when they are in credit
+    DepositsHeld,   // Deposits owed back to tenants
+    RentIncome,
+    FeeIncome, // Late fees
+    BadDebt,   // Debts written off
+}
+
+impl LedgerAccount {
+    /// Whether debits increase the account's balance, as they do for assets and
+    /// expenses. Credits increase the balance of liabilities and income.
+    pub fn is_debit_normal(&self) -> bool {
+        matches!(
+            self,
+            LedgerAccount::Cash | LedgerAccount::RentReceivable | LedgerAccount::BadDebt
+        )
+    }
+}
+
+// --- Journal Entry Kind ENUM (Database Representation) ---
+#[derive(Clone, Copy, Debug, PartialEq, Eq, sqlx::Type, Deserialize, Serialize)]
+#[sqlx(type_name = "journal_entry_kind", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum JournalEntryKind {
+    Charge,          // Rent or a fee falls due: receivable up, income up
+    Receipt,         // Money received: cash up, receivable down
+    Credit,          // Part of a charge forgiven, e.g. a waived fee: income down, receivable down
+    Refund,          // Money paid back to a tenant in credit: receivable up, cash down
+    WriteOff,        // A debt given up on: bad debt up, receivable down
+    DepositReceived, // Cash up, deposits held up
+    DepositReturned, // Deposits held down, cash down
+}
+
+impl JournalEntryKind {
+    /// Whether entries of this kind can be posted by hand.
+    pub fn is_manual(&self) -> bool {
+        !matches!(self, JournalEntryKind::Charge | JournalEntryKind::Receipt)
+    }
+
+    /// Whether a manual entry of this kind relates to one payment: credits must
+    /// name the charge they reduce, refunds and write-offs may, deposits never do.
+    pub fn takes_payment(&self) -> bool {
+        matches!(
+            self,
+            JournalEntryKind::Credit | JournalEntryKind::Refund | JournalEntryKind::WriteOff
+        )
+    }
+
+    /// The accounts debited and credited by a manual entry of this kind against a
+    /// charge of the given kind, or `None` for kinds only ever posted by the API
+    /// itself. A credit reverses the income the charge earned.
+    pub fn manual_accounts(&self, charge: PaymentKind) -> Option<(LedgerAccount, LedgerAccount)> {
+        use LedgerAccount::*;
+
+        match self {
+            JournalEntryKind::Charge | JournalEntryKind::Receipt => None,
+            JournalEntryKind::Credit => Some((charge.income_account(), RentReceivable)),
+            JournalEntryKind::Refund => Some((RentReceivable, Cash)),
+            JournalEntryKind::WriteOff => Some((BadDebt, RentReceivable)),
+            JournalEntryKind::DepositReceived => Some((Cash, DepositsHeld)),
+            JournalEntryKind::DepositReturned => Some((DepositsHeld, Cash)),
+        }
+    }
+}
+
+// --- New Journal Entry (Ledger Input) ---
+// One business event, posted as lines that must balance.
+#[derive(Debug, Clone)]
+pub struct NewJournalEntry {
+    pub kind: JournalEntryKind,
+    pub user_id: Uuid,
+    pub property_id: Option<Uuid>,
+    pub currency: String,
+    pub entry_date: Date,
+    pub memo: Option<String>,
+    pub payment_id: Option<Uuid>,
+    pub receipt_id: Option<Uuid>,
+    pub created_by: Option<Uuid>, // Who recorded a receipt, waiver or manual entry
+    pub lines: Vec<JournalLine>,
+}
+
+impl NewJournalEntry {
+    /// Whether the debits and credits add up to the same amount.
+    pub fn is_balanced(&self) -> bool {
+        let debits: BigDecimal = self.lines.iter().map(|line| &line.debit).sum();
+        let credits: BigDecimal = self.lines.iter().map(|line| &line.credit).sum();
+        debits == credits
+    }
+}
+
+// --- Journal Entry Model (Database Representation) ---
+#[derive(Debug, Serialize, Clone)]
+pub struct JournalEntry {
+    pub id: Uuid,
+    pub kind: JournalEntryKind,
+    pub user_id: Uuid,
+    pub property_id: Option<Uuid>,
+    pub currency: String,
+    #[serde(with = "iso_date")]
+    pub entry_date: Date,
+    pub memo: Option<String>,
+    pub payment_id: Option<Uuid>,
+    pub receipt_id: Option<Uuid>,
+    pub created_by: Option<Uuid>,
+    pub created_at: OffsetDateTime,
+    pub lines: Vec<JournalLine>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct JournalLine {
+    pub account: LedgerAccount,
+    pub debit: BigDecimal,
+    pub credit: BigDecimal,
+}
+
+impl JournalLine {
+    /// The two lines moving `amount` from `credit` to `debit`, the shape of every
+    /// entry the API posts.
+    pub fn transfer(
+        debit: LedgerAccount,
+        credit: LedgerAccount,
+        amount: &BigDecimal,
+    ) -> Vec<JournalLine> {
+        vec![
+            JournalLine::debit(debit, amount.clone()),
+            JournalLine::credit(credit, amount.clone()),
+        ]
+    }
+
+    pub fn debit(account: LedgerAccount, amount: BigDecimal) -> Self {
+        JournalLine {
+            account,
+            debit: amount,
+            credit: BigDecimal::from(0),
+        }
+    }
+
+    pub fn credit(account: LedgerAccount, amount: BigDecimal) -> Self {
+        JournalLine {
+            account,
+            debit: BigDecimal::from(0),
+            credit: amount,
+        }
+    }
+}
+
+// --- Ledger Statement (API Response Body) ---
+
+// One line of an account, with the balance after it
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct LedgerLine {
+    pub entry_id: Uuid,
+    #[serde(with = "iso_date")]
+    pub entry_date: Date,
+    pub kind: JournalEntryKind,
+    pub memo: Option<String>,
+    pub user_id: Uuid,
+    pub property_id: Option<Uuid>,
+    pub payment_id: Option<Uuid>,
+    pub receipt_id: Option<Uuid>,
+    pub debit: BigDecimal,
+    pub credit: BigDecimal,
+    pub balance: BigDecimal, // Of the account after this line
+}
+
+#[derive(Debug, Serialize)]
+pub struct LedgerStatement {
+    pub account: LedgerAccount,
+    pub currency: String,
+    #[serde(with = "iso_date::option")]
+    pub from: Option<Date>,
+    #[serde(with = "iso_date")]
+    pub to: Date,
+    pub opening_balance: BigDecimal, // Before `from`
+    pub closing_balance: BigDecimal, // At the end of `to`
+    pub lines: Vec<LedgerLine>,
+}
+
+// For picking the statement's account and period (Query Parameters)
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LedgerQuery {
+    #[serde(default, with = "iso_date::option")]
+    pub from: Option<Date>, // Defaults to the first entry
+    #[serde(default, with = "iso_date::option")]
+    pub to: Option<Date>, // Defaults to today
+    pub account: Option<LedgerAccount>, // Defaults to rent receivable
+    pub currency: Option<String>,       // Defaults to USD
+    pub user_id: Option<Uuid>,          // Narrows a property's statement to one tenant
+    pub property_id: Option<Uuid>,      // Narrows a tenant's statement to one property
+}
+
+// For posting a credit, refund, write-off or deposit by hand (API Request Body)
+#[derive(Debug, Deserialize)]
+pub struct CreateLedgerEntry {
+    pub kind: JournalEntryKind,
+    pub user_id: Uuid,
+    pub property_id: Uuid,
+    pub amount: BigDecimal,
+    pub currency: String,
+    #[serde(default, with = "iso_date::option")]
+    pub entry_date: Option<Date>, // Defaults to today
+    pub memo: String,
+    pub payment_id: Option<Uuid>, // The charge credited, refunded or written off
+}
+
+impl Validate for CreateLedgerEntry {
+    fn validate(&self, v: &mut Validator) {
+        v.check(
+            self.kind.is_manual(),
+            "kind",
+            "invalid",
+            "Charges and receipts are posted by recording payments and receipts",
+        );
+        v.check(
+            self.kind != JournalEntryKind::Credit || self.payment_id.is_some(),
+            "payment_id",
+            "required",
+            "Credits must name the payment they reduce",
+        );
+        v.check(
+            self.kind.takes_payment() || self.payment_id.is_none(),
+            "payment_id",
+            "invalid",
+            "Deposits are not posted against a payment",
+        );
+        v.amount("amount", &self.amount);
+        v.currency("currency", &self.currency);
+        v.length("memo", &self.memo, 1, 500);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::date;
+
+    #[test]
+    fn every_manual_entry_balances_and_moves_two_different_accounts() {
+        for kind in [
+            JournalEntryKind::Credit,
+            JournalEntryKind::Refund,
+            JournalEntryKind::WriteOff,
+            JournalEntryKind::DepositReceived,
+            JournalEntryKind::DepositReturned,
+        ] {
+            let (debit, credit) = kind.manual_accounts(PaymentKind::Rent).unwrap();
+            assert_ne!(debit, credit, "{:?}", kind);
+
+            let entry = NewJournalEntry {
+                kind,
+                user_id: Uuid::new_v4(),
+                property_id: None,
+                currency: "USD".into(),
+                entry_date: date!(2025 - 06 - 01),
+                memo: None,
+                payment_id: None,
+                receipt_id: None,
+                created_by: None,
+                lines: JournalLine::transfer(debit, credit, &BigDecimal::from(250)),
+            };
+            assert!(entry.is_balanced(), "{:?}", kind);
+        }
+        assert_eq!(
+            JournalEntryKind::Charge.manual_accounts(PaymentKind::Rent),
+            None
+        );
+        assert_eq!(
+            JournalEntryKind::Receipt.manual_accounts(PaymentKind::Rent),
+            None
+        );
+    }
+
+    #[test]
+    fn a_credit_reverses_the_income_of_the_charge_it_reduces() {
+        assert_eq!(
+            JournalEntryKind::Credit.manual_accounts(PaymentKind::Rent),
+            Some((LedgerAccount::RentIncome, LedgerAccount::RentReceivable))
+        );
+        assert_eq!(
+            JournalEntryKind::Credit.manual_accounts(PaymentKind::LateFee),
+            Some((LedgerAccount::FeeIncome, LedgerAccount::RentReceivable))
+        );
+    }
+}
@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::AuthResponse;
+use crate::validation::Validate;
+
+// --- MFA DTOs ---
+
+// Returned when TOTP enrollment starts; the URI is usually rendered as a QR code (API Response Body)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TotpEnrollmentResponse {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+// For confirming or disabling TOTP with a current code (API Request Body)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TotpCodeRequest {
+    pub code: String,
+}
+
+impl Validate for TotpCodeRequest {}
+
+// Recovery codes, shown to the user exactly once (API Response Body)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecoveryCodesResponse {
+    pub recovery_codes: Vec<String>,
+}
+
+// Returned by `/login` instead of tokens when a second factor is required (API Response Body)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MfaChallengeResponse {
+    pub mfa_required: bool,
+    pub mfa_token: String, // Exchanged at `/login/mfa` together with a code
+    pub expires_in: i64,
+}
+
+// For completing a login with a TOTP code or a recovery code (API Request Body)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MfaLoginRequest {
+    pub mfa_token: String,
+    pub code: Option<String>,
+    pub recovery_code: Option<String>,
+}
+
+impl Validate for MfaLoginRequest {}
+
+// Response from `/login`: either a full session or an MFA challenge (API Response Body)
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum LoginResponse {
+    Authenticated(AuthResponse),
+    MfaRequired(MfaChallengeResponse),
+}
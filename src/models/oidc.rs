@@ -0,0 +1,21 @@
+use serde::Deserialize;
+use sqlx::FromRow;
+
+// --- OIDC Login State (Database Representation) ---
+// What we need to finish an authorization request once the provider redirects back.
+#[derive(Debug, FromRow)]
+pub struct OidcLoginState {
+    pub nonce: String,
+    pub code_verifier: String,
+}
+
+// --- OIDC DTOs ---
+
+// For the provider's redirect back to `/auth/oidc/callback` (Query Parameters)
+#[derive(Debug, Deserialize)]
+pub struct OidcCallbackParams {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    pub error: Option<String>,
+    pub error_description: Option<String>,
+}
@@ -0,0 +1,226 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use time::format_description::well_known::Rfc3339;
+use time::{Date, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::errors::{AppError, FieldError};
+
+/// Page size used when the client does not ask for one.
+pub const DEFAULT_PAGE_SIZE: i64 = 50;
+/// Largest page a client can ask for.
+pub const MAX_PAGE_SIZE: i64 = 100;
+
+// --- Sort Direction ---
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+}
+
+// --- Paginated Response ---
+// One page of a list endpoint. Pass `next_cursor` back as `cursor` for the next page;
+// it is `null` on the last page.
+#[derive(Debug, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+impl<T> Page<T> {
+    /// Builds a page from up to `limit + 1` rows; the extra row only signals that
+    /// another page exists.
+    pub fn from_rows(mut rows: Vec<T>, limit: i64, cursor: impl Fn(&T) -> Cursor) -> Self {
+        let has_more = rows.len() as i64 > limit;
+        rows.truncate(limit as usize);
+        let next_cursor = has_more
+            .then(|| rows.last().map(|row| cursor(row).encode()))
+            .flatten();
+
+        Page {
+            items: rows,
+            next_cursor,
+        }
+    }
+}
+
+/// Clamps a requested page size to `1..=MAX_PAGE_SIZE`.
+pub fn page_limit(requested: Option<i64>) -> i64 {
+    requested
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE)
+}
+
+// --- Keyset Cursor ---
+// The sort key and id of the last row of a page. Clients treat the encoded form as opaque.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Cursor {
+    pub sort: String,
+    pub order: SortOrder,
+    pub value: String, // Sort key of the last row, as Postgres text
+    pub id: Uuid,      // Breaks ties between rows with the same sort key
+}
+
+impl Cursor {
+    pub fn encode(&self) -> String {
+        // Serializing a struct of strings and a uuid cannot fail
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    /// Decodes a cursor, checking it was issued for the same sort field and order and
+    /// that its value is of the Postgres type `cast` it will be cast to, so a tampered
+    /// cursor is refused here rather than by the database.
+    pub fn decode(
+        encoded: &str,
+        sort: &str,
+        cast: &str,
+        order: SortOrder,
+    ) -> Result<Self, AppError> {
+        let invalid = || {
+            AppError::InvalidQuery(vec![FieldError::new(
+                "cursor",
+                "invalid",
+                "Cursor is not one this API issued",
+            )])
+        };
+        let cursor: Cursor = URL_SAFE_NO_PAD
+            .decode(encoded)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or_else(invalid)?;
+
+        if cursor.sort != sort || cursor.order != order {
+            return Err(AppError::InvalidQuery(vec![FieldError::new(
+                "cursor",
+                "mismatch",
+                "Cursor was issued for a different sort order",
+            )]));
+        }
+
+        let parses = match cast {
+            "timestamptz" => OffsetDateTime::parse(&cursor.value, &Rfc3339).is_ok(),
+            "date" => {
+                let format = time::macros::format_description!("[year]-[month]-[day]");
+                cursor.value == "infinity" || Date::parse(&cursor.value, &format).is_ok()
+            }
+            "numeric" => BigDecimal::from_str(&cursor.value).is_ok(),
+            _ => true,
+        };
+        if !parses {
+            return Err(invalid());
+        }
+
+        Ok(cursor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encoded(value: &str) -> String {
+        Cursor {
+            sort: "due_date".into(),
+            order: SortOrder::Asc,
+            value: value.into(),
+            id: Uuid::nil(),
+        }
+        .encode()
+    }
+
+    #[test]
+    fn cursor_values_must_parse_as_the_sort_key_type() {
+        for (value, cast, valid) in [
+            ("2025-06-01", "date", true),
+            ("infinity", "date", true),
+            ("yesterday", "date", false),
+            ("2025-06-01T12:00:00Z", "timestamptz", true),
+            ("2025-06-01", "timestamptz", false),
+            ("1250.50", "numeric", true),
+            ("1,250", "numeric", false),
+            ("anything at all", "text", true),
+        ] {
+            let decoded = Cursor::decode(&encoded(value), "due_date", cast, SortOrder::Asc);
+            assert_eq!(decoded.is_ok(), valid, "{:?} as {}", value, cast);
+        }
+    }
+
+    #[test]
+    fn cursors_decode_to_what_was_encoded() {
+        let id = Uuid::new_v4();
+        let cursor = Cursor {
+            sort: "amount".into(),
+            order: SortOrder::Desc,
+            value: "1250.50".into(),
+            id,
+        };
+
+        let decoded =
+            Cursor::decode(&cursor.encode(), "amount", "numeric", SortOrder::Desc).unwrap();
+        assert_eq!(decoded.sort, "amount");
+        assert_eq!(decoded.order, SortOrder::Desc);
+        assert_eq!(decoded.value, "1250.50");
+        assert_eq!(decoded.id, id);
+    }
+
+    #[test]
+    fn tampered_and_mismatched_cursors_are_refused() {
+        let code = |result: Result<Cursor, AppError>| match result {
+            Err(AppError::InvalidQuery(errors)) => errors[0].code,
+            other => panic!("expected an invalid query, got {:?}", other),
+        };
+
+        let mut tampered = encoded("2025-06-01");
+        tampered.insert(3, '!');
+        assert_eq!(
+            code(Cursor::decode(
+                &tampered,
+                "due_date",
+                "date",
+                SortOrder::Asc
+            )),
+            "invalid"
+        );
+        let not_a_cursor = URL_SAFE_NO_PAD.encode(br#"{"sort":"due_date"}"#);
+        assert_eq!(
+            code(Cursor::decode(
+                &not_a_cursor,
+                "due_date",
+                "date",
+                SortOrder::Asc
+            )),
+            "invalid"
+        );
+
+        assert_eq!(
+            code(Cursor::decode(
+                &encoded("2025-06-01"),
+                "amount",
+                "numeric",
+                SortOrder::Asc
+            )),
+            "mismatch"
+        );
+        assert_eq!(
+            code(Cursor::decode(
+                &encoded("2025-06-01"),
+                "due_date",
+                "date",
+                SortOrder::Desc
+            )),
+            "mismatch"
+        );
+    }
+}
@@ -1,18 +1,82 @@
 use bigdecimal::BigDecimal;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use time::format_description::well_known::Rfc3339;
 use time::{Date, OffsetDateTime};
 use uuid::Uuid;
 
+use crate::models::{LedgerAccount, SortOrder};
+use crate::validation::{Validate, Validator};
+
+// Dates in query parameters and lease bodies are written as `YYYY-MM-DD`
+time::serde::format_description!(pub iso_date, Date, "[year]-[month]-[day]");
+
 // --- Payment ENUM (Database Representation) ---
+// Database and JSON share the same snake_case names, e.g. `partially_paid`.
 #[derive(Clone, Debug, PartialEq, sqlx::Type, Deserialize, Serialize)]
-#[sqlx(type_name = "payment_status", rename_all = "lowercase")]
+#[sqlx(type_name = "payment_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
 pub enum PaymentStatus {
     Pending,
     Completed,
     Failed,
     Overdue,
     PartiallyPaid,
+    Waived, // A late fee that is no longer owed; only set by waiving it
+}
+
+impl PaymentStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PaymentStatus::Pending => "pending",
+            PaymentStatus::Completed => "completed",
+            PaymentStatus::Failed => "failed",
+            PaymentStatus::Overdue => "overdue",
+            PaymentStatus::PartiallyPaid => "partially_paid",
+            PaymentStatus::Waived => "waived",
+        }
+    }
+
+    /// Whether a payment may move from this status to `next`.
+    ///
+    /// Completed and waived payments are final. Failed payments can only be retried
+    /// by returning them to pending, and a partially paid payment can take further
+    /// partial amounts until it is completed. Fees are waived through their own
+    /// endpoint, so no transition here leads to waived.
+    ///
+    /// Receipts move payments to partially paid and completed as they are allocated,
+    /// so requests cannot set those two directly.
+    pub fn can_transition_to(&self, next: &PaymentStatus) -> bool {
+        use PaymentStatus::*;
+
+        matches!(
+            (self, next),
+            (Pending, Completed | Failed | Overdue | PartiallyPaid)
+                | (Overdue, Completed | Failed | PartiallyPaid)
+                | (PartiallyPaid, Completed | Failed | Overdue | PartiallyPaid)
+                | (Failed, Pending)
+        )
+    }
+}
+
+// --- Payment Kind ENUM (Database Representation) ---
+#[derive(Clone, Copy, Debug, PartialEq, Eq, sqlx::Type, Deserialize, Serialize)]
+#[sqlx(type_name = "payment_kind", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentKind {
+    Manual,  // Recorded through the API
+    Rent,    // Generated from a lease by the rent scheduler
+    LateFee, // Charged for paying the parent payment late
+}
+
+impl PaymentKind {
+    /// The ledger account credited with what a charge of this kind earns.
+    pub fn income_account(&self) -> LedgerAccount {
+        match self {
+            PaymentKind::Manual | PaymentKind::Rent => LedgerAccount::RentIncome,
+            PaymentKind::LateFee => LedgerAccount::FeeIncome,
+        }
+    }
 }
 
 // --- Payment Model (Database Representation) ---
@@ -22,6 +86,8 @@ pub struct Payment {
     pub user_id: Uuid,
     pub property_id: Option<Uuid>,
     pub amount: BigDecimal,
+    pub amount_paid: BigDecimal, // Sum of the receipts allocated to it
+    pub balance_due: BigDecimal, // `amount` less `amount_paid`
     pub currency: String,
     pub status: PaymentStatus,
     pub notes: Option<String>,
@@ -29,6 +95,9 @@ pub struct Payment {
     pub due_date: Option<Date>,
     pub period_start: Option<Date>,
     pub period_end: Option<Date>,
+    pub kind: PaymentKind,
+    pub lease_id: Option<Uuid>, // The lease a rent charge was generated from
+    pub parent_payment_id: Option<Uuid>, // The late payment a late fee is charged for
     pub created_at: OffsetDateTime,
     pub updated_at: OffsetDateTime,
 }
@@ -48,6 +117,106 @@ pub struct CreatePayment {
     pub period_end: Option<Date>,
 }
 
+impl Validate for CreatePayment {
+    fn validate(&self, v: &mut Validator) {
+        v.amount("amount", &self.amount);
+        v.currency("currency", &self.currency);
+        if let Some(notes) = &self.notes {
+            v.length("notes", notes, 0, 1000);
+        }
+        match (self.period_start, self.period_end) {
+            (Some(start), Some(end)) => v.check(
+                end >= start,
+                "period_end",
+                "range",
+                "Must not be before period_start",
+            ),
+            (Some(_), None) => v.check(
+                false,
+                "period_end",
+                "required",
+                "Required when period_start is given",
+            ),
+            (None, Some(_)) => v.check(
+                false,
+                "period_start",
+                "required",
+                "Required when period_end is given",
+            ),
+            (None, None) => {}
+        }
+    }
+}
+
+// For a rent charge generated from a lease by the rent scheduler
+#[derive(Debug, Clone)]
+pub struct NewRentCharge {
+    pub lease_id: Uuid,
+    pub user_id: Uuid,
+    pub property_id: Uuid,
+    pub amount: BigDecimal,
+    pub currency: String,
+    pub notes: String,
+    pub due_date: Date,
+    pub period_start: Date,
+    pub period_end: Date,
+}
+
+// For moving a payment to a new status (API Request Body)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdatePaymentStatus {
+    pub status: PaymentStatus,
+    pub reason: Option<String>, // Kept in the payment's status history
+    pub transaction_id: Option<String>, // The provider's reference for a failed charge
+}
+
+impl Validate for UpdatePaymentStatus {
+    fn validate(&self, v: &mut Validator) {
+        v.check(
+            !matches!(
+                self.status,
+                PaymentStatus::Completed | PaymentStatus::PartiallyPaid
+            ),
+            "status",
+            "invalid",
+            "Follows from the receipts allocated to the payment; record a receipt instead",
+        );
+        if let Some(reason) = &self.reason {
+            v.length("reason", reason, 1, 500);
+        }
+        if let Some(transaction_id) = &self.transaction_id {
+            v.length("transaction_id", transaction_id, 1, 255);
+            v.check(
+                self.status == PaymentStatus::Failed,
+                "transaction_id",
+                "invalid",
+                "Only recorded when marking a payment failed; receipts carry the rest",
+            );
+        }
+    }
+}
+
+// --- Overdue Balance (API Response Body) ---
+// What one tenant owes for one property across their overdue payments.
+#[derive(Debug, FromRow, Serialize)]
+pub struct OverdueBalance {
+    pub user_id: Uuid,
+    pub property_id: Option<Uuid>,
+    pub currency: String,
+    pub payments: i64,           // Overdue payments making up the balance
+    pub outstanding: BigDecimal, // Sum of their `balance_due`
+    #[serde(with = "iso_date")]
+    pub oldest_due_date: Date,
+}
+
+// For filtering the overdue balances (Query Parameters)
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OverdueBalancesQuery {
+    pub user_id: Option<Uuid>,
+    pub property_id: Option<Uuid>,
+}
+
 // For a simplified payment response (API Response Body)
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PaymentResponse {
@@ -55,6 +224,8 @@ pub struct PaymentResponse {
     pub user_id: Uuid,
     pub property_id: Option<Uuid>,
     pub amount: BigDecimal,
+    pub amount_paid: BigDecimal, // Sum of the receipts allocated to it
+    pub balance_due: BigDecimal, // `amount` less `amount_paid`
     pub currency: String,
     pub status: PaymentStatus,
     pub notes: Option<String>,
@@ -62,4 +233,120 @@ pub struct PaymentResponse {
     pub due_date: Option<Date>,
     pub period_start: Option<Date>,
     pub period_end: Option<Date>,
+    pub kind: PaymentKind,
+    pub lease_id: Option<Uuid>,
+    pub parent_payment_id: Option<Uuid>,
+}
+
+// --- Payment Sort Field ---
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentSortField {
+    #[default]
+    CreatedAt,
+    DueDate,
+    Amount,
+}
+
+impl PaymentSortField {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PaymentSortField::CreatedAt => "created_at",
+            PaymentSortField::DueDate => "due_date",
+            PaymentSortField::Amount => "amount",
+        }
+    }
+
+    /// The SQL expression rows are ordered by, and the type cursor values are cast to.
+    /// Payments without a due date sort as if due at the end of time.
+    pub fn sql(&self) -> (&'static str, &'static str) {
+        match self {
+            PaymentSortField::CreatedAt => ("created_at", "timestamptz"),
+            PaymentSortField::DueDate => ("COALESCE(due_date, 'infinity'::date)", "date"),
+            PaymentSortField::Amount => ("amount", "numeric"),
+        }
+    }
+
+    /// The row's sort key as Postgres text, for the cursor.
+    pub fn cursor_value(&self, payment: &Payment) -> String {
+        match self {
+            PaymentSortField::CreatedAt => payment.created_at.format(&Rfc3339).unwrap_or_default(),
+            PaymentSortField::DueDate => payment
+                .due_date
+                .map_or_else(|| "infinity".to_string(), |date| date.to_string()),
+            PaymentSortField::Amount => payment.amount.to_string(),
+        }
+    }
+}
+
+// For filtering, sorting and paging the payment list (Query Parameters)
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ListPaymentsQuery {
+    pub user_id: Option<Uuid>,
+    pub property_id: Option<Uuid>,
+    pub status: Option<PaymentStatus>,
+    pub currency: Option<String>,
+    #[serde(default, with = "iso_date::option")]
+    pub due_from: Option<Date>,
+    #[serde(default, with = "iso_date::option")]
+    pub due_to: Option<Date>,
+    #[serde(default, with = "iso_date::option")]
+    pub period_start_from: Option<Date>,
+    #[serde(default, with = "iso_date::option")]
+    pub period_start_to: Option<Date>,
+    pub amount_min: Option<BigDecimal>,
+    pub amount_max: Option<BigDecimal>,
+    pub sort: Option<PaymentSortField>,
+    pub order: Option<SortOrder>, // Defaults to descending
+    pub limit: Option<i64>,
+    pub cursor: Option<String>,
+}
+
+impl ListPaymentsQuery {
+    /// The requested sort field and order, newest first by default.
+    pub fn sort_order(&self) -> (PaymentSortField, SortOrder) {
+        (
+            self.sort.unwrap_or_default(),
+            self.order.unwrap_or(SortOrder::Desc),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_documented_status_changes_are_allowed() {
+        use PaymentStatus::*;
+
+        let allowed = [
+            (Pending, Completed),
+            (Pending, Failed),
+            (Pending, Overdue),
+            (Pending, PartiallyPaid),
+            (Overdue, Completed),
+            (Overdue, Failed),
+            (Overdue, PartiallyPaid),
+            (PartiallyPaid, Completed),
+            (PartiallyPaid, Failed),
+            (PartiallyPaid, Overdue),
+            (PartiallyPaid, PartiallyPaid),
+            (Failed, Pending),
+        ];
+        let all = [Pending, Completed, Failed, Overdue, PartiallyPaid, Waived];
+        for from in &all {
+            for to in &all {
+                let expected = allowed.contains(&(from.clone(), to.clone()));
+                assert_eq!(
+                    from.can_transition_to(to),
+                    expected,
+                    "{} -> {}",
+                    from.as_str(),
+                    to.as_str()
+                );
+            }
+        }
+    }
 }
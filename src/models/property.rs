@@ -1,17 +1,25 @@
 use bigdecimal::BigDecimal;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use sqlx::FromRow;
 use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
 use uuid::Uuid;
 
+use crate::models::SortOrder;
+use crate::validation::{Validate, Validator};
+
 // --- Property Model (Database Representation) ---
+// Read from the `property_details` view; the tenants and rent come from the lease
+// in effect today and are empty while the property is vacant.
 #[derive(Debug, FromRow, Serialize, Deserialize, Clone)]
 pub struct Property {
     pub id: Uuid,
     pub address: String,
     pub unit_number: Option<String>,
-    pub current_rent_amount: BigDecimal,
-    pub current_tenant_id: Option<Uuid>, // Foreign key to the users table
+    pub owner_id: Option<Uuid>, // Landlord who manages the property
+    pub current_lease_id: Option<Uuid>,
+    pub current_rent_amount: Option<BigDecimal>,
+    pub current_tenant_ids: Vec<Uuid>,
     pub created_at: OffsetDateTime,
     pub updated_at: OffsetDateTime,
 }
@@ -23,6 +31,112 @@ pub struct Property {
 pub struct CreateProperty {
     pub address: String,
     pub unit_number: Option<String>,
-    pub current_rent_amount: BigDecimal,
-    pub current_tenant_id: Option<Uuid>,
+    pub owner_id: Option<Uuid>, // Admins only; landlords always own what they create
+}
+
+impl Validate for CreateProperty {
+    fn validate(&self, v: &mut Validator) {
+        v.length("address", &self.address, 1, 255);
+        if let Some(unit_number) = &self.unit_number {
+            v.length("unit_number", unit_number, 1, 32);
+        }
+    }
+}
+
+// For partially updating a property (API Request Body)
+// Nullable fields use `Option<Option<_>>`: absent leaves the value alone, `null` clears it.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateProperty {
+    pub address: Option<String>,
+    #[serde(default, deserialize_with = "double_option")]
+    pub unit_number: Option<Option<String>>,
+}
+
+impl Validate for UpdateProperty {
+    fn validate(&self, v: &mut Validator) {
+        if let Some(address) = &self.address {
+            v.length("address", address, 1, 255);
+        }
+        if let Some(Some(unit_number)) = &self.unit_number {
+            v.length("unit_number", unit_number, 1, 32);
+        }
+    }
+}
+
+/// Deserializes a field that is present (even as `null`) to `Some`, so "absent"
+/// and "set to null" can be told apart.
+pub fn double_option<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+// --- Property Sort Field ---
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PropertySortField {
+    #[default]
+    Address,
+    CreatedAt,
+    Rent,
+}
+
+impl PropertySortField {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PropertySortField::Address => "address",
+            PropertySortField::CreatedAt => "created_at",
+            PropertySortField::Rent => "rent",
+        }
+    }
+
+    /// The SQL expression rows are ordered by, and the type cursor values are cast to.
+    /// Vacant properties sort as if their rent were zero.
+    pub fn sql(&self) -> (&'static str, &'static str) {
+        match self {
+            PropertySortField::Address => ("address", "text"),
+            PropertySortField::CreatedAt => ("created_at", "timestamptz"),
+            PropertySortField::Rent => ("COALESCE(current_rent_amount, 0)", "numeric"),
+        }
+    }
+
+    /// The row's sort key as Postgres text, for the cursor.
+    pub fn cursor_value(&self, property: &Property) -> String {
+        match self {
+            PropertySortField::Address => property.address.clone(),
+            PropertySortField::CreatedAt => {
+                property.created_at.format(&Rfc3339).unwrap_or_default()
+            }
+            PropertySortField::Rent => property
+                .current_rent_amount
+                .as_ref()
+                .map_or_else(|| "0".to_string(), ToString::to_string),
+        }
+    }
+}
+
+// For filtering, sorting and paging the property list (Query Parameters)
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ListPropertiesQuery {
+    pub q: Option<String>, // Case-insensitive search within the address
+    pub has_tenant: Option<bool>,
+    pub rent_min: Option<BigDecimal>,
+    pub rent_max: Option<BigDecimal>,
+    pub sort: Option<PropertySortField>,
+    pub order: Option<SortOrder>, // Defaults to ascending
+    pub limit: Option<i64>,
+    pub cursor: Option<String>,
+}
+
+impl ListPropertiesQuery {
+    /// The requested sort field and order, by address by default.
+    pub fn sort_order(&self) -> (PropertySortField, SortOrder) {
+        (
+            self.sort.unwrap_or_default(),
+            self.order.unwrap_or(SortOrder::Asc),
+        )
+    }
 }
@@ -0,0 +1,123 @@
+use bigdecimal::BigDecimal;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use time::{Date, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::models::iso_date;
+use crate::validation::{Validate, Validator};
+
+/// Most charges one receipt can be allocated against explicitly.
+pub const MAX_RECEIPT_ALLOCATIONS: usize = 100;
+
+// --- Receipt Model (Database Representation) ---
+// Money received from a tenant, allocated against the charges it pays.
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct Receipt {
+    pub id: Uuid,
+    pub user_id: Uuid,             // Who paid
+    pub property_id: Option<Uuid>, // `null` only for receipts recorded before receipts existed
+    pub amount: BigDecimal,
+    pub unallocated: BigDecimal, // Not yet allocated to any charge, e.g. paid in advance
+    pub currency: String,
+    #[serde(with = "iso_date")]
+    pub received_on: Date,
+    pub transaction_id: Option<String>,
+    pub notes: Option<String>,
+    pub recorded_by: Option<Uuid>,
+    pub created_at: OffsetDateTime,
+}
+
+// --- Payment Allocation Model (Database Representation) ---
+// The part of a receipt that went towards one charge.
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct PaymentAllocation {
+    pub receipt_id: Uuid,
+    pub payment_id: Uuid,
+    pub amount: BigDecimal,
+    pub created_at: OffsetDateTime,
+}
+
+// For a receipt along with its allocations (API Response Body)
+#[derive(Debug, Serialize)]
+pub struct ReceiptDetails {
+    #[serde(flatten)]
+    pub receipt: Receipt,
+    pub allocations: Vec<PaymentAllocation>,
+}
+
+// --- Receipt DTOs ---
+
+// For recording money received (API Request Body)
+#[derive(Debug, Deserialize)]
+pub struct CreateReceipt {
+    pub user_id: Uuid,
+    pub property_id: Uuid,
+    pub amount: BigDecimal,
+    pub currency: String,
+    #[serde(default, with = "iso_date::option")]
+    pub received_on: Option<Date>, // Defaults to today
+    pub transaction_id: Option<String>,
+    pub notes: Option<String>,
+    pub allocations: Option<Vec<NewAllocation>>, // Defaults to the oldest charges first
+}
+
+// One charge a receipt pays towards, and how much of it
+#[derive(Debug, Deserialize)]
+pub struct NewAllocation {
+    pub payment_id: Uuid,
+    pub amount: BigDecimal,
+}
+
+impl Validate for CreateReceipt {
+    fn validate(&self, v: &mut Validator) {
+        v.amount("amount", &self.amount);
+        v.currency("currency", &self.currency);
+        if let Some(transaction_id) = &self.transaction_id {
+            v.length("transaction_id", transaction_id, 1, 255);
+        }
+        if let Some(notes) = &self.notes {
+            v.length("notes", notes, 0, 1000);
+        }
+
+        let Some(allocations) = &self.allocations else {
+            return;
+        };
+        v.check(
+            allocations.len() <= MAX_RECEIPT_ALLOCATIONS,
+            "allocations",
+            "length",
+            &format!("Must list at most {} allocations", MAX_RECEIPT_ALLOCATIONS),
+        );
+        for allocation in allocations {
+            v.amount("allocations.amount", &allocation.amount);
+        }
+        v.check(
+            allocations.iter().enumerate().all(|(i, allocation)| {
+                allocations[..i]
+                    .iter()
+                    .all(|other| other.payment_id != allocation.payment_id)
+            }),
+            "allocations",
+            "duplicate",
+            "Must not list the same payment twice",
+        );
+        let allocated: BigDecimal = allocations.iter().map(|a| &a.amount).sum();
+        v.check(
+            allocated <= self.amount,
+            "allocations",
+            "range",
+            "Must not add up to more than the amount received",
+        );
+    }
+}
+
+// For filtering and paging the receipt list (Query Parameters)
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ListReceiptsQuery {
+    pub user_id: Option<Uuid>,
+    pub property_id: Option<Uuid>,
+    pub limit: Option<i64>,
+    pub cursor: Option<String>,
+}
@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::validation::{Validate, Validator};
+
+// --- User Token Purpose ENUM (Database Representation) ---
+#[derive(Clone, Copy, Debug, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "user_token_purpose", rename_all = "snake_case")]
+pub enum UserTokenPurpose {
+    EmailVerification,
+    PasswordReset,
+}
+
+// --- Refresh Token Model (Database Representation) ---
+#[derive(Debug, FromRow, Clone)]
+pub struct RefreshToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub family_id: Uuid,
+    pub expires_at: OffsetDateTime,
+    pub used_at: Option<OffsetDateTime>,
+    pub revoked_at: Option<OffsetDateTime>,
+}
+
+// --- Refresh Token DTOs ---
+
+// For exchanging or revoking a refresh token (API Request Body)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
+}
+
+impl Validate for RefreshTokenRequest {}
+
+// For consuming an emailed verification token (API Request Body)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VerifyEmailRequest {
+    pub token: String,
+}
+
+impl Validate for VerifyEmailRequest {}
+
+// For requesting a password reset email (API Request Body)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ForgotPasswordRequest {
+    pub email: String,
+}
+
+impl Validate for ForgotPasswordRequest {}
+
+// For setting a new password with an emailed reset token (API Request Body)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResetPasswordRequest {
+    pub token: String,
+    pub new_password: String,
+}
+
+impl Validate for ResetPasswordRequest {
+    fn validate(&self, v: &mut Validator) {
+        v.password("new_password", &self.new_password);
+    }
+}
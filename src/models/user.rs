@@ -3,12 +3,36 @@ use sqlx::FromRow;
 use time::OffsetDateTime;
 use uuid::Uuid;
 
+use crate::validation::{Validate, Validator};
+
+// --- User Role ENUM (Database Representation) ---
+#[derive(Clone, Copy, Debug, PartialEq, Eq, sqlx::Type, Deserialize, Serialize)]
+#[sqlx(type_name = "user_role", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum UserRole {
+    Admin,
+    Landlord, // Landlords and property managers
+    Tenant,
+}
+
 // --- User Model (Database Representation) ---
 #[derive(Debug, FromRow, Serialize, Deserialize, Clone)]
 pub struct User {
     pub id: Uuid,
     pub username: String,
+    #[serde(skip_serializing)] // Never send the hash to clients, even by accident
     pub password_hash: String,
+    pub role: UserRole,
+    pub email: Option<String>, // Nullable for accounts created before emails were collected
+    pub full_name: Option<String>,
+    pub phone: Option<String>,
+    pub email_verified_at: Option<OffsetDateTime>,
+    pub failed_login_attempts: i32,
+    pub locked_until: Option<OffsetDateTime>, // Logins are refused until this time
+    #[serde(skip_serializing)]
+    pub totp_secret: Option<String>,
+    pub totp_enabled_at: Option<OffsetDateTime>, // Set once TOTP enrollment is confirmed
+    pub totp_last_used_step: Option<i64>,
     pub created_at: OffsetDateTime,
     pub updated_at: OffsetDateTime,
 }
@@ -19,20 +43,100 @@ pub struct User {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RegisterUser {
     pub username: String,
+    pub email: String,
     pub password: String,
+    pub full_name: Option<String>,
+    pub phone: Option<String>,
+    pub role: Option<UserRole>, // Only tenant may be self-registered; admins grant the others
+}
+
+impl Validate for RegisterUser {
+    fn validate(&self, v: &mut Validator) {
+        v.length("username", &self.username, 3, 32);
+        v.check(
+            self.username
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-')),
+            "username",
+            "format",
+            "May only contain letters, digits, '.', '_' and '-'",
+        );
+        v.email("email", &self.email);
+        v.password("password", &self.password);
+        if let Some(full_name) = &self.full_name {
+            v.length("full_name", full_name, 1, 100);
+        }
+        if let Some(phone) = &self.phone {
+            v.length("phone", phone, 5, 32);
+            v.check(
+                phone
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || matches!(c, '+' | '-' | ' ' | '(' | ')')),
+                "phone",
+                "format",
+                "May only contain digits, spaces and '+', '-', '(' or ')'",
+            );
+        }
+    }
 }
 
+// For an admin changing a user's role (API Request Body)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateUserRole {
+    pub role: UserRole,
+}
+
+impl Validate for UpdateUserRole {}
+
 // For user login (API Request Body)
 #[derive(Debug, Serialize, Deserialize)]
 pub struct LoginUser {
+    #[serde(alias = "email", alias = "login")] // Either a username or an email address
     pub username: String,
     pub password: String,
 }
 
+impl Validate for LoginUser {}
+
+// Public view of a user, safe to return from any endpoint (API Response Body)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UserResponse {
+    pub id: Uuid,
+    pub username: String,
+    pub role: UserRole,
+    pub email: Option<String>,
+    pub full_name: Option<String>,
+    pub phone: Option<String>,
+    pub email_verified: bool,
+    pub mfa_enabled: bool,
+    pub created_at: OffsetDateTime,
+    pub updated_at: OffsetDateTime,
+}
+
+impl From<User> for UserResponse {
+    fn from(user: User) -> Self {
+        UserResponse {
+            id: user.id,
+            username: user.username,
+            role: user.role,
+            email: user.email,
+            full_name: user.full_name,
+            phone: user.phone,
+            email_verified: user.email_verified_at.is_some(),
+            mfa_enabled: user.totp_enabled_at.is_some(),
+            created_at: user.created_at,
+            updated_at: user.updated_at,
+        }
+    }
+}
+
 // For a simplified successful login response (API Response Body)
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AuthResponse {
     pub user_id: Uuid,
     pub username: String,
-    pub token: String,
+    pub role: UserRole,
+    pub token: String,         // Short-lived access token
+    pub expires_in: i64,       // Access token lifetime in seconds
+    pub refresh_token: String, // Single-use token for `/token/refresh`
 }
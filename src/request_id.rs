@@ -0,0 +1,58 @@
+// src/request_id.rs
+
+use axum::{extract::Request, middleware::Next, response::Response};
+use http::HeaderValue;
+use tracing::Instrument;
+use uuid::Uuid;
+
+/// Header carrying the request id, both on the way in and on the way out.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// Middleware that gives every request an id, so a client reporting an error can
+/// be matched with our logs.
+///
+/// An id sent by a proxy or client in `X-Request-Id` is kept if it looks sane;
+/// otherwise a new one is generated. The id is echoed back in the response header,
+/// added to the tracing span of the request and included in error bodies.
+pub async fn request_id(request: Request, next: Next) -> Response {
+    let id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| is_valid_request_id(id))
+        .map(str::to_owned)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    let span = tracing::info_span!(
+        "request",
+        request_id = %id,
+        method = %request.method(),
+        path = %request.uri().path(),
+    );
+
+    let mut response = REQUEST_ID
+        .scope(id.clone(), next.run(request).instrument(span))
+        .await;
+
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+
+    response
+}
+
+/// The id of the request being handled, if called from within one.
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(Clone::clone).ok()
+}
+
+fn is_valid_request_id(id: &str) -> bool {
+    (1..=64).contains(&id.len())
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
@@ -0,0 +1,228 @@
+// src/validation.rs
+
+use bigdecimal::{BigDecimal, Zero};
+
+use crate::errors::{AppError, FieldError};
+
+/// Shortest password we accept.
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+/// Longest password we accept, which also bounds the cost of hashing it.
+pub const MAX_PASSWORD_LENGTH: usize = 128;
+
+/// Largest amount a `DECIMAL(10, 2)` column can hold.
+const MAX_AMOUNT: i64 = 100_000_000;
+
+/// Rules a request body must satisfy beyond deserializing.
+///
+/// Implementations report every failing field to the `Validator` rather than
+/// stopping at the first one, so clients can fix a form in one round trip.
+/// Bodies whose types already say everything keep the default, which checks nothing.
+pub trait Validate {
+    fn validate(&self, _validator: &mut Validator) {}
+}
+
+/// Collects field errors while a request body is validated.
+#[derive(Debug, Default)]
+pub struct Validator {
+    errors: Vec<FieldError>,
+}
+
+impl Validator {
+    /// Records an error for `field` unless `valid` holds.
+    pub fn check(&mut self, valid: bool, field: &str, code: &'static str, message: &str) {
+        if !valid {
+            self.errors.push(FieldError::new(field, code, message));
+        }
+    }
+
+    /// Checks that a string has between `min` and `max` characters, ignoring
+    /// surrounding whitespace when checking it is not blank.
+    pub fn length(&mut self, field: &str, value: &str, min: usize, max: usize) {
+        let length = value.chars().count();
+        if value.trim().is_empty() && min > 0 {
+            self.errors
+                .push(FieldError::new(field, "required", "Must not be blank"));
+        } else if length < min || length > max {
+            self.errors.push(FieldError::new(
+                field,
+                "length",
+                format!("Must be between {} and {} characters", min, max),
+            ));
+        }
+    }
+
+    /// Checks that an amount of money is positive, in cents and fits in the database.
+    pub fn amount(&mut self, field: &str, value: &BigDecimal) {
+        if *value <= BigDecimal::zero() {
+            self.errors
+                .push(FieldError::new(field, "range", "Must be greater than zero"));
+        } else {
+            self.amount_bounds(field, value);
+        }
+    }
+
+    /// Like `amount`, but also accepts zero, e.g. for an optional deposit.
+    pub fn non_negative_amount(&mut self, field: &str, value: &BigDecimal) {
+        if *value < BigDecimal::zero() {
+            self.errors
+                .push(FieldError::new(field, "range", "Must not be negative"));
+        } else {
+            self.amount_bounds(field, value);
+        }
+    }
+
+    fn amount_bounds(&mut self, field: &str, value: &BigDecimal) {
+        if *value >= BigDecimal::from(MAX_AMOUNT) {
+            self.errors.push(FieldError::new(
+                field,
+                "range",
+                format!("Must be less than {}", MAX_AMOUNT),
+            ));
+        } else if value.normalized().fractional_digit_count() > 2 {
+            self.errors.push(FieldError::new(
+                field,
+                "precision",
+                "Must have at most 2 decimal places",
+            ));
+        }
+    }
+
+    /// Checks that a percentage is more than 0 and at most 100, in hundredths.
+    pub fn percentage(&mut self, field: &str, value: &BigDecimal) {
+        if *value <= BigDecimal::zero() || *value > BigDecimal::from(100) {
+            self.errors.push(FieldError::new(
+                field,
+                "range",
+                "Must be more than 0 and at most 100",
+            ));
+        } else if value.normalized().fractional_digit_count() > 2 {
+            self.errors.push(FieldError::new(
+                field,
+                "precision",
+                "Must have at most 2 decimal places",
+            ));
+        }
+    }
+
+    /// Checks that a currency is written as an ISO 4217 code.
+    pub fn currency(&mut self, field: &str, value: &str) {
+        self.check(
+            value.len() == 3 && value.chars().all(|c| c.is_ascii_uppercase()),
+            field,
+            "format",
+            "Must be a three-letter ISO 4217 code, e.g. USD",
+        );
+    }
+
+    /// Checks that a string is plausibly an email address. Whether it really is one
+    /// is only known once the verification email arrives.
+    pub fn email(&mut self, field: &str, value: &str) {
+        let plausible = value.len() <= 254
+            && value.split_once('@').is_some_and(|(local, domain)| {
+                !local.is_empty()
+                    && domain.contains('.')
+                    && !domain.starts_with('.')
+                    && !domain.ends_with('.')
+                    && !domain.contains('@')
+            })
+            && !value.chars().any(char::is_whitespace);
+        self.check(plausible, field, "format", "Must be a valid email address");
+    }
+
+    /// Checks a new password against the length policy.
+    pub fn password(&mut self, field: &str, value: &str) {
+        self.length(field, value, MIN_PASSWORD_LENGTH, MAX_PASSWORD_LENGTH);
+    }
+
+    /// Returns the collected errors as a `422 Unprocessable Entity`, if there are any.
+    pub fn finish(self) -> Result<(), AppError> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::Validation(self.errors))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn codes(check: impl FnOnce(&mut Validator)) -> Vec<&'static str> {
+        let mut validator = Validator::default();
+        check(&mut validator);
+        validator.errors.iter().map(|error| error.code).collect()
+    }
+
+    fn money(amount: &str) -> BigDecimal {
+        BigDecimal::from_str(amount).unwrap()
+    }
+
+    #[test]
+    fn amounts_must_be_positive_whole_cents_that_fit_the_column() {
+        for (amount, expected) in [
+            ("0.01", vec![]),
+            ("1250.50", vec![]),
+            ("99999999.99", vec![]),
+            ("0", vec!["range"]),
+            ("-5", vec!["range"]),
+            ("100000000", vec!["range"]),
+            ("10.005", vec!["precision"]),
+            ("10.500", vec![]),
+        ] {
+            assert_eq!(
+                codes(|v| v.amount("amount", &money(amount))),
+                expected,
+                "{}",
+                amount
+            );
+        }
+        assert_eq!(
+            codes(|v| v.non_negative_amount("deposit", &money("0"))),
+            Vec::<&str>::new()
+        );
+        assert_eq!(
+            codes(|v| v.non_negative_amount("deposit", &money("-0.01"))),
+            ["range"]
+        );
+    }
+
+    #[test]
+    fn percentages_are_more_than_zero_and_at_most_a_hundred() {
+        for (percentage, expected) in [
+            ("0.01", vec![]),
+            ("100", vec![]),
+            ("0", vec!["range"]),
+            ("100.01", vec!["range"]),
+            ("5.125", vec!["precision"]),
+        ] {
+            assert_eq!(
+                codes(|v| v.percentage("rate", &money(percentage))),
+                expected,
+                "{}",
+                percentage
+            );
+        }
+    }
+
+    #[test]
+    fn currencies_are_three_upper_case_letters() {
+        assert!(codes(|v| v.currency("currency", "USD")).is_empty());
+        for currency in ["usd", "US", "USDX", "U5D", ""] {
+            assert_eq!(
+                codes(|v| v.currency("currency", currency)),
+                ["format"],
+                "{:?}",
+                currency
+            );
+        }
+    }
+
+    #[test]
+    fn lengths_count_characters_and_refuse_blanks() {
+        assert!(codes(|v| v.length("memo", "héllo", 1, 5)).is_empty());
+        assert_eq!(codes(|v| v.length("memo", "   ", 1, 5)), ["required"]);
+        assert_eq!(codes(|v| v.length("memo", "toolong", 1, 5)), ["length"]);
+    }
+}